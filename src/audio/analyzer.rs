@@ -1,12 +1,17 @@
-use std::collections::VecDeque;
+use realfft::RealFftPlanner;
 
-use realfft::{RealFftPlanner, RealToComplex};
-use rustfft::num_complex::Complex;
+use std::path::Path;
 
+use crate::audio::cache::AnalysisCache;
+use crate::audio::features::{self, ChromaMap, MelFilterbank, NUM_MEL_FILTERS, NUM_MFCC};
+use crate::audio::meter;
+use crate::audio::structure;
+use crate::audio::loader::AudioLoader;
+use crate::audio::tempo;
 use crate::audio::types::{
     AudioData, AudioAnalysis, Beat, BeatType, EnergyLevel,
-    TempoMap, TimeSignature, Phrase, PhraseType, SpectralFeatures,
-    AnalysisConfig, TempoChange
+    TempoChange, TempoMap, TimeSignature, Phrase, SpectralFeatures,
+    AnalysisConfig
 };
 use crate::error::{AudioError, Result};
 
@@ -26,6 +31,41 @@ impl AudioAnalyzer {
         Self { config }
     }
 
+    /// Load and analyze an audio file, reusing a cached result when possible
+    ///
+    /// Cache problems only cost a fresh analysis; they are logged, never
+    /// returned as errors.
+    pub async fn analyze_file<P: AsRef<Path>>(&self, path: P, cache: Option<&AnalysisCache>) -> Result<AudioAnalysis> {
+        let path = path.as_ref();
+
+        let key = match cache {
+            Some(_) => AnalysisCache::key_for_file(path, &self.config)
+                .map_err(|e| tracing::warn!("Could not hash audio for the analysis cache: {}", e))
+                .ok(),
+            None => None,
+        };
+
+        if let (Some(cache), Some(key)) = (cache, &key) {
+            if let Some(analysis) = cache.load(key) {
+                tracing::info!("Using cached analysis for {:?}", path);
+                return Ok(analysis);
+            }
+        }
+
+        tracing::debug!("Loading audio from: {:?}", path);
+        let audio_data = AudioLoader::load(path).await?;
+        let analysis = self.analyze(&audio_data).await?;
+
+        if let (Some(cache), Some(key)) = (cache, &key) {
+            match cache.store(key, &analysis) {
+                Ok(()) => tracing::debug!("Cached analysis in {:?}", cache.dir()),
+                Err(e) => tracing::warn!("Could not cache audio analysis: {}", e),
+            }
+        }
+
+        Ok(analysis)
+    }
+
     /// Perform comprehensive audio analysis
     pub async fn analyze(&self, audio_data: &AudioData) -> Result<AudioAnalysis> {
         // Validate configuration
@@ -46,18 +86,35 @@ impl AudioAnalyzer {
         tracing::debug!("Performing onset detection...");
         let (onsets, onset_detection_function) = self.detect_onsets(&mono_samples, audio_data.sample_rate)?;
 
-        // Step 3: Beat tracking from onsets
+        // Step 3: Local tempo from windowed autocorrelation of the onset function
+        tracing::debug!("Tracking local tempo...");
+        let frame_rate = audio_data.sample_rate as f64 / self.config.hop_size as f64;
+        let tempo_curve = tempo::local_tempo(
+            &onset_detection_function,
+            frame_rate,
+            self.config.min_bpm,
+            self.config.max_bpm,
+        );
+
+        // Step 4: Beat tracking that follows the local tempo
         tracing::debug!("Tracking beats from onsets...");
-        let beats = self.track_beats(&onsets, &energy_levels)?;
+        let mut beats = self.track_beats(&onsets, &onset_detection_function, frame_rate, &tempo_curve, &energy_levels)?;
+
+        // Step 5: Meter and bar phase mark downbeats and offbeats
+        tracing::debug!("Inferring meter...");
+        let meter = meter::detect(&beats, &onset_detection_function, frame_rate);
+        meter::mark_beats(&mut beats, &meter);
 
-        // Step 4: Tempo estimation
+        // Step 6: Tempo estimation
         tracing::debug!("Estimating tempo...");
-        let tempo = self.estimate_tempo(&beats, audio_data.duration)?;
+        let mut tempo = self.estimate_tempo(&beats, &tempo_curve)?;
+        tempo.time_signature = meter.time_signature;
 
-        // Step 5: Optional spectral features
-        let spectral_features = if self.config.calculate_spectral_features {
+        // Step 7: Spectral features (structure detection needs chroma and MFCC too)
+        let needs_features = self.config.calculate_spectral_features || self.config.detect_phrases;
+        let features = if needs_features {
             tracing::debug!("Calculating spectral features...");
-            self.calculate_spectral_features(&mono_samples, audio_data.sample_rate)?
+            self.calculate_spectral_features(&mono_samples, audio_data.sample_rate, onset_detection_function)?
         } else {
             SpectralFeatures {
                 mfcc: vec![],
@@ -68,14 +125,27 @@ impl AudioAnalyzer {
             }
         };
 
-        // Step 6: Optional phrase detection
+        // Step 8: Optional phrase detection
         let phrases = if self.config.detect_phrases {
             tracing::debug!("Detecting musical phrases...");
-            self.detect_phrases(&beats, &energy_levels, audio_data.duration)?
+            self.detect_phrases(&features, audio_data.sample_rate, &energy_levels, audio_data.duration)?
         } else {
             vec![]
         };
 
+        // Only report the full feature set when it was asked for
+        let spectral_features = if self.config.calculate_spectral_features {
+            features
+        } else {
+            SpectralFeatures {
+                mfcc: vec![],
+                spectral_centroid: vec![],
+                spectral_rolloff: vec![],
+                chroma: vec![],
+                onset_detection_function: features.onset_detection_function,
+            }
+        };
+
         tracing::info!(
             "Analysis complete: {} beats detected, BPM: {:.1}, confidence: {:.2}",
             beats.len(),
@@ -85,6 +155,7 @@ impl AudioAnalyzer {
 
         Ok(AudioAnalysis {
             beats,
+            onsets,
             tempo: tempo.clone(),
             energy_levels,
             bpm: tempo.global_bpm,
@@ -163,9 +234,7 @@ impl AudioAnalyzer {
 
             // Zero-pad if necessary
             if window.len() < self.config.window_size {
-                for i in window.len()..self.config.window_size {
-                    input_buffer[i] = 0.0;
-                }
+                input_buffer[window.len()..].fill(0.0);
             }
 
             // Perform FFT
@@ -250,12 +319,27 @@ impl AudioAnalyzer {
         Ok((onsets, spectral_flux))
     }
 
-    /// Track beats from detected onsets
-    fn track_beats(&self, onsets: &[f64], energy_levels: &[EnergyLevel]) -> Result<Vec<Beat>> {
+    /// Track beats from the onset function, falling back to raw onsets and energy peaks
+    fn track_beats(
+        &self,
+        onsets: &[f64],
+        onset_detection_function: &[f32],
+        frame_rate: f64,
+        tempo_curve: &[TempoChange],
+        energy_levels: &[EnergyLevel],
+    ) -> Result<Vec<Beat>> {
         let mut beats = Vec::new();
 
-        // If we have onsets, use them
-        if !onsets.is_empty() {
+        // Dynamic programming over the onset function, one beat per local period
+        if !tempo_curve.is_empty() {
+            let times = tempo::track_beats(onset_detection_function, frame_rate, tempo_curve);
+            if times.len() >= 2 {
+                beats = self.beats_at_times(&times, energy_levels);
+            }
+        }
+
+        // Otherwise use the detected onsets directly
+        if beats.is_empty() && !onsets.is_empty() {
             beats = self.track_beats_from_onsets(onsets, energy_levels)?;
         }
 
@@ -271,8 +355,6 @@ impl AudioAnalyzer {
 
     /// Track beats from detected onsets
     fn track_beats_from_onsets(&self, onsets: &[f64], energy_levels: &[EnergyLevel]) -> Result<Vec<Beat>> {
-        let mut beats = Vec::new();
-
         // Filter onsets to remove those too close together
         let min_beat_interval = 60.0 / self.config.max_bpm as f64; // Minimum time between beats
         let mut filtered_onsets = Vec::new();
@@ -285,8 +367,14 @@ impl AudioAnalyzer {
             }
         }
 
-        // Convert filtered onsets to beats with additional metadata
-        for (i, &time) in filtered_onsets.iter().enumerate() {
+        Ok(self.beats_at_times(&filtered_onsets, energy_levels))
+    }
+
+    /// Attach energy, strength and beat type to beat times
+    fn beats_at_times(&self, times: &[f64], energy_levels: &[EnergyLevel]) -> Vec<Beat> {
+        let mut beats = Vec::with_capacity(times.len());
+
+        for &time in times {
             // Find energy level at this time
             let local_energy = energy_levels
                 .iter()
@@ -297,23 +385,17 @@ impl AudioAnalyzer {
             // Calculate beat strength based on local energy and onset prominence
             let strength = (local_energy * 2.0).min(1.0);
 
-            // Simple beat type classification (every 4th beat is a downbeat)
-            let beat_type = if i % 4 == 0 {
-                BeatType::Downbeat
-            } else {
-                BeatType::Beat
-            };
-
+            // Downbeats and offbeats are marked later from the inferred meter
             beats.push(Beat {
                 time,
                 strength,
-                beat_type,
+                beat_type: BeatType::Beat,
                 onset_value: strength, // Using strength as onset value for now
                 local_energy,
             });
         }
 
-        Ok(beats)
+        beats
     }
 
     /// Fallback: Track beats from energy levels when onset detection fails
@@ -353,16 +435,10 @@ impl AudioAnalyzer {
                 if is_local_max && energy.time - last_beat_time >= min_beat_interval {
                     let strength = ((energy.rms - mean_energy) / (max_energy - mean_energy)).min(1.0);
 
-                    let beat_type = if beats.len() % 4 == 0 {
-                        BeatType::Downbeat
-                    } else {
-                        BeatType::Beat
-                    };
-
                     beats.push(Beat {
                         time: energy.time,
                         strength,
-                        beat_type,
+                        beat_type: BeatType::Beat,
                         onset_value: energy.rms,
                         local_energy: energy.rms,
                     });
@@ -376,8 +452,40 @@ impl AudioAnalyzer {
         Ok(beats)
     }
 
-    /// Estimate tempo using inter-beat interval analysis
-    fn estimate_tempo(&self, beats: &[Beat], duration: f64) -> Result<TempoMap> {
+    /// Estimate tempo from the local tempo curve, or from inter-beat intervals without one
+    fn estimate_tempo(&self, beats: &[Beat], tempo_curve: &[TempoChange]) -> Result<TempoMap> {
+        if !tempo_curve.is_empty() {
+            // Confidence-weighted median of the window tempos
+            let mut points: Vec<&TempoChange> = tempo_curve.iter().collect();
+            points.sort_by(|a, b| a.bpm.total_cmp(&b.bpm));
+
+            let total_weight: f32 = points.iter().map(|p| p.confidence.max(1e-3)).sum();
+            let mut cumulative = 0.0;
+            let global_bpm = points
+                .iter()
+                .find(|p| {
+                    cumulative += p.confidence.max(1e-3);
+                    cumulative >= total_weight / 2.0
+                })
+                .map(|p| p.bpm)
+                .unwrap_or(120.0);
+
+            let confidence = tempo_curve.iter().map(|p| p.confidence).sum::<f32>() / tempo_curve.len() as f32;
+            let tempo_changes = tempo::tempo_changes(tempo_curve);
+
+            tracing::debug!(
+                "Tempo tracking: {:.1} BPM (confidence: {:.2}) with {} tempo changes",
+                global_bpm, confidence, tempo_changes.len()
+            );
+
+            return Ok(TempoMap {
+                global_bpm,
+                confidence: confidence.clamp(0.0, 1.0),
+                tempo_changes,
+                time_signature: TimeSignature::default(),
+            });
+        }
+
         if beats.len() < 2 {
             return Ok(TempoMap {
                 global_bpm: 120.0, // Default fallback
@@ -442,21 +550,31 @@ impl AudioAnalyzer {
         Ok(TempoMap {
             global_bpm: global_bpm as f32,
             confidence,
-            tempo_changes: vec![], // Future feature
+            tempo_changes: vec![],
             time_signature: TimeSignature::default(),
         })
     }
 
     /// Calculate spectral features for advanced analysis
-    fn calculate_spectral_features(&self, samples: &[f32], sample_rate: u32) -> Result<SpectralFeatures> {
+    fn calculate_spectral_features(
+        &self,
+        samples: &[f32],
+        sample_rate: u32,
+        onset_detection_function: Vec<f32>,
+    ) -> Result<SpectralFeatures> {
         // Create a new FFT planner for this analysis
         let mut planner = RealFftPlanner::new();
         let fft = planner.plan_fft_forward(self.config.window_size);
         let mut spectrum_buffer = fft.make_output_vec();
         let mut input_buffer = fft.make_input_vec();
 
+        let filterbank = MelFilterbank::new(NUM_MEL_FILTERS, self.config.window_size, sample_rate);
+        let chroma_map = ChromaMap::new(self.config.window_size, sample_rate);
+
         let mut spectral_centroids = Vec::new();
         let mut spectral_rolloffs = Vec::new();
+        let mut mfccs = Vec::new();
+        let mut chromas = Vec::new();
 
         // Process audio in windows
         for window in samples
@@ -471,9 +589,7 @@ impl AudioAnalyzer {
 
             // Zero-pad if necessary
             if window.len() < self.config.window_size {
-                for i in window.len()..self.config.window_size {
-                    input_buffer[i] = 0.0;
-                }
+                input_buffer[window.len()..].fill(0.0);
             }
 
             // Perform FFT
@@ -519,55 +635,33 @@ impl AudioAnalyzer {
 
             let spectral_rolloff = (rolloff_bin as f32 / magnitude.len() as f32) * (sample_rate as f32 / 2.0);
             spectral_rolloffs.push(spectral_rolloff);
+
+            // MFCC from the mel filterbank, chroma by folding bins onto pitch classes
+            let power: Vec<f32> = magnitude.iter().map(|&m| m * m).collect();
+            mfccs.push(features::mfcc(&filterbank.apply(&power), NUM_MFCC));
+            chromas.push(chroma_map.apply(&power));
         }
 
         Ok(SpectralFeatures {
-            mfcc: vec![], // MFCC calculation would be more complex
+            mfcc: mfccs,
             spectral_centroid: spectral_centroids,
             spectral_rolloff: spectral_rolloffs,
-            chroma: vec![], // Chroma features would require additional processing
-            onset_detection_function: vec![], // Already calculated in onset detection
+            chroma: chromas,
+            onset_detection_function,
         })
     }
 
-    /// Detect musical phrases and sections
-    fn detect_phrases(&self, beats: &[Beat], energy_levels: &[EnergyLevel], duration: f64) -> Result<Vec<Phrase>> {
-        let mut phrases = Vec::new();
-
-        if beats.is_empty() {
-            return Ok(phrases);
-        }
+    /// Detect musical phrases and sections from the song's self-similarity
+    fn detect_phrases(
+        &self,
+        features: &SpectralFeatures,
+        sample_rate: u32,
+        energy_levels: &[EnergyLevel],
+        duration: f64,
+    ) -> Result<Vec<Phrase>> {
+        let frame_rate = sample_rate as f64 / self.config.hop_size as f64;
 
-        // Simple phrase detection based on energy changes and beat patterns
-        let phrase_length = 8.0; // Assume 8-second phrases initially
-        let mut current_start = 0.0;
-
-        while current_start < duration {
-            let phrase_end = (current_start + phrase_length).min(duration);
-
-            // Determine phrase type based on position and energy
-            let phrase_type = if current_start < duration * 0.1 {
-                PhraseType::Intro
-            } else if current_start > duration * 0.9 {
-                PhraseType::Outro
-            } else {
-                // Simple alternating pattern for demo
-                if ((current_start / phrase_length) as usize) % 2 == 0 {
-                    PhraseType::Verse
-                } else {
-                    PhraseType::Chorus
-                }
-            };
-
-            phrases.push(Phrase {
-                start: current_start,
-                end: phrase_end,
-                phrase_type,
-                confidence: 0.6, // Placeholder confidence
-            });
-
-            current_start = phrase_end;
-        }
+        let phrases = structure::segment(&features.chroma, &features.mfcc, frame_rate, energy_levels, duration);
 
         tracing::debug!("Detected {} musical phrases", phrases.len());
         Ok(phrases)
@@ -583,7 +677,7 @@ impl Default for AudioAnalyzer {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::audio::types::AudioFormat;
+    use crate::audio::types::{AudioFormat, PhraseType};
     use std::path::PathBuf;
 
     fn create_test_audio_data() -> AudioData {
@@ -598,11 +692,15 @@ mod tests {
             })
             .collect();
 
+        test_audio(samples, sample_rate)
+    }
+
+    fn test_audio(samples: Vec<f32>, sample_rate: u32) -> AudioData {
         AudioData {
+            duration: samples.len() as f64 / sample_rate as f64,
             samples,
             sample_rate,
             channels: 1,
-            duration,
             file_path: PathBuf::from("test.wav"),
             format: AudioFormat {
                 extension: "wav".to_string(),
@@ -613,6 +711,18 @@ mod tests {
         }
     }
 
+    /// Short decaying clicks at each `(time, amplitude)` onset
+    fn click_track(onsets: &[(f64, f32)], duration: f64, sample_rate: u32) -> Vec<f32> {
+        let mut samples = vec![0.0f32; (sample_rate as f64 * duration) as usize];
+        for &(time, amplitude) in onsets {
+            let start = (time * sample_rate as f64) as usize;
+            for (i, sample) in samples[start..start + 200].iter_mut().enumerate() {
+                *sample = amplitude * (i as f32 * 0.9).sin() * (-(i as f32) / 40.0).exp();
+            }
+        }
+        samples
+    }
+
     #[tokio::test]
     async fn test_audio_analysis() {
         let audio_data = create_test_audio_data();
@@ -642,6 +752,131 @@ mod tests {
         assert!(energy_levels.iter().all(|e| e.rms >= 0.0));
     }
 
+    #[tokio::test]
+    async fn test_phrases_follow_song_structure() {
+        // Alternating quiet C major and loud F# major sections, 10 seconds each
+        let sample_rate = 11025u32;
+        let sections: [(&[f32], f32); 4] = [
+            (&[261.63, 329.63, 392.0], 0.1),
+            (&[369.99, 466.16, 554.37], 0.4),
+            (&[261.63, 329.63, 392.0], 0.1),
+            (&[369.99, 466.16, 554.37], 0.4),
+        ];
+        let samples: Vec<f32> = sections
+            .iter()
+            .flat_map(|&(notes, amplitude)| {
+                (0..sample_rate * 10).map(move |i| {
+                    let t = i as f32 / sample_rate as f32;
+                    notes.iter().map(|f| (2.0 * std::f32::consts::PI * f * t).sin()).sum::<f32>() * amplitude / 3.0
+                })
+            })
+            .collect();
+
+        let audio_data = test_audio(samples, sample_rate);
+
+        let analysis = AudioAnalyzer::new().analyze(&audio_data).await.unwrap();
+        let phrases = &analysis.phrases;
+
+        for boundary in [10.0, 20.0, 30.0] {
+            assert!(phrases.iter().any(|p| (p.start - boundary).abs() <= 1.0), "no boundary near {}", boundary);
+        }
+        for phrase in phrases.iter().filter(|p| (9.0..11.0).contains(&p.start) || (29.0..31.0).contains(&p.start)) {
+            assert_eq!(phrase.phrase_type, PhraseType::Chorus);
+        }
+        assert!(phrases.iter().any(|p| p.confidence != 0.6));
+    }
+
+    #[tokio::test]
+    async fn test_tempo_drift_is_tracked() {
+        // Click track speeding up from 100 to 125 BPM over 40 seconds
+        let sample_rate = 11025;
+        let duration = 40.0;
+        let mut clicks = Vec::new();
+        let mut time = 0.25;
+        while time < duration - 0.1 {
+            clicks.push(time);
+            time += 60.0 / (100.0 + 25.0 * time / duration);
+        }
+
+        let onsets: Vec<(f64, f32)> = clicks.iter().map(|&c| (c, 1.0)).collect();
+        let audio_data = test_audio(click_track(&onsets, duration, sample_rate), sample_rate);
+
+        let analysis = AudioAnalyzer::with_config(AnalysisConfig::fast()).analyze(&audio_data).await.unwrap();
+
+        assert!(analysis.tempo.tempo_changes.len() >= 2);
+        assert!(analysis.tempo.tempo_changes.iter().all(|c| (0.0..=1.0).contains(&c.confidence)));
+        assert!((analysis.tempo_at_time(5.0) - 103.0).abs() < 5.0, "{}", analysis.tempo_at_time(5.0));
+        assert!((analysis.tempo_at_time(35.0) - 122.0).abs() < 5.0, "{}", analysis.tempo_at_time(35.0));
+
+        // Beats stay on the clicks as the tempo drifts
+        let late_clicks: Vec<f64> = clicks.into_iter().filter(|&c| c > 30.0).collect();
+        let on_beat = late_clicks
+            .iter()
+            .filter(|&&click| analysis.beats.iter().any(|b| (b.time - click).abs() < 0.05))
+            .count();
+        assert!(on_beat as f64 >= late_clicks.len() as f64 * 0.9, "{} of {}", on_beat, late_clicks.len());
+    }
+
+    #[tokio::test]
+    async fn test_waltz_meter_and_downbeats() {
+        // 150 BPM clicks, loud on the "1" of every three
+        let sample_rate = 11025;
+        let duration = 30.0;
+        let onsets: Vec<(f64, f32)> = (0..((duration - 0.5) / 0.4) as usize)
+            .map(|i| (0.2 + i as f64 * 0.4, if i % 3 == 0 { 1.0 } else { 0.3 }))
+            .collect();
+        let accented: Vec<f64> = onsets.iter().filter(|o| o.1 == 1.0).map(|o| o.0).collect();
+
+        let audio_data = test_audio(click_track(&onsets, duration, sample_rate), sample_rate);
+
+        let analysis = AudioAnalyzer::with_config(AnalysisConfig::fast()).analyze(&audio_data).await.unwrap();
+
+        assert_eq!(analysis.tempo.time_signature.beats_per_measure, 3);
+        assert_eq!(analysis.tempo.time_signature.beat_note_value, 4);
+
+        let downbeats: Vec<f64> = analysis.beats.iter()
+            .filter(|b| b.beat_type == BeatType::Downbeat)
+            .map(|b| b.time)
+            .collect();
+        assert!(downbeats.len() >= 20);
+        assert!(downbeats.iter().all(|&d| accented.iter().any(|&a| (a - d).abs() < 0.05)));
+        assert!(analysis.beats.iter().any(|b| b.beat_type == BeatType::Offbeat));
+    }
+
+    #[tokio::test]
+    async fn test_spectral_features_are_populated() {
+        let audio_data = create_test_audio_data();
+        let analysis = AudioAnalyzer::new().analyze(&audio_data).await.unwrap();
+        let features = &analysis.spectral_features;
+
+        let frames = features.spectral_centroid.len();
+        assert!(frames > 0);
+        assert_eq!(features.mfcc.len(), frames);
+        assert_eq!(features.chroma.len(), frames);
+        assert!(features.mfcc.iter().all(|c| c.len() == NUM_MFCC));
+        assert!(!features.onset_detection_function.is_empty());
+
+        // The 440 Hz test tone is an A (pitch class 9)
+        let chroma = &features.chroma[frames / 2];
+        assert_eq!(chroma.len(), 12);
+        assert_eq!(chroma[9], 1.0);
+    }
+
+    #[tokio::test]
+    async fn test_onset_function_kept_without_spectral_features() {
+        let audio_data = create_test_audio_data();
+        let config = AnalysisConfig {
+            calculate_spectral_features: false,
+            ..AnalysisConfig::default()
+        };
+
+        let analysis = AudioAnalyzer::with_config(config).analyze(&audio_data).await.unwrap();
+
+        assert!(!analysis.spectral_features.onset_detection_function.is_empty());
+        assert!(analysis.spectral_features.mfcc.is_empty());
+        assert!(!analysis.phrases.is_empty());
+    }
+
     #[test]
     fn test_config_validation() {
         let mut config = AnalysisConfig::default();
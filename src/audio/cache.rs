@@ -0,0 +1,154 @@
+//! On-disk cache of audio analysis results
+//!
+//! Analysing a song is the slowest step before rendering starts, yet the song
+//! rarely changes between runs. Results are stored as JSON under a key built
+//! from the audio file's contents and the analysis settings, so a run with the
+//! same song and settings skips decoding and analysis entirely.
+
+use std::path::{Path, PathBuf};
+
+use crate::audio::types::{AnalysisConfig, AudioAnalysis};
+use crate::error::Result;
+
+/// Bumped whenever `AudioAnalysis` or the analysis itself changes meaning
+const CACHE_VERSION: u32 = 2;
+
+/// 64-bit FNV-1a, stable across builds and platforms unlike `DefaultHasher`
+struct Fnv64(u64);
+
+impl Fnv64 {
+    fn new() -> Self {
+        Self(0xcbf2_9ce4_8422_2325)
+    }
+
+    fn write(&mut self, bytes: &[u8]) {
+        for &byte in bytes {
+            self.0 ^= byte as u64;
+            self.0 = self.0.wrapping_mul(0x0000_0100_0000_01b3);
+        }
+    }
+}
+
+/// Directory of cached analyses
+#[derive(Debug, Clone)]
+pub struct AnalysisCache {
+    dir: PathBuf,
+}
+
+impl AnalysisCache {
+    /// Cache stored in `dir`, created on first write
+    pub fn new<P: Into<PathBuf>>(dir: P) -> Self {
+        Self { dir: dir.into() }
+    }
+
+    /// Cache in the user's cache directory (`$XDG_CACHE_HOME` or `~/.cache`)
+    pub fn default_location() -> Self {
+        let base = std::env::var_os("XDG_CACHE_HOME")
+            .map(PathBuf::from)
+            .or_else(|| std::env::var_os("HOME").map(|home| PathBuf::from(home).join(".cache")))
+            .unwrap_or_else(std::env::temp_dir);
+
+        Self::new(base.join("retro-compositor").join("analysis"))
+    }
+
+    /// Directory the cache lives in
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    /// Cache key for audio content analysed with `config`
+    ///
+    /// Content and settings are hashed separately, so entries for one song
+    /// share a prefix.
+    pub fn key(audio: &[u8], config: &AnalysisConfig) -> String {
+        let mut content = Fnv64::new();
+        content.write(&CACHE_VERSION.to_le_bytes());
+        content.write(audio);
+
+        let mut settings = Fnv64::new();
+        settings.write(&serde_json::to_vec(config).unwrap_or_default());
+
+        format!("{:016x}-{:016x}", content.0, settings.0)
+    }
+
+    /// Cache key for an audio file analysed with `config`
+    pub fn key_for_file<P: AsRef<Path>>(path: P, config: &AnalysisConfig) -> Result<String> {
+        let audio = std::fs::read(path)?;
+        Ok(Self::key(&audio, config))
+    }
+
+    fn entry_path(&self, key: &str) -> PathBuf {
+        self.dir.join(format!("{}.json", key))
+    }
+
+    /// Cached analysis for `key`, if present and readable
+    ///
+    /// Unreadable entries are treated as misses so a corrupt file only costs
+    /// a re-analysis.
+    pub fn load(&self, key: &str) -> Option<AudioAnalysis> {
+        let content = std::fs::read(self.entry_path(key)).ok()?;
+        serde_json::from_slice(&content).ok()
+    }
+
+    /// Store an analysis under `key`
+    pub fn store(&self, key: &str, analysis: &AudioAnalysis) -> Result<()> {
+        std::fs::create_dir_all(&self.dir)?;
+
+        let content = serde_json::to_vec(analysis)
+            .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidData, e))?;
+
+        // Write then rename, so a concurrent run never reads half a file
+        let temp = self.dir.join(format!("{}.json.tmp{}", key, std::process::id()));
+        std::fs::write(&temp, content)?;
+        std::fs::rename(&temp, self.entry_path(key))?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::audio::types::{AnalysisBuilder, BeatType, PhraseType};
+    use tempfile::tempdir;
+
+    fn analysis() -> AudioAnalysis {
+        let mut analysis = AnalysisBuilder::new(8.0)
+            .beat(0.5, BeatType::Downbeat)
+            .phrase(0.0, 8.0, PhraseType::Chorus)
+            .build();
+        analysis.spectral_features.onset_detection_function = vec![0.1, 0.2];
+        analysis
+    }
+
+    #[test]
+    fn test_key_depends_on_content_and_settings() {
+        let config = AnalysisConfig::default();
+        let key = AnalysisCache::key(b"song", &config);
+
+        assert_eq!(key.len(), 33);
+        assert_eq!(key, AnalysisCache::key(b"song", &config));
+        assert_ne!(key, AnalysisCache::key(b"other song", &config));
+        assert_ne!(key, AnalysisCache::key(b"song", &AnalysisConfig::fast()));
+    }
+
+    #[test]
+    fn test_store_and_load() {
+        let dir = tempdir().unwrap();
+        let cache = AnalysisCache::new(dir.path().join("nested"));
+        let key = AnalysisCache::key(b"song", &AnalysisConfig::default());
+
+        assert!(cache.load(&key).is_none());
+        cache.store(&key, &analysis()).unwrap();
+
+        let loaded = cache.load(&key).unwrap();
+        assert_eq!(loaded.beats.len(), 1);
+        assert_eq!(loaded.beats[0].beat_type, BeatType::Downbeat);
+        assert_eq!(loaded.phrases[0].phrase_type, PhraseType::Chorus);
+        assert_eq!(loaded.spectral_features.onset_detection_function, vec![0.1, 0.2]);
+        assert_eq!(loaded.duration, 8.0);
+
+        // A corrupt entry is a miss, not an error
+        std::fs::write(cache.dir().join(format!("{}.json", key)), "{").unwrap();
+        assert!(cache.load(&key).is_none());
+    }
+}
@@ -0,0 +1,215 @@
+//! Timbre and harmony features computed from a power spectrum
+//!
+//! MFCCs come from a triangular mel filterbank followed by a log and a DCT-II.
+//! Chroma folds every spectral bin onto its nearest pitch class (C = 0).
+
+/// Number of triangular filters in the mel filterbank
+pub const NUM_MEL_FILTERS: usize = 40;
+
+/// Number of cepstral coefficients kept per frame
+pub const NUM_MFCC: usize = 13;
+
+/// Number of pitch classes in a chroma vector
+pub const NUM_CHROMA: usize = 12;
+
+/// Lowest frequency considered by the filterbank and chroma folding
+const MIN_HZ: f32 = 27.5;
+
+/// Highest frequency folded into chroma; above this harmonics dominate
+const MAX_CHROMA_HZ: f32 = 5000.0;
+
+fn hz_to_mel(hz: f32) -> f32 {
+    2595.0 * (1.0 + hz / 700.0).log10()
+}
+
+fn mel_to_hz(mel: f32) -> f32 {
+    700.0 * (10f32.powf(mel / 2595.0) - 1.0)
+}
+
+/// Triangular filters spaced evenly on the mel scale
+#[derive(Debug, Clone)]
+pub struct MelFilterbank {
+    /// First FFT bin of each filter and its weights from there on
+    filters: Vec<(usize, Vec<f32>)>,
+}
+
+impl MelFilterbank {
+    /// Build a filterbank for spectra of `fft_size / 2 + 1` bins
+    pub fn new(num_filters: usize, fft_size: usize, sample_rate: u32) -> Self {
+        let num_bins = fft_size / 2 + 1;
+        let bin_hz = sample_rate as f32 / fft_size as f32;
+        let min_mel = hz_to_mel(MIN_HZ);
+        let max_mel = hz_to_mel(sample_rate as f32 / 2.0);
+
+        // Filter edges: num_filters + 2 points evenly spaced in mel
+        let edges: Vec<f32> = (0..num_filters + 2)
+            .map(|i| mel_to_hz(min_mel + (max_mel - min_mel) * i as f32 / (num_filters + 1) as f32))
+            .collect();
+
+        let filters = edges
+            .windows(3)
+            .map(|edge| {
+                let (low, center, high) = (edge[0], edge[1], edge[2]);
+                let first = (low / bin_hz).floor() as usize;
+                let last = ((high / bin_hz).ceil() as usize).min(num_bins - 1);
+
+                let weights = (first..=last)
+                    .map(|bin| {
+                        let hz = bin as f32 * bin_hz;
+                        if hz <= low || hz >= high {
+                            0.0
+                        } else if hz <= center {
+                            (hz - low) / (center - low)
+                        } else {
+                            (high - hz) / (high - center)
+                        }
+                    })
+                    .collect();
+
+                (first, weights)
+            })
+            .collect();
+
+        Self { filters }
+    }
+
+    /// Energy in each mel band of a power spectrum
+    pub fn apply(&self, power_spectrum: &[f32]) -> Vec<f32> {
+        self.filters
+            .iter()
+            .map(|(first, weights)| {
+                weights
+                    .iter()
+                    .zip(power_spectrum.iter().skip(*first))
+                    .map(|(&w, &p)| w * p)
+                    .sum()
+            })
+            .collect()
+    }
+}
+
+/// Mel-frequency cepstral coefficients from mel band energies (log + orthonormal DCT-II)
+pub fn mfcc(mel_energies: &[f32], num_coefficients: usize) -> Vec<f32> {
+    let n = mel_energies.len();
+    if n == 0 {
+        return vec![0.0; num_coefficients];
+    }
+
+    let log_energies: Vec<f32> = mel_energies.iter().map(|&e| (e + 1e-10).ln()).collect();
+
+    (0..num_coefficients)
+        .map(|k| {
+            let scale = if k == 0 { (1.0 / n as f32).sqrt() } else { (2.0 / n as f32).sqrt() };
+            let sum: f32 = log_energies
+                .iter()
+                .enumerate()
+                .map(|(i, &e)| e * (std::f32::consts::PI * k as f32 * (i as f32 + 0.5) / n as f32).cos())
+                .sum();
+            sum * scale
+        })
+        .collect()
+}
+
+/// Folds spectral bins onto the 12 pitch classes
+#[derive(Debug, Clone)]
+pub struct ChromaMap {
+    /// Pitch class of each FFT bin, if it lies in the folded range
+    classes: Vec<Option<usize>>,
+}
+
+impl ChromaMap {
+    /// Build the bin-to-pitch-class map for spectra of `fft_size / 2 + 1` bins
+    pub fn new(fft_size: usize, sample_rate: u32) -> Self {
+        let bin_hz = sample_rate as f32 / fft_size as f32;
+
+        let classes = (0..fft_size / 2 + 1)
+            .map(|bin| {
+                let hz = bin as f32 * bin_hz;
+                if !(MIN_HZ..=MAX_CHROMA_HZ).contains(&hz) {
+                    return None;
+                }
+                let midi = 69.0 + 12.0 * (hz / 440.0).log2();
+                Some((midi.round() as i32).rem_euclid(NUM_CHROMA as i32) as usize)
+            })
+            .collect();
+
+        Self { classes }
+    }
+
+    /// Chroma vector of a power spectrum, scaled so the strongest class is 1.0
+    pub fn apply(&self, power_spectrum: &[f32]) -> Vec<f32> {
+        let mut chroma = vec![0.0f32; NUM_CHROMA];
+
+        for (class, &power) in self.classes.iter().zip(power_spectrum.iter()) {
+            if let Some(class) = class {
+                chroma[*class] += power;
+            }
+        }
+
+        let max = chroma.iter().fold(0.0f32, |acc, &c| acc.max(c));
+        if max > 0.0 {
+            chroma.iter_mut().for_each(|c| *c /= max);
+        }
+
+        chroma
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sine_power_spectrum(frequency: f32, fft_size: usize, sample_rate: u32) -> Vec<f32> {
+        let mut power = vec![0.0; fft_size / 2 + 1];
+        let bin = (frequency * fft_size as f32 / sample_rate as f32).round() as usize;
+        power[bin] = 1.0;
+        power
+    }
+
+    #[test]
+    fn test_chroma_finds_pitch_class() {
+        let map = ChromaMap::new(4096, 44100);
+
+        // A4 = 440 Hz -> pitch class 9
+        let chroma = map.apply(&sine_power_spectrum(440.0, 4096, 44100));
+        assert_eq!(chroma.len(), NUM_CHROMA);
+        assert_eq!(chroma[9], 1.0);
+        assert_eq!(chroma.iter().filter(|&&c| c > 0.0).count(), 1);
+
+        // C5 = 523.25 Hz -> pitch class 0
+        let chroma = map.apply(&sine_power_spectrum(523.25, 4096, 44100));
+        assert_eq!(chroma[0], 1.0);
+    }
+
+    #[test]
+    fn test_mel_filterbank_covers_spectrum() {
+        let bank = MelFilterbank::new(NUM_MEL_FILTERS, 1024, 22050);
+        let flat = vec![1.0; 513];
+
+        let energies = bank.apply(&flat);
+
+        assert_eq!(energies.len(), NUM_MEL_FILTERS);
+        assert!(energies.iter().all(|&e| e > 0.0));
+    }
+
+    #[test]
+    fn test_mfcc_of_flat_spectrum_has_no_shape() {
+        let coefficients = mfcc(&[1.0; NUM_MEL_FILTERS], NUM_MFCC);
+
+        assert_eq!(coefficients.len(), NUM_MFCC);
+        assert!(coefficients[1..].iter().all(|c| c.abs() < 1e-4));
+    }
+
+    #[test]
+    fn test_mfcc_distinguishes_bright_and_dark_spectra() {
+        let bank = MelFilterbank::new(NUM_MEL_FILTERS, 1024, 22050);
+        let dark: Vec<f32> = (0..513).map(|i| 1.0 / (1.0 + i as f32)).collect();
+        let bright: Vec<f32> = (0..513).map(|i| i as f32 / 513.0).collect();
+
+        let dark = mfcc(&bank.apply(&dark), NUM_MFCC);
+        let bright = mfcc(&bank.apply(&bright), NUM_MFCC);
+
+        // The first shape coefficient tracks spectral tilt
+        assert!(dark[1] > bright[1]);
+    }
+}
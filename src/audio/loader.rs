@@ -1,5 +1,4 @@
 use std::fs::File;
-use std::io::BufReader;
 use std::path::Path;
 
 use symphonia::core::audio::{AudioBufferRef, Signal};
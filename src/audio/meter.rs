@@ -0,0 +1,355 @@
+//! Meter inference and bar-phase estimation
+//!
+//! Each tracked beat gets an accent from the onset function. The accent
+//! sequence is correlated against the accent template of every supported meter
+//! at every bar phase; the best match gives the time signature and where the
+//! "1" falls. Phase is re-estimated every few bars so a dropped or doubled beat
+//! doesn't shift every later downbeat.
+
+use crate::audio::types::{Beat, BeatType, TimeSignature};
+
+/// Bars per window when re-estimating the bar phase
+const PHASE_WINDOW_BARS: usize = 4;
+
+/// Correlation gain a window needs before it moves the bar phase
+const PHASE_SWITCH_MARGIN: f32 = 0.2;
+
+/// Template weight at or above which a beat counts as a strong (non-offbeat) beat
+const STRONG_BEAT_WEIGHT: f32 = 0.5;
+
+/// Search radius around a beat when reading its accent, in seconds
+const ACCENT_RADIUS: f64 = 0.05;
+
+/// A meter as it appears at the level of tracked beats
+struct MeterTemplate {
+    time_signature: TimeSignature,
+
+    /// Expected accent of each tracked beat within a bar
+    accents: &'static [f32],
+
+    /// Only considered when beats subdivide in three (compound meter)
+    compound_only: bool,
+}
+
+const TEMPLATES: [MeterTemplate; 5] = [
+    MeterTemplate {
+        time_signature: TimeSignature { beats_per_measure: 4, beat_note_value: 4 },
+        accents: &[1.0, 0.3, 0.6, 0.3],
+        compound_only: false,
+    },
+    MeterTemplate {
+        time_signature: TimeSignature { beats_per_measure: 3, beat_note_value: 4 },
+        accents: &[1.0, 0.3, 0.3],
+        compound_only: false,
+    },
+    // 5/4 counted 3 + 2
+    MeterTemplate {
+        time_signature: TimeSignature { beats_per_measure: 5, beat_note_value: 4 },
+        accents: &[1.0, 0.3, 0.3, 0.6, 0.3],
+        compound_only: false,
+    },
+    // 6/8 tracked at the eighth note
+    MeterTemplate {
+        time_signature: TimeSignature { beats_per_measure: 6, beat_note_value: 8 },
+        accents: &[1.0, 0.2, 0.2, 0.6, 0.2, 0.2],
+        compound_only: false,
+    },
+    // 6/8 tracked at the dotted quarter, each beat split in three
+    MeterTemplate {
+        time_signature: TimeSignature { beats_per_measure: 6, beat_note_value: 8 },
+        accents: &[1.0, 0.4],
+        compound_only: true,
+    },
+];
+
+/// Result of meter inference
+#[derive(Debug, Clone)]
+pub struct MeterAnalysis {
+    /// Inferred time signature
+    pub time_signature: TimeSignature,
+
+    /// Position of each beat within its bar (0 = downbeat)
+    pub bar_positions: Vec<usize>,
+
+    /// Expected accent of each bar position
+    pub accents: Vec<f32>,
+
+    /// How well the accents fit the meter (0.0-1.0)
+    pub confidence: f32,
+}
+
+impl MeterAnalysis {
+    /// Plain 4/4 counted from the first beat, used when there is too little to go on
+    fn common_time(beat_count: usize) -> Self {
+        let accents = TEMPLATES[0].accents.to_vec();
+        Self {
+            time_signature: TimeSignature::default(),
+            bar_positions: (0..beat_count).map(|i| i % accents.len()).collect(),
+            accents,
+            confidence: 0.0,
+        }
+    }
+}
+
+/// Infer the meter and bar phase of tracked beats
+pub fn detect(beats: &[Beat], odf: &[f32], frame_rate: f64) -> MeterAnalysis {
+    let longest_bar = TEMPLATES.iter().map(|t| t.accents.len()).max().unwrap_or(4);
+    if beats.len() < 2 * longest_bar || odf.is_empty() {
+        return MeterAnalysis::common_time(beats.len());
+    }
+
+    let accents: Vec<f32> = beats.iter().map(|beat| accent_at(odf, frame_rate, beat.time)).collect();
+    let compound = has_ternary_subdivision(beats, odf, frame_rate);
+
+    // Best template and phase over the whole song
+    let Some((template, phase, correlation)) = TEMPLATES
+        .iter()
+        .filter(|t| compound || !t.compound_only)
+        .flat_map(|t| (0..t.accents.len()).map(move |phase| (t, phase)))
+        .map(|(t, phase)| (t, phase, phase_correlation(&accents, t.accents, phase)))
+        .max_by(|a, b| a.2.total_cmp(&b.2))
+    else {
+        return MeterAnalysis::common_time(beats.len());
+    };
+
+    if correlation <= 0.0 {
+        return MeterAnalysis::common_time(beats.len());
+    }
+
+    let bar_positions = track_phase(&accents, template.accents, phase);
+
+    tracing::debug!(
+        "Meter: {}/{} (correlation {:.2}, compound subdivision: {})",
+        template.time_signature.beats_per_measure,
+        template.time_signature.beat_note_value,
+        correlation,
+        compound
+    );
+
+    MeterAnalysis {
+        time_signature: template.time_signature.clone(),
+        bar_positions,
+        accents: template.accents.to_vec(),
+        confidence: correlation.clamp(0.0, 1.0),
+    }
+}
+
+/// Mark downbeats, strong beats and offbeats from a meter analysis
+pub fn mark_beats(beats: &mut [Beat], meter: &MeterAnalysis) {
+    for (beat, &position) in beats.iter_mut().zip(meter.bar_positions.iter()) {
+        beat.beat_type = if position == 0 {
+            BeatType::Downbeat
+        } else if meter.accents.get(position).copied().unwrap_or(0.0) >= STRONG_BEAT_WEIGHT {
+            BeatType::Beat
+        } else {
+            BeatType::Offbeat
+        };
+    }
+}
+
+/// Bar position of every beat, letting each window of bars move the phase
+fn track_phase(accents: &[f32], template: &[f32], initial_phase: usize) -> Vec<usize> {
+    let bar = template.len();
+    let window = bar * PHASE_WINDOW_BARS;
+    let mut phase = initial_phase;
+    let mut positions = Vec::with_capacity(accents.len());
+
+    for start in (0..accents.len()).step_by(window) {
+        let end = (start + window).min(accents.len());
+        let local = &accents[start..end];
+
+        if local.len() >= 2 * bar {
+            // Phases here are relative to the window start
+            let relative = |phase: usize| (phase + bar - start % bar) % bar;
+            let current = phase_correlation(local, template, relative(phase));
+            let (best, score) = (0..bar)
+                .map(|p| (p, phase_correlation(local, template, relative(p))))
+                .max_by(|a, b| a.1.total_cmp(&b.1))
+                .unwrap();
+
+            if best != phase && score - current > PHASE_SWITCH_MARGIN {
+                phase = best;
+            }
+        }
+
+        positions.extend((start..end).map(|i| (i + bar - phase) % bar));
+    }
+
+    positions
+}
+
+/// Pearson correlation between accents and the template tiled from `phase`
+///
+/// `phase` is the index of the first downbeat within the first bar.
+fn phase_correlation(accents: &[f32], template: &[f32], phase: usize) -> f32 {
+    let bar = template.len();
+    let expected: Vec<f32> = (0..accents.len()).map(|i| template[(i + bar - phase) % bar]).collect();
+
+    let n = accents.len() as f32;
+    let mean_a = accents.iter().sum::<f32>() / n;
+    let mean_e = expected.iter().sum::<f32>() / n;
+
+    let mut covariance = 0.0;
+    let mut variance_a = 0.0;
+    let mut variance_e = 0.0;
+    for (&a, &e) in accents.iter().zip(expected.iter()) {
+        covariance += (a - mean_a) * (e - mean_e);
+        variance_a += (a - mean_a).powi(2);
+        variance_e += (e - mean_e).powi(2);
+    }
+
+    if variance_a <= 0.0 || variance_e <= 0.0 {
+        return 0.0;
+    }
+
+    covariance / (variance_a * variance_e).sqrt()
+}
+
+/// Strongest onset near a beat
+fn accent_at(odf: &[f32], frame_rate: f64, time: f64) -> f32 {
+    let center = (time * frame_rate).round() as isize;
+    let radius = (ACCENT_RADIUS * frame_rate).ceil() as isize;
+
+    (center - radius..=center + radius)
+        .filter(|&i| i >= 0 && (i as usize) < odf.len())
+        .map(|i| odf[i as usize])
+        .fold(0.0f32, f32::max)
+}
+
+/// Whether onsets between beats fall on thirds rather than halves of the beat
+fn has_ternary_subdivision(beats: &[Beat], odf: &[f32], frame_rate: f64) -> bool {
+    let between = |fraction: f64| -> f32 {
+        let values: Vec<f32> = beats
+            .windows(2)
+            .map(|pair| accent_at(odf, frame_rate, pair[0].time + (pair[1].time - pair[0].time) * fraction))
+            .collect();
+        values.iter().sum::<f32>() / values.len().max(1) as f32
+    };
+
+    let thirds = (between(1.0 / 3.0) + between(2.0 / 3.0)) / 2.0;
+    let half = between(0.5);
+    let on_beat = beats.iter().map(|b| accent_at(odf, frame_rate, b.time)).sum::<f32>() / beats.len() as f32;
+
+    thirds > on_beat * 0.2 && thirds > half * 1.5
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const FRAME_RATE: f64 = 100.0;
+    const BEAT_SECONDS: f64 = 0.5;
+
+    /// Beats every half second with onset heights cycling through `pattern`,
+    /// optionally adding softer onsets at the given fractions of each beat
+    fn accented(pattern: &[f32], bars: usize, offset: usize, subdivisions: &[f64]) -> (Vec<Beat>, Vec<f32>) {
+        let count = pattern.len() * bars;
+        let mut odf = vec![0.0f32; ((count + 1) as f64 * BEAT_SECONDS * FRAME_RATE) as usize];
+        let beats = (0..count)
+            .map(|i| {
+                let time = i as f64 * BEAT_SECONDS;
+                odf[(time * FRAME_RATE) as usize] = pattern[(i + offset) % pattern.len()];
+                for fraction in subdivisions {
+                    odf[((time + fraction * BEAT_SECONDS) * FRAME_RATE) as usize] = 0.3;
+                }
+                Beat {
+                    time,
+                    strength: 0.5,
+                    beat_type: BeatType::Beat,
+                    onset_value: 0.5,
+                    local_energy: 0.5,
+                }
+            })
+            .collect();
+        (beats, odf)
+    }
+
+    fn signature(meter: &MeterAnalysis) -> (u8, u8) {
+        (meter.time_signature.beats_per_measure, meter.time_signature.beat_note_value)
+    }
+
+    #[test]
+    fn test_detects_supported_meters() {
+        // (accent pattern, subdivision onsets, expected signature)
+        type Case = (&'static [f32], &'static [f64], (u8, u8));
+        let cases: [Case; 5] = [
+            (&[1.0, 0.4, 0.7, 0.4], &[], (4, 4)),
+            (&[1.0, 0.4, 0.4], &[], (3, 4)),
+            (&[1.0, 0.4, 0.4, 0.7, 0.4], &[], (5, 4)),
+            (&[1.0, 0.3, 0.3, 0.7, 0.3, 0.3], &[], (6, 8)),
+            (&[1.0, 0.5], &[1.0 / 3.0, 2.0 / 3.0], (6, 8)),
+        ];
+
+        for (pattern, subdivisions, expected) in cases {
+            let (beats, odf) = accented(pattern, 8, 0, subdivisions);
+            let meter = detect(&beats, &odf, FRAME_RATE);
+            assert_eq!(signature(&meter), expected, "pattern {:?}", pattern);
+            assert!(meter.confidence > 0.5);
+        }
+    }
+
+    #[test]
+    fn test_duple_subdivision_is_not_compound() {
+        let (beats, odf) = accented(&[1.0, 0.5], 8, 0, &[0.5]);
+
+        let meter = detect(&beats, &odf, FRAME_RATE);
+
+        assert_eq!(signature(&meter), (4, 4));
+    }
+
+    #[test]
+    fn test_downbeats_follow_bar_phase() {
+        // Waltz that starts on beat 2 of the bar
+        let (mut beats, odf) = accented(&[1.0, 0.4, 0.4], 8, 1, &[]);
+
+        let meter = detect(&beats, &odf, FRAME_RATE);
+        mark_beats(&mut beats, &meter);
+
+        let downbeats: Vec<usize> = (0..beats.len()).filter(|&i| beats[i].beat_type == BeatType::Downbeat).collect();
+        assert_eq!(&downbeats[..3], &[2, 5, 8]);
+        assert_eq!(beats[0].beat_type, BeatType::Offbeat);
+    }
+
+    #[test]
+    fn test_marks_strong_beats_and_offbeats_in_common_time() {
+        let (mut beats, odf) = accented(&[1.0, 0.4, 0.7, 0.4], 4, 0, &[]);
+
+        let meter = detect(&beats, &odf, FRAME_RATE);
+        mark_beats(&mut beats, &meter);
+
+        let types: Vec<BeatType> = beats[..4].iter().map(|b| b.beat_type.clone()).collect();
+        assert_eq!(types, vec![BeatType::Downbeat, BeatType::Offbeat, BeatType::Beat, BeatType::Offbeat]);
+    }
+
+    #[test]
+    fn test_phase_recovers_after_dropped_beat() {
+        let (mut beats, odf) = accented(&[1.0, 0.4, 0.4, 0.4], 12, 0, &[]);
+
+        // Lose one beat in the middle: later downbeats shift by one index
+        beats.remove(22);
+        let meter = detect(&beats, &odf, FRAME_RATE);
+        mark_beats(&mut beats, &meter);
+
+        let late: Vec<usize> = (32..beats.len()).filter(|&i| beats[i].beat_type == BeatType::Downbeat).collect();
+        assert!(late.iter().all(|&i| (i + 1) % 4 == 0), "{:?}", late);
+    }
+
+    #[test]
+    fn test_too_few_beats_defaults_to_common_time() {
+        let (beats, odf) = accented(&[1.0, 0.4, 0.4], 2, 0, &[]);
+
+        let meter = detect(&beats, &odf, FRAME_RATE);
+
+        assert_eq!(signature(&meter), (4, 4));
+        assert_eq!(meter.confidence, 0.0);
+    }
+
+    #[test]
+    fn test_non_finite_onsets_do_not_panic() {
+        let (mut beats, mut odf) = accented(&[1.0, 0.4, 0.6, 0.4], 8, 0, &[]);
+        odf[(4.0 * BEAT_SECONDS * FRAME_RATE) as usize] = f32::INFINITY;
+
+        let meter = detect(&beats, &odf, FRAME_RATE);
+        mark_beats(&mut beats, &meter);
+    }
+}
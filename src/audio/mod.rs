@@ -7,9 +7,10 @@
 //! ## Core Features
 //!
 //! - **Beat Detection**: FFT-based onset detection with configurable sensitivity
-//! - **Tempo Analysis**: BPM calculation using autocorrelation and peak detection
+//! - **Tempo Analysis**: Windowed autocorrelation tempo tracking that follows tempo drift
 //! - **Energy Analysis**: RMS energy calculation for dynamic cut timing
-//! - **Musical Structure**: Phrase and section boundary detection
+//! - **Musical Structure**: Section boundaries from a self-similarity matrix, with
+//!   repeated-section clustering to find verses and choruses
 //!
 //! ## Usage
 //!
@@ -32,9 +33,16 @@
 //! ```
 
 pub mod analyzer;
+pub mod cache;
+pub mod features;
 pub mod loader;
+pub mod meter;
+pub mod report;
+pub mod structure;
+pub mod tempo;
 pub mod types;
 pub use analyzer::AudioAnalyzer;
+pub use cache::AnalysisCache;
 pub use loader::AudioLoader;
 pub use types::{
     AudioData, AudioAnalysis, Beat, EnergyLevel,
@@ -0,0 +1,230 @@
+//! Human- and tool-readable dumps of an analysis
+//!
+//! Used by `retro-compositor analyze` to inspect beat, tempo and section
+//! detection without rendering anything. JSON keeps the structure, CSV
+//! flattens everything into one row per event for spreadsheets and plotting,
+//! and Audacity labels show beats and sections on top of the waveform.
+
+use std::fmt::Write as _;
+
+use serde::Serialize;
+
+use crate::audio::types::{AudioAnalysis, Beat, BeatType, EnergyLevel, Phrase, PhraseType, TempoMap};
+
+/// Output formats for an analysis report
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ReportFormat {
+    /// Nested JSON document
+    Json,
+
+    /// One row per beat, tempo change, energy sample and phrase
+    Csv,
+
+    /// Audacity label track: beats as points, phrases as regions
+    Labels,
+}
+
+impl ReportFormat {
+    /// All available formats
+    pub const ALL: [ReportFormat; 3] = [ReportFormat::Json, ReportFormat::Csv, ReportFormat::Labels];
+
+    /// Parse a format from its name
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.to_lowercase().as_str() {
+            "json" => Some(Self::Json),
+            "csv" => Some(Self::Csv),
+            "labels" | "audacity" | "txt" => Some(Self::Labels),
+            _ => None,
+        }
+    }
+
+    /// Canonical name of the format
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Json => "json",
+            Self::Csv => "csv",
+            Self::Labels => "labels",
+        }
+    }
+
+    /// Render `analysis` in this format
+    pub fn render(&self, analysis: &AudioAnalysis) -> String {
+        match self {
+            Self::Json => to_json(analysis),
+            Self::Csv => to_csv(analysis),
+            Self::Labels => to_labels(analysis),
+        }
+    }
+}
+
+/// The parts of an analysis worth looking at; spectral features are left out
+#[derive(Serialize)]
+struct Report<'a> {
+    duration: f64,
+    bpm: f32,
+    bpm_confidence: f32,
+    tempo: &'a TempoMap,
+    beats: &'a [Beat],
+    energy: &'a [EnergyLevel],
+    phrases: &'a [Phrase],
+}
+
+fn beat_type_name(beat_type: &BeatType) -> &'static str {
+    match beat_type {
+        BeatType::Downbeat => "downbeat",
+        BeatType::Beat => "beat",
+        BeatType::Offbeat => "offbeat",
+        BeatType::Onset => "onset",
+    }
+}
+
+fn phrase_type_name(phrase_type: PhraseType) -> &'static str {
+    match phrase_type {
+        PhraseType::Intro => "intro",
+        PhraseType::Verse => "verse",
+        PhraseType::Chorus => "chorus",
+        PhraseType::Bridge => "bridge",
+        PhraseType::Outro => "outro",
+        PhraseType::Unknown => "unknown",
+    }
+}
+
+/// Pretty-printed JSON with beats, tempo map, energy curve and phrases
+pub fn to_json(analysis: &AudioAnalysis) -> String {
+    let report = Report {
+        duration: analysis.duration,
+        bpm: analysis.bpm,
+        bpm_confidence: analysis.bpm_confidence,
+        tempo: &analysis.tempo,
+        beats: &analysis.beats,
+        energy: &analysis.energy_levels,
+        phrases: &analysis.phrases,
+    };
+
+    serde_json::to_string_pretty(&report).unwrap_or_default()
+}
+
+/// CSV with columns `kind,start,end,value,label`
+///
+/// | kind   | value       | label            |
+/// |--------|-------------|------------------|
+/// | beat   | strength    | beat type        |
+/// | tempo  | BPM         | confidence       |
+/// | energy | RMS         | peak             |
+/// | phrase | confidence  | section type     |
+///
+/// `end` is only set for phrases.
+pub fn to_csv(analysis: &AudioAnalysis) -> String {
+    let mut csv = String::from("kind,start,end,value,label\n");
+
+    for change in &analysis.tempo.tempo_changes {
+        let _ = writeln!(csv, "tempo,{:.3},,{:.2},{:.3}", change.time, change.bpm, change.confidence);
+    }
+    for beat in &analysis.beats {
+        let _ = writeln!(csv, "beat,{:.3},,{:.3},{}", beat.time, beat.strength, beat_type_name(&beat.beat_type));
+    }
+    for level in &analysis.energy_levels {
+        let _ = writeln!(csv, "energy,{:.3},,{:.4},{:.4}", level.time, level.rms, level.peak);
+    }
+    for phrase in &analysis.phrases {
+        let _ = writeln!(
+            csv,
+            "phrase,{:.3},{:.3},{:.3},{}",
+            phrase.start, phrase.end, phrase.confidence, phrase_type_name(phrase.phrase_type)
+        );
+    }
+
+    csv
+}
+
+/// Audacity label track (tab-separated `start end label`)
+///
+/// Import with File > Import > Labels. Downbeats are labelled with their bar
+/// number so bars can be counted against the waveform.
+pub fn to_labels(analysis: &AudioAnalysis) -> String {
+    let mut labels = String::new();
+    let mut bar = 0;
+
+    for beat in &analysis.beats {
+        let label = match beat.beat_type {
+            BeatType::Downbeat => {
+                bar += 1;
+                format!("bar {}", bar)
+            }
+            ref other => beat_type_name(other).to_string(),
+        };
+        let _ = writeln!(labels, "{:.6}\t{:.6}\t{}", beat.time, beat.time, label);
+    }
+
+    for phrase in &analysis.phrases {
+        let _ = writeln!(
+            labels,
+            "{:.6}\t{:.6}\t{}",
+            phrase.start, phrase.end, phrase_type_name(phrase.phrase_type)
+        );
+    }
+
+    labels
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::audio::types::{AnalysisBuilder, TempoChange};
+
+    fn analysis() -> AudioAnalysis {
+        let mut analysis = AnalysisBuilder::new(4.0)
+            .beat(0.5, BeatType::Downbeat)
+            .beat(1.0, BeatType::Beat)
+            .beat(2.5, BeatType::Downbeat)
+            .energy(0.1, 0.25)
+            .phrase(0.0, 4.0, PhraseType::Chorus)
+            .build();
+        analysis.tempo.tempo_changes = vec![TempoChange { time: 0.0, bpm: 120.0, confidence: 0.9 }];
+        analysis
+    }
+
+    #[test]
+    fn test_format_names() {
+        for format in ReportFormat::ALL {
+            assert_eq!(ReportFormat::from_name(format.name()), Some(format));
+        }
+        assert_eq!(ReportFormat::from_name("Audacity"), Some(ReportFormat::Labels));
+        assert_eq!(ReportFormat::from_name("xml"), None);
+    }
+
+    #[test]
+    fn test_json_leaves_out_spectral_features() {
+        let json: serde_json::Value = serde_json::from_str(&to_json(&analysis())).unwrap();
+
+        assert_eq!(json["beats"].as_array().unwrap().len(), 3);
+        assert_eq!(json["beats"][0]["beat_type"], "downbeat");
+        assert_eq!(json["tempo"]["global_bpm"], 120.0);
+        assert_eq!(json["energy"][0]["rms"], 0.25);
+        assert_eq!(json["phrases"][0]["phrase_type"], "chorus");
+        assert!(json.get("spectral_features").is_none());
+    }
+
+    #[test]
+    fn test_csv_has_a_row_per_event() {
+        let csv = to_csv(&analysis());
+        let rows: Vec<&str> = csv.lines().collect();
+
+        assert_eq!(rows[0], "kind,start,end,value,label");
+        assert_eq!(rows.len(), 1 + 1 + 3 + 1 + 1);
+        assert!(rows.contains(&"beat,0.500,,1.000,downbeat"));
+        assert!(rows.contains(&"phrase,0.000,4.000,1.000,chorus"));
+        assert!(rows.iter().all(|row| row.split(',').count() == 5));
+    }
+
+    #[test]
+    fn test_labels_number_bars() {
+        let labels = to_labels(&analysis());
+        let rows: Vec<&str> = labels.lines().collect();
+
+        assert_eq!(rows[0], "0.500000\t0.500000\tbar 1");
+        assert_eq!(rows[1], "1.000000\t1.000000\tbeat");
+        assert_eq!(rows[2], "2.500000\t2.500000\tbar 2");
+        assert_eq!(rows[3], "0.000000\t4.000000\tchorus");
+    }
+}
@@ -0,0 +1,467 @@
+//! Structural segmentation of a song into labelled sections
+//!
+//! Chroma and MFCC frames are pooled into short blocks and compared in a
+//! self-similarity matrix. A checkerboard kernel slid along its diagonal gives
+//! a novelty curve whose peaks are section boundaries. Sections are then
+//! clustered by similarity; the most repeated high-energy cluster is the chorus.
+
+use crate::audio::types::{EnergyLevel, Phrase, PhraseType};
+
+/// Length of one pooled feature block in seconds
+pub const BLOCK_SECONDS: f64 = 0.5;
+
+/// Half-width of the checkerboard kernel in blocks
+const KERNEL_BLOCKS: usize = 8;
+
+/// Shortest section the segmentation will produce
+pub const MIN_SECTION_SECONDS: f64 = 6.0;
+
+/// Cosine similarity above which two sections count as repeats
+const REPEAT_SIMILARITY: f32 = 0.8;
+
+/// Relative weight of harmony (chroma) against timbre (MFCC)
+const CHROMA_WEIGHT: f32 = 0.6;
+
+/// Split a song into sections from per-frame chroma and MFCC features
+///
+/// `frame_rate` is the number of feature frames per second.
+pub fn segment(
+    chroma: &[Vec<f32>],
+    mfcc: &[Vec<f32>],
+    frame_rate: f64,
+    energy_levels: &[EnergyLevel],
+    duration: f64,
+) -> Vec<Phrase> {
+    if duration <= 0.0 {
+        return Vec::new();
+    }
+
+    let blocks = pool_blocks(chroma, mfcc, frame_rate, duration);
+    let block_count = blocks.len();
+
+    // Too short to hold more than one section
+    if block_count < 2 * KERNEL_BLOCKS || duration < 2.0 * MIN_SECTION_SECONDS {
+        return vec![Phrase {
+            start: 0.0,
+            end: duration,
+            phrase_type: PhraseType::Unknown,
+            confidence: 0.0,
+        }];
+    }
+
+    let ssm = self_similarity(&blocks);
+    let novelty = novelty_curve(&ssm, block_count);
+    let boundaries = pick_boundaries(&novelty, duration);
+
+    // Section spans in blocks
+    let mut edges = vec![0];
+    edges.extend(boundaries.iter().copied());
+    edges.push(block_count);
+
+    let sections: Vec<Section> = edges
+        .windows(2)
+        .map(|span| {
+            let (first, last) = (span[0], span[1]);
+            let start = first as f64 * BLOCK_SECONDS;
+            let end = (last as f64 * BLOCK_SECONDS).min(duration);
+            Section {
+                start,
+                end,
+                feature: normalize(mean_vector(&blocks[first..last])),
+                energy: average_energy(energy_levels, start, end),
+                boundary_strength: if first == 0 { 1.0 } else { novelty[first] },
+            }
+        })
+        .collect();
+
+    label_sections(&sections)
+}
+
+/// One candidate section before labelling
+struct Section {
+    start: f64,
+    end: f64,
+    feature: Vec<f32>,
+    energy: f32,
+    boundary_strength: f32,
+}
+
+/// Average frames into blocks and build one unit-length feature vector per block
+fn pool_blocks(chroma: &[Vec<f32>], mfcc: &[Vec<f32>], frame_rate: f64, duration: f64) -> Vec<Vec<f32>> {
+    let block_count = (duration / BLOCK_SECONDS).ceil() as usize;
+    let frames_per_block = (frame_rate * BLOCK_SECONDS).max(1.0);
+
+    let pool = |frames: &[Vec<f32>], block: usize| -> Vec<f32> {
+        let first = (block as f64 * frames_per_block) as usize;
+        let last = (((block + 1) as f64 * frames_per_block) as usize).min(frames.len());
+        if first >= last {
+            return Vec::new();
+        }
+        mean_vector(&frames[first..last])
+    };
+
+    let chroma_blocks: Vec<Vec<f32>> = (0..block_count).map(|b| normalize(pool(chroma, b))).collect();
+
+    // Drop c0 (loudness) and standardize each coefficient across the song
+    let mut mfcc_blocks: Vec<Vec<f32>> = (0..block_count)
+        .map(|b| pool(mfcc, b).into_iter().skip(1).collect())
+        .collect();
+    standardize(&mut mfcc_blocks);
+
+    chroma_blocks
+        .into_iter()
+        .zip(mfcc_blocks)
+        .map(|(c, m)| {
+            let m = normalize(m);
+            let mut block: Vec<f32> = c.iter().map(|&x| x * CHROMA_WEIGHT).collect();
+            block.extend(m.iter().map(|&x| x * (1.0 - CHROMA_WEIGHT)));
+            normalize(block)
+        })
+        .collect()
+}
+
+/// Cosine similarity between every pair of blocks, row-major
+fn self_similarity(blocks: &[Vec<f32>]) -> Vec<f32> {
+    let n = blocks.len();
+    let mut ssm = vec![0.0f32; n * n];
+
+    for i in 0..n {
+        for j in i..n {
+            let similarity = dot(&blocks[i], &blocks[j]);
+            ssm[i * n + j] = similarity;
+            ssm[j * n + i] = similarity;
+        }
+    }
+
+    ssm
+}
+
+/// Foote novelty: correlate a Gaussian-tapered checkerboard kernel along the diagonal
+fn novelty_curve(ssm: &[f32], n: usize) -> Vec<f32> {
+    let half = KERNEL_BLOCKS as isize;
+    let sigma = KERNEL_BLOCKS as f32 / 2.0;
+
+    let mut novelty: Vec<f32> = (0..n as isize)
+        .map(|center| {
+            let mut sum = 0.0;
+            for a in -half..half {
+                for b in -half..half {
+                    let (i, j) = (center + a, center + b);
+                    if i < 0 || j < 0 || i >= n as isize || j >= n as isize {
+                        continue;
+                    }
+
+                    // Same side of the center: similar is good; across it: similar is bad
+                    let sign = if (a < 0) == (b < 0) { 1.0 } else { -1.0 };
+                    let (da, db) = (a as f32 + 0.5, b as f32 + 0.5);
+                    let taper = (-(da * da + db * db) / (2.0 * sigma * sigma)).exp();
+                    sum += sign * taper * ssm[i as usize * n + j as usize];
+                }
+            }
+            sum.max(0.0)
+        })
+        .collect();
+
+    let max = novelty.iter().fold(0.0f32, |acc, &x| acc.max(x));
+    if max > 0.0 {
+        novelty.iter_mut().for_each(|x| *x /= max);
+    }
+
+    novelty
+}
+
+/// Strongest novelty peaks, at least `MIN_SECTION_SECONDS` apart and from the ends
+fn pick_boundaries(novelty: &[f32], duration: f64) -> Vec<usize> {
+    let n = novelty.len();
+    let min_gap = (MIN_SECTION_SECONDS / BLOCK_SECONDS).round() as usize;
+    let last_block = ((duration / BLOCK_SECONDS) as usize).min(n);
+
+    let mean = novelty.iter().sum::<f32>() / n as f32;
+    let deviation = (novelty.iter().map(|&x| (x - mean).powi(2)).sum::<f32>() / n as f32).sqrt();
+    let threshold = mean + 0.5 * deviation;
+
+    // Local maxima above the adaptive threshold, strongest first
+    let mut peaks: Vec<usize> = (1..n.saturating_sub(1))
+        .filter(|&i| novelty[i] >= threshold && novelty[i] >= novelty[i - 1] && novelty[i] > novelty[i + 1])
+        .collect();
+    peaks.sort_by(|&a, &b| novelty[b].total_cmp(&novelty[a]));
+
+    let mut boundaries: Vec<usize> = Vec::new();
+    for peak in peaks {
+        if peak < min_gap || peak + min_gap > last_block {
+            continue;
+        }
+        if boundaries.iter().all(|&b| b.abs_diff(peak) >= min_gap) {
+            boundaries.push(peak);
+        }
+    }
+
+    boundaries.sort_unstable();
+    boundaries
+}
+
+/// Cluster sections by similarity and name them
+fn label_sections(sections: &[Section]) -> Vec<Phrase> {
+    let count = sections.len();
+
+    // Greedy clustering against each cluster's first member
+    let mut cluster_of = vec![0usize; count];
+    let mut representatives: Vec<usize> = Vec::new();
+    for (i, section) in sections.iter().enumerate() {
+        let best = representatives
+            .iter()
+            .enumerate()
+            .map(|(c, &r)| (c, dot(&section.feature, &sections[r].feature)))
+            .filter(|&(_, similarity)| similarity >= REPEAT_SIMILARITY)
+            .max_by(|a, b| a.1.total_cmp(&b.1));
+
+        cluster_of[i] = match best {
+            Some((cluster, _)) => cluster,
+            None => {
+                representatives.push(i);
+                representatives.len() - 1
+            }
+        };
+    }
+
+    let members = |cluster: usize| -> Vec<usize> { (0..count).filter(|&i| cluster_of[i] == cluster).collect() };
+    let song_energy = sections.iter().map(|s| s.energy).sum::<f32>() / count as f32;
+    let relative_energy = |indices: &[usize]| -> f32 {
+        let energy = indices.iter().map(|&i| sections[i].energy).sum::<f32>() / indices.len() as f32;
+        if song_energy > 0.0 { energy / song_energy } else { 1.0 }
+    };
+
+    // Chorus: the repeated cluster with the most repetitions weighted by loudness,
+    // or failing any repeats, a clearly louder section
+    let chorus = (0..representatives.len())
+        .map(|c| (c, members(c)))
+        .filter(|(_, m)| m.len() >= 2)
+        .map(|(c, m)| (c, m.len() as f32 * relative_energy(&m)))
+        .max_by(|a, b| a.1.total_cmp(&b.1))
+        .map(|(c, _)| c)
+        .or_else(|| {
+            (0..count)
+                .max_by(|&a, &b| sections[a].energy.total_cmp(&sections[b].energy))
+                .filter(|&i| relative_energy(&[i]) > 1.2)
+                .map(|i| cluster_of[i])
+        });
+
+    let mut seen_chorus = false;
+
+    sections
+        .iter()
+        .enumerate()
+        .map(|(i, section)| {
+            let cluster = cluster_of[i];
+            let repeated = members(cluster).len() >= 2;
+            let quiet = section.energy <= song_energy;
+
+            let phrase_type = if Some(cluster) == chorus {
+                PhraseType::Chorus
+            } else if i == 0 && quiet {
+                PhraseType::Intro
+            } else if i == count - 1 && quiet {
+                PhraseType::Outro
+            } else if repeated || !seen_chorus {
+                PhraseType::Verse
+            } else {
+                PhraseType::Bridge
+            };
+            seen_chorus |= phrase_type == PhraseType::Chorus;
+
+            // Repeats are as certain as they are similar; one-offs as certain as they are distinct
+            let others = (0..count).filter(|&j| j != i);
+            let label_confidence = if repeated {
+                let similar: Vec<f32> = others
+                    .filter(|&j| cluster_of[j] == cluster)
+                    .map(|j| dot(&section.feature, &sections[j].feature))
+                    .collect();
+                similar.iter().sum::<f32>() / similar.len() as f32
+            } else {
+                1.0 - others.map(|j| dot(&section.feature, &sections[j].feature)).fold(0.0f32, f32::max)
+            };
+
+            Phrase {
+                start: section.start,
+                end: section.end,
+                phrase_type,
+                confidence: ((section.boundary_strength + label_confidence.clamp(0.0, 1.0)) / 2.0).clamp(0.0, 1.0),
+            }
+        })
+        .collect()
+}
+
+fn average_energy(energy_levels: &[EnergyLevel], start: f64, end: f64) -> f32 {
+    let values: Vec<f32> = energy_levels
+        .iter()
+        .filter(|e| e.time >= start && e.time < end)
+        .map(|e| e.rms)
+        .collect();
+
+    if values.is_empty() {
+        0.0
+    } else {
+        values.iter().sum::<f32>() / values.len() as f32
+    }
+}
+
+fn mean_vector(vectors: &[Vec<f32>]) -> Vec<f32> {
+    let dimensions = vectors.iter().map(|v| v.len()).max().unwrap_or(0);
+    let mut mean = vec![0.0f32; dimensions];
+
+    for vector in vectors {
+        for (m, &x) in mean.iter_mut().zip(vector.iter()) {
+            *m += x;
+        }
+    }
+
+    if !vectors.is_empty() {
+        mean.iter_mut().for_each(|m| *m /= vectors.len() as f32);
+    }
+
+    mean
+}
+
+/// Zero mean, unit variance per dimension across all vectors
+fn standardize(vectors: &mut [Vec<f32>]) {
+    let dimensions = vectors.iter().map(|v| v.len()).max().unwrap_or(0);
+
+    for d in 0..dimensions {
+        let values: Vec<f32> = vectors.iter().filter_map(|v| v.get(d).copied()).collect();
+        if values.is_empty() {
+            continue;
+        }
+        let mean = values.iter().sum::<f32>() / values.len() as f32;
+        let deviation = (values.iter().map(|&x| (x - mean).powi(2)).sum::<f32>() / values.len() as f32).sqrt();
+
+        for value in vectors.iter_mut().filter_map(|v| v.get_mut(d)) {
+            *value = if deviation > 1e-6 { (*value - mean) / deviation } else { 0.0 };
+        }
+    }
+}
+
+fn normalize(mut vector: Vec<f32>) -> Vec<f32> {
+    let norm = dot(&vector, &vector).sqrt();
+    if norm > 1e-9 {
+        vector.iter_mut().for_each(|x| *x /= norm);
+    }
+    vector
+}
+
+fn dot(a: &[f32], b: &[f32]) -> f32 {
+    a.iter().zip(b.iter()).map(|(&x, &y)| x * y).sum()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::styles::noise::hash_unit;
+
+    const FRAME_RATE: f64 = 10.0;
+
+    type Song = (Vec<Vec<f32>>, Vec<Vec<f32>>, Vec<EnergyLevel>, f64);
+
+    /// Synthetic song built from sections of (chroma peaks, timbre, energy, seconds)
+    fn song(sections: &[(&[usize], f32, f32, f64)]) -> Song {
+        let mut chroma = Vec::new();
+        let mut mfcc = Vec::new();
+        let mut energy = Vec::new();
+        let mut time = 0.0;
+
+        for &(notes, timbre, rms, seconds) in sections {
+            for _ in 0..(seconds * FRAME_RATE) as usize {
+                let frame = chroma.len() as u64;
+                let jitter = |k: u64| hash_unit(7, frame, k) * 0.1;
+
+                chroma.push(
+                    (0..12)
+                        .map(|c| if notes.contains(&c) { 1.0 } else { jitter(c as u64) })
+                        .collect(),
+                );
+                mfcc.push(
+                    (0..13)
+                        .map(|k| timbre * ((k as f32) * 0.7 + timbre).sin() + jitter(20 + k))
+                        .collect(),
+                );
+                energy.push(EnergyLevel {
+                    time,
+                    rms,
+                    peak: rms,
+                    spectral_centroid: 0.0,
+                    zero_crossing_rate: 0.0,
+                });
+                time += 1.0 / FRAME_RATE;
+            }
+        }
+
+        (chroma, mfcc, energy, time)
+    }
+
+    #[test]
+    fn test_segments_repeated_structure() {
+        let verse: (&[usize], f32, f32, f64) = (&[0, 4, 7], 1.0, 0.2, 12.0);
+        let chorus: (&[usize], f32, f32, f64) = (&[6, 10, 1], 3.0, 0.6, 12.0);
+        let (chroma, mfcc, energy, duration) = song(&[verse, chorus, verse, chorus, verse]);
+
+        let phrases = segment(&chroma, &mfcc, FRAME_RATE, &energy, duration);
+
+        assert_eq!(phrases.len(), 5);
+        for (phrase, expected) in phrases.iter().skip(1).zip([12.0, 24.0, 36.0, 48.0]) {
+            assert!((phrase.start - expected).abs() <= 1.0, "boundary at {}", phrase.start);
+        }
+
+        let types: Vec<PhraseType> = phrases.iter().map(|p| p.phrase_type).collect();
+        assert_eq!(
+            types,
+            vec![PhraseType::Intro, PhraseType::Chorus, PhraseType::Verse, PhraseType::Chorus, PhraseType::Outro]
+        );
+    }
+
+    #[test]
+    fn test_confidence_is_computed() {
+        let a: (&[usize], f32, f32, f64) = (&[0, 4, 7], 1.0, 0.3, 10.0);
+        let b: (&[usize], f32, f32, f64) = (&[2, 5, 9], 2.0, 0.5, 10.0);
+        let (chroma, mfcc, energy, duration) = song(&[a, b, a, b]);
+
+        let phrases = segment(&chroma, &mfcc, FRAME_RATE, &energy, duration);
+
+        assert!(phrases.iter().all(|p| (0.0..=1.0).contains(&p.confidence)));
+        assert!(phrases.iter().any(|p| p.confidence != phrases[0].confidence));
+        assert!(phrases.iter().all(|p| p.confidence > 0.5));
+    }
+
+    #[test]
+    fn test_uniform_song_is_one_section() {
+        let a: (&[usize], f32, f32, f64) = (&[0, 4, 7], 1.0, 0.3, 30.0);
+        let (chroma, mfcc, energy, duration) = song(&[a]);
+
+        let phrases = segment(&chroma, &mfcc, FRAME_RATE, &energy, duration);
+
+        assert_eq!(phrases.len(), 1);
+        assert_eq!(phrases[0].end, duration);
+    }
+
+    #[test]
+    fn test_short_song_is_unknown() {
+        let a: (&[usize], f32, f32, f64) = (&[0], 1.0, 0.3, 4.0);
+        let (chroma, mfcc, energy, duration) = song(&[a]);
+
+        let phrases = segment(&chroma, &mfcc, FRAME_RATE, &energy, duration);
+
+        assert_eq!(phrases.len(), 1);
+        assert_eq!(phrases[0].phrase_type, PhraseType::Unknown);
+    }
+
+    #[test]
+    fn test_nan_features_do_not_panic() {
+        let a: (&[usize], f32, f32, f64) = (&[0, 4, 7], 1.0, 0.3, 10.0);
+        let b: (&[usize], f32, f32, f64) = (&[2, 5, 9], 2.0, 0.5, 10.0);
+        let (mut chroma, mfcc, mut energy, duration) = song(&[a, b, a]);
+        chroma[120][3] = f32::NAN;
+        energy[50].rms = f32::NAN;
+
+        let phrases = segment(&chroma, &mfcc, FRAME_RATE, &energy, duration);
+
+        assert!(!phrases.is_empty());
+    }
+}
@@ -0,0 +1,359 @@
+//! Local tempo estimation and tempo-following beat tracking
+//!
+//! The onset detection function is autocorrelated in overlapping windows to
+//! get a tempo candidate score per window. A Viterbi pass picks the tempo path
+//! that scores well while avoiding octave jumps between windows. Beats are
+//! then placed by dynamic programming (Ellis, 2007) using the local beat period.
+
+use crate::audio::types::TempoChange;
+
+/// Length of each autocorrelation window in seconds
+const WINDOW_SECONDS: f64 = 8.0;
+
+/// Step between autocorrelation windows in seconds
+const HOP_SECONDS: f64 = 2.0;
+
+/// Center of the log-Gaussian tempo prior
+const PRIOR_BPM: f64 = 120.0;
+
+/// Width of the tempo prior in octaves
+const PRIOR_OCTAVES: f64 = 1.0;
+
+/// Viterbi penalty per octave of tempo change between windows
+const TRANSITION_PENALTY: f32 = 4.0;
+
+/// How strongly beats are held to the local period
+const TIGHTNESS: f32 = 100.0;
+
+/// Relative tempo change that starts a new `TempoChange` point
+const CHANGE_TOLERANCE: f32 = 0.02;
+
+/// Tempo of each analysis window, smoothed across windows
+///
+/// Returns one point per window, timed at the window center.
+pub fn local_tempo(odf: &[f32], frame_rate: f64, min_bpm: f32, max_bpm: f32) -> Vec<TempoChange> {
+    let envelope = onset_envelope(odf, frame_rate);
+    let window = (WINDOW_SECONDS * frame_rate) as usize;
+    let hop = ((HOP_SECONDS * frame_rate) as usize).max(1);
+
+    let min_lag = ((60.0 * frame_rate / max_bpm as f64).floor() as usize).max(1);
+    let max_lag = (60.0 * frame_rate / min_bpm as f64).ceil() as usize;
+
+    if envelope.is_empty() || min_lag >= max_lag || max_lag * 2 > envelope.len() {
+        return Vec::new();
+    }
+
+    // Short recordings still get one window covering everything
+    let window = window.min(envelope.len());
+    let starts: Vec<usize> = (0..=envelope.len() - window).step_by(hop).collect();
+
+    let lags: Vec<usize> = (min_lag..=max_lag).collect();
+    let prior: Vec<f32> = lags
+        .iter()
+        .map(|&lag| {
+            let bpm = 60.0 * frame_rate / lag as f64;
+            let octaves = (bpm / PRIOR_BPM).log2() / PRIOR_OCTAVES;
+            (-0.5 * octaves * octaves).exp() as f32
+        })
+        .collect();
+
+    // Autocorrelation per window, normalized by zero-lag energy
+    let correlations: Vec<(Vec<f32>, f32)> = starts
+        .iter()
+        .map(|&start| {
+            let frames = &envelope[start..start + window];
+            let energy = autocorrelation(frames, 0);
+            let values = lags.iter().map(|&lag| autocorrelation(frames, lag)).collect();
+            (values, energy)
+        })
+        .collect();
+
+    let scores: Vec<Vec<f32>> = correlations
+        .iter()
+        .map(|(values, energy)| {
+            values
+                .iter()
+                .zip(prior.iter())
+                .map(|(&r, &p)| if *energy > 0.0 { r / energy * p } else { 0.0 })
+                .collect()
+        })
+        .collect();
+
+    let path = viterbi(&scores, &lags);
+
+    starts
+        .iter()
+        .zip(path)
+        .zip(correlations.iter())
+        .map(|((&start, state), (values, energy))| {
+            let lag = refine_lag(values, state) + min_lag as f32;
+            let confidence = if *energy > 0.0 { (values[state] / energy).clamp(0.0, 1.0) } else { 0.0 };
+
+            TempoChange {
+                time: (start + window / 2) as f64 / frame_rate,
+                bpm: (60.0 * frame_rate / lag as f64) as f32,
+                confidence,
+            }
+        })
+        .collect()
+}
+
+/// Reduce a per-window tempo curve to the points where the tempo actually moves
+pub fn tempo_changes(curve: &[TempoChange]) -> Vec<TempoChange> {
+    let mut changes: Vec<TempoChange> = Vec::new();
+
+    for point in curve {
+        match changes.last() {
+            Some(last) if ((point.bpm - last.bpm) / last.bpm).abs() < CHANGE_TOLERANCE => {}
+            _ => changes.push(point.clone()),
+        }
+    }
+
+    // Anchor the end of the curve so interpolation doesn't extrapolate a drift
+    if let (Some(last_point), Some(last_change)) = (curve.last(), changes.last()) {
+        let drift = ((last_point.bpm - last_change.bpm) / last_change.bpm).abs();
+        if last_point.time > last_change.time && drift >= CHANGE_TOLERANCE / 2.0 {
+            changes.push(last_point.clone());
+        }
+    }
+
+    changes
+}
+
+/// Tempo at a time, interpolated linearly between tempo points
+pub fn interpolate(points: &[TempoChange], time: f64, fallback: f32) -> f32 {
+    let (Some(first), Some(last)) = (points.first(), points.last()) else {
+        return fallback;
+    };
+
+    if time <= first.time {
+        return first.bpm;
+    }
+    if time >= last.time {
+        return last.bpm;
+    }
+
+    let next = points.partition_point(|p| p.time <= time);
+    let (a, b) = (&points[next - 1], &points[next]);
+    let t = ((time - a.time) / (b.time - a.time)) as f32;
+
+    a.bpm + (b.bpm - a.bpm) * t
+}
+
+/// Beat times that follow the local tempo curve through the onset function
+pub fn track_beats(odf: &[f32], frame_rate: f64, curve: &[TempoChange]) -> Vec<f64> {
+    let envelope = onset_envelope(odf, frame_rate);
+    if envelope.is_empty() || curve.is_empty() {
+        return Vec::new();
+    }
+
+    // Scale the envelope so the tightness works for any loudness
+    let deviation = (envelope.iter().map(|&x| x * x).sum::<f32>() / envelope.len() as f32).sqrt();
+    if deviation <= 0.0 {
+        return Vec::new();
+    }
+    let onset: Vec<f32> = envelope.iter().map(|&x| x / deviation).collect();
+
+    let period_at = |frame: usize| -> f32 {
+        let bpm = interpolate(curve, frame as f64 / frame_rate, curve[0].bpm);
+        (60.0 * frame_rate / bpm as f64) as f32
+    };
+
+    let mut score = vec![0.0f32; onset.len()];
+    let mut backlink: Vec<Option<usize>> = vec![None; onset.len()];
+
+    for t in 0..onset.len() {
+        let period = period_at(t);
+        let earliest = t.saturating_sub((2.0 * period).round() as usize);
+        let latest = t.saturating_sub((period / 2.0).round() as usize);
+
+        let best = (earliest..latest)
+            .map(|prev| {
+                let ratio = (t - prev) as f32 / period;
+                (prev, score[prev] - TIGHTNESS * ratio.ln().powi(2))
+            })
+            .max_by(|a, b| a.1.total_cmp(&b.1));
+
+        score[t] = onset[t];
+        if let Some((prev, value)) = best {
+            if value > 0.0 {
+                score[t] += value;
+                backlink[t] = Some(prev);
+            }
+        }
+    }
+
+    // Finish on the best-scoring frame within the last beat period
+    let tail = onset.len().saturating_sub(period_at(onset.len() - 1).round() as usize);
+    let Some(mut frame) = (tail..onset.len()).max_by(|&a, &b| score[a].total_cmp(&score[b])) else {
+        return Vec::new();
+    };
+
+    let mut beats = vec![frame];
+    while let Some(prev) = backlink[frame] {
+        beats.push(prev);
+        frame = prev;
+    }
+    beats.reverse();
+
+    beats.into_iter().map(|f| f as f64 / frame_rate).collect()
+}
+
+/// Onset function with its slowly varying floor removed and negatives clipped
+fn onset_envelope(odf: &[f32], frame_rate: f64) -> Vec<f32> {
+    let radius = ((frame_rate * 0.25) as usize).max(1);
+
+    (0..odf.len())
+        .map(|i| {
+            let start = i.saturating_sub(radius);
+            let end = (i + radius + 1).min(odf.len());
+            let local_mean = odf[start..end].iter().sum::<f32>() / (end - start) as f32;
+            (odf[i] - local_mean).max(0.0)
+        })
+        .collect()
+}
+
+fn autocorrelation(frames: &[f32], lag: usize) -> f32 {
+    if lag >= frames.len() {
+        return 0.0;
+    }
+
+    let sum: f32 = frames.iter().zip(frames[lag..].iter()).map(|(&a, &b)| a * b).sum();
+    sum / (frames.len() - lag) as f32
+}
+
+/// Best path through the tempo candidates, penalizing tempo jumps
+fn viterbi(scores: &[Vec<f32>], lags: &[usize]) -> Vec<usize> {
+    let Some(first) = scores.first() else {
+        return Vec::new();
+    };
+
+    let states = lags.len();
+    let log_lags: Vec<f32> = lags.iter().map(|&l| (l as f32).log2()).collect();
+
+    let mut total = first.clone();
+    let mut back: Vec<Vec<usize>> = Vec::with_capacity(scores.len());
+
+    for window in &scores[1..] {
+        let mut next = vec![0.0f32; states];
+        let mut links = vec![0usize; states];
+
+        for s in 0..states {
+            let (best, value) = (0..states)
+                .map(|p| (p, total[p] - TRANSITION_PENALTY * (log_lags[s] - log_lags[p]).abs()))
+                .max_by(|a, b| a.1.total_cmp(&b.1))
+                .unwrap();
+            next[s] = value + window[s];
+            links[s] = best;
+        }
+
+        total = next;
+        back.push(links);
+    }
+
+    let mut state = (0..states)
+        .max_by(|&a, &b| total[a].total_cmp(&total[b]))
+        .unwrap_or(0);
+
+    let mut path = vec![state];
+    for links in back.iter().rev() {
+        state = links[state];
+        path.push(state);
+    }
+    path.reverse();
+    path
+}
+
+/// Sub-frame lag offset from a parabola through the peak and its neighbours
+fn refine_lag(values: &[f32], index: usize) -> f32 {
+    if index == 0 || index + 1 >= values.len() {
+        return index as f32;
+    }
+
+    let (a, b, c) = (values[index - 1], values[index], values[index + 1]);
+    let denominator = a - 2.0 * b + c;
+    if denominator.abs() < 1e-9 {
+        return index as f32;
+    }
+
+    index as f32 + (0.5 * (a - c) / denominator).clamp(-0.5, 0.5)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const FRAME_RATE: f64 = 100.0;
+
+    /// Impulse-train onset function whose tempo ramps linearly
+    fn ramp_odf(seconds: f64, start_bpm: f64, end_bpm: f64) -> (Vec<f32>, Vec<f64>) {
+        let mut odf = vec![0.0f32; (seconds * FRAME_RATE) as usize];
+        let mut clicks = Vec::new();
+        let mut time = 0.5;
+
+        while time < seconds {
+            let frame = (time * FRAME_RATE).round() as usize;
+            if frame < odf.len() {
+                odf[frame] = 1.0;
+            }
+            clicks.push(time);
+            time += 60.0 / (start_bpm + (end_bpm - start_bpm) * time / seconds);
+        }
+
+        (odf, clicks)
+    }
+
+    #[test]
+    fn test_steady_tempo_is_found() {
+        let (odf, _) = ramp_odf(30.0, 128.0, 128.0);
+
+        let curve = local_tempo(&odf, FRAME_RATE, 60.0, 200.0);
+
+        assert!(!curve.is_empty());
+        for point in &curve {
+            assert!((point.bpm - 128.0).abs() < 2.0, "bpm {}", point.bpm);
+            assert!(point.confidence > 0.3);
+        }
+        assert_eq!(tempo_changes(&curve).len(), 1);
+    }
+
+    #[test]
+    fn test_tempo_ramp_produces_changes() {
+        let (odf, _) = ramp_odf(40.0, 100.0, 130.0);
+
+        let curve = local_tempo(&odf, FRAME_RATE, 60.0, 200.0);
+        let changes = tempo_changes(&curve);
+
+        assert!(changes.len() >= 3);
+        assert!(changes.windows(2).all(|w| w[1].bpm > w[0].bpm));
+        assert!((interpolate(&changes, 5.0, 0.0) - 104.0).abs() < 5.0);
+        assert!((interpolate(&changes, 35.0, 0.0) - 126.0).abs() < 5.0);
+    }
+
+    #[test]
+    fn test_beats_follow_local_tempo() {
+        let (odf, clicks) = ramp_odf(40.0, 100.0, 130.0);
+
+        let curve = local_tempo(&odf, FRAME_RATE, 60.0, 200.0);
+        let beats = track_beats(&odf, FRAME_RATE, &curve);
+
+        let matched = clicks
+            .iter()
+            .filter(|&&click| beats.iter().any(|&beat| (beat - click).abs() < 0.03))
+            .count();
+        assert!(matched as f64 >= clicks.len() as f64 * 0.9, "{} of {}", matched, clicks.len());
+    }
+
+    #[test]
+    fn test_interpolate_between_points() {
+        let points = vec![
+            TempoChange { time: 0.0, bpm: 100.0, confidence: 1.0 },
+            TempoChange { time: 10.0, bpm: 120.0, confidence: 1.0 },
+        ];
+
+        assert_eq!(interpolate(&points, 5.0, 0.0), 110.0);
+        assert_eq!(interpolate(&points, -1.0, 0.0), 100.0);
+        assert_eq!(interpolate(&points, 20.0, 0.0), 120.0);
+        assert_eq!(interpolate(&[], 5.0, 90.0), 90.0);
+    }
+}
@@ -85,11 +85,15 @@ pub struct AudioFormat {
 }
 
 /// Complete analysis results for an audio file
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct AudioAnalysis {
     /// Detected beats with timestamps
     pub beats: Vec<Beat>,
 
+    /// Onset candidates the beats were tracked from (seconds)
+    #[serde(default)]
+    pub onsets: Vec<f64>,
+
     /// Calculated tempo information
     pub tempo: TempoMap,
 
@@ -144,16 +148,92 @@ impl AudioAnalysis {
         self.beats.iter().find(|beat| beat.time > time)
     }
 
-    /// Get tempo at a specific time
+    /// Get tempo at a specific time, interpolated between tempo changes
     pub fn tempo_at_time(&self, time: f64) -> f32 {
-        // For now, return the global BPM
-        // In future versions, this could support tempo changes
-        self.bpm
+        self.tempo.bpm_at(time)
+    }
+}
+
+/// Builds analyses for tests: 120 BPM in 4/4, with no events until they are added
+#[cfg(test)]
+pub(crate) struct AnalysisBuilder {
+    analysis: AudioAnalysis,
+}
+
+#[cfg(test)]
+impl AnalysisBuilder {
+    pub fn new(duration: f64) -> Self {
+        Self {
+            analysis: AudioAnalysis {
+                beats: vec![],
+                onsets: vec![],
+                tempo: TempoMap {
+                    global_bpm: 120.0,
+                    confidence: 1.0,
+                    tempo_changes: vec![],
+                    time_signature: TimeSignature::default(),
+                },
+                energy_levels: vec![],
+                bpm: 120.0,
+                bpm_confidence: 1.0,
+                duration,
+                config: AnalysisConfig::default(),
+                phrases: vec![],
+                spectral_features: SpectralFeatures {
+                    mfcc: vec![],
+                    spectral_centroid: vec![],
+                    spectral_rolloff: vec![],
+                    chroma: vec![],
+                    onset_detection_function: vec![],
+                },
+            },
+        }
+    }
+
+    /// Set the global tempo
+    pub fn bpm(mut self, bpm: f32) -> Self {
+        self.analysis.tempo.global_bpm = bpm;
+        self.analysis.bpm = bpm;
+        self
+    }
+
+    /// Add a full-strength beat
+    pub fn beat(mut self, time: f64, beat_type: BeatType) -> Self {
+        self.analysis.beats.push(Beat {
+            time,
+            strength: 1.0,
+            beat_type,
+            onset_value: 0.5,
+            local_energy: 0.5,
+        });
+        self
+    }
+
+    /// Add an energy reading with the given RMS
+    pub fn energy(mut self, time: f64, rms: f32) -> Self {
+        self.analysis.energy_levels.push(EnergyLevel {
+            time,
+            rms,
+            peak: 1.0,
+            spectral_centroid: 0.0,
+            zero_crossing_rate: 0.0,
+        });
+        self
+    }
+
+    /// Add a phrase detected with full confidence
+    pub fn phrase(mut self, start: f64, end: f64, phrase_type: PhraseType) -> Self {
+        self.analysis.phrases.push(Phrase { start, end, phrase_type, confidence: 1.0 });
+        self
+    }
+
+    pub fn build(self) -> AudioAnalysis {
+        self.analysis
     }
 }
 
 /// Individual beat detection with metadata
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Beat {
     /// Time of the beat in seconds
     pub time: f64,
@@ -172,7 +252,8 @@ pub struct Beat {
 }
 
 /// Classification of beat types
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
 pub enum BeatType {
     /// Strong downbeat (typically beat 1 of a measure)
     Downbeat,
@@ -188,7 +269,7 @@ pub enum BeatType {
 }
 
 /// Tempo mapping information
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct TempoMap {
     /// Global BPM
     pub global_bpm: f32,
@@ -196,15 +277,22 @@ pub struct TempoMap {
     /// Confidence in global BPM (0.0-1.0)
     pub confidence: f32,
 
-    /// Tempo changes over time (future feature)
+    /// Tempo changes over time, sorted by time
     pub tempo_changes: Vec<TempoChange>,
 
     /// Time signature (4/4, 3/4, etc.)
     pub time_signature: TimeSignature,
 }
 
+impl TempoMap {
+    /// Tempo at a specific time, falling back to the global BPM without tempo changes
+    pub fn bpm_at(&self, time: f64) -> f32 {
+        crate::audio::tempo::interpolate(&self.tempo_changes, time, self.global_bpm)
+    }
+}
+
 /// Tempo change point (for songs with varying tempo)
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct TempoChange {
     /// Time when tempo changes
     pub time: f64,
@@ -217,7 +305,7 @@ pub struct TempoChange {
 }
 
 /// Time signature information
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct TimeSignature {
     /// Beats per measure (numerator)
     pub beats_per_measure: u8,
@@ -236,7 +324,7 @@ impl Default for TimeSignature {
 }
 
 /// Energy level measurement at a point in time
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct EnergyLevel {
     /// Time in seconds
     pub time: f64,
@@ -255,7 +343,7 @@ pub struct EnergyLevel {
 }
 
 /// Musical phrase or section
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Phrase {
     /// Start time in seconds
     pub start: f64,
@@ -271,7 +359,8 @@ pub struct Phrase {
 }
 
 /// Types of musical phrases/sections
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
 pub enum PhraseType {
     /// Introduction
     Intro,
@@ -293,7 +382,7 @@ pub enum PhraseType {
 }
 
 /// Spectral analysis features
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SpectralFeatures {
     /// Mel-frequency cepstral coefficients
     pub mfcc: Vec<Vec<f32>>,
@@ -464,6 +553,7 @@ mod tests {
 
         let analysis = AudioAnalysis {
             beats,
+            onsets: vec![],
             tempo: TempoMap {
                 global_bpm: 120.0,
                 confidence: 0.9,
@@ -489,4 +579,23 @@ mod tests {
         assert_eq!(beats_in_range.len(), 1);
         assert_eq!(beats_in_range[0].time, 2.5);
     }
+
+    #[test]
+    fn test_tempo_map_interpolates_changes() {
+        let mut tempo = TempoMap {
+            global_bpm: 120.0,
+            confidence: 0.9,
+            tempo_changes: vec![],
+            time_signature: TimeSignature::default(),
+        };
+        assert_eq!(tempo.bpm_at(10.0), 120.0);
+
+        tempo.tempo_changes = vec![
+            TempoChange { time: 4.0, bpm: 100.0, confidence: 0.8 },
+            TempoChange { time: 12.0, bpm: 140.0, confidence: 0.8 },
+        ];
+        assert_eq!(tempo.bpm_at(0.0), 100.0);
+        assert_eq!(tempo.bpm_at(8.0), 120.0);
+        assert_eq!(tempo.bpm_at(30.0), 140.0);
+    }
 }
\ No newline at end of file
@@ -1,7 +1,7 @@
 // Minimal test to verify the core functionality works
 
 use retro_compositor::{
-    styles::{StyleRegistry, StyleConfig},
+    styles::{FrameContext, StyleRegistry, StyleConfig},
     video::Frame,
 };
 
@@ -40,7 +40,7 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
 
     // Test 5: Apply VHS Effect
     println!("\n5. Testing VHS Effect Application...");
-    let result = vhs_style.apply_effect(&mut frame, &config);
+    let result = vhs_style.apply_effect(&mut frame, &config, &FrameContext::default());
 
     match result {
         Ok(()) => {
@@ -2,7 +2,7 @@
 
 use std::path::PathBuf;
 use retro_compositor::{
-    audio::{AudioLoader, AudioAnalyzer, types::AnalysisConfig},
+    audio::{AudioAnalyzer, types::AnalysisConfig},
     config::Config,
 };
 
@@ -1,7 +1,7 @@
 // Quick test for video creation with the fixes
 
 use retro_compositor::{
-    video::{VideoCompositor, VideoParams},
+    video::{FrameRate, VideoCompositor, VideoParams},
 };
 
 #[tokio::main]
@@ -15,13 +15,14 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
 
     // Create compositor with smaller resolution for testing
     let params = VideoParams {
-        fps: 30.0,
+        fps: FrameRate::integer(30),
         resolution: (640, 480),
         codec: "h264".to_string(),
         quality: 75, // Lower quality for faster encoding
+        ..VideoParams::default()
     };
 
-    let mut compositor = VideoCompositor::new(params);
+    let compositor = VideoCompositor::new(params);
 
     // Check FFmpeg
     if !VideoCompositor::check_ffmpeg_available() {
@@ -44,14 +45,10 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
             println!("   Frames: {}", video.frame_count);
             println!("   Size: {:.1} KB", video.file_size as f64 / 1024.0);
 
-            // Clean up
-            compositor.cleanup()?;
-
             println!("✅ Video creation fixed! Ready for full composition.");
         }
         Err(e) => {
             println!("❌ Still having issues: {}", e);
-            compositor.cleanup().ok();
         }
     }
 
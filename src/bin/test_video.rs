@@ -1,9 +1,8 @@
 // Test binary for video processing functionality
 
-use std::path::PathBuf;
 use retro_compositor::{
-    video::{VideoLoader, VideoProcessor, VideoCompositor, VideoParams, Frame},
-    styles::{VhsStyle, StyleConfig},
+    video::{FrameRate, VideoLoader, VideoProcessor, VideoCompositor, VideoParams, Frame},
+    styles::{FrameContext, VhsStyle, StyleConfig},
     config::Config,
     Style, // Import Style trait from the main lib re-export
 };
@@ -20,12 +19,12 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     // Test 1: Video Loader Initialization
     println!("\n1. Testing Video Loader...");
     match VideoLoader::new() {
-        Ok(mut loader) => {
+        Ok(_loader) => {
             println!("   ✅ Video loader initialized successfully");
 
             // Test format support detection
             println!("   Testing format support:");
-            let test_formats = vec!["test.mp4", "test.avi", "test.mov", "test.mkv", "test.txt"];
+            let test_formats = ["test.mp4", "test.avi", "test.mov", "test.mkv", "test.txt"];
             for format in test_formats {
                 let supported = VideoLoader::is_supported(format);
                 println!("     {} - {}", format, if supported { "✅ Supported" } else { "❌ Not supported" });
@@ -40,10 +39,11 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     // Test 2: Video Processor
     println!("\n2. Testing Video Processor...");
     let video_params = VideoParams {
-        fps: 30.0,
+        fps: FrameRate::integer(30),
         resolution: (640, 480),
         codec: "h264".to_string(),
         quality: 85,
+        ..VideoParams::default()
     };
 
     match VideoProcessor::new(video_params.clone()) {
@@ -70,7 +70,7 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         .set("noise_level", 0.4);
 
     // Create test frames
-    let mut test_frames = vec![
+    let mut test_frames = [
         Frame::new_filled(320, 240, [255, 100, 100]), // Red-ish
         Frame::new_filled(320, 240, [100, 255, 100]), // Green-ish
         Frame::new_filled(320, 240, [100, 100, 255]), // Blue-ish
@@ -80,7 +80,7 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
 
     // Apply VHS effects
     for (i, frame) in test_frames.iter_mut().enumerate() {
-        match style.apply_effect(frame, &style_config) {
+        match style.apply_effect(frame, &style_config, &FrameContext::for_frame(i as u64)) {
             Ok(()) => println!("   ✅ Applied VHS effect to frame {}", i + 1),
             Err(e) => println!("   ❌ Failed to apply effect to frame {}: {}", i + 1, e),
         }
@@ -97,7 +97,7 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
 
     // Test 4: Video Compositor
     println!("\n4. Testing Video Compositor...");
-    let mut compositor = VideoCompositor::new(video_params);
+    let compositor = VideoCompositor::new(video_params);
 
     // Check if FFmpeg is available
     if VideoCompositor::check_ffmpeg_available() {
@@ -152,7 +152,7 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     println!("\n6. Testing Configuration Integration...");
     let app_config = Config::default();
     println!("   Video configuration:");
-    println!("     Target FPS: {:.1}", app_config.video.params.fps);
+    println!("     Target FPS: {}", app_config.video.params.fps);
     println!("     Resolution: {}x{}", app_config.video.params.resolution.0, app_config.video.params.resolution.1);
     println!("     Codec: {}", app_config.video.params.codec);
     println!("     Quality: {}", app_config.video.params.quality);
@@ -164,6 +164,7 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     println!("     Min cut interval: {:.1}s", app_config.composition.min_cut_interval);
     println!("     Max cut interval: {:.1}s", app_config.composition.max_cut_interval);
     println!("     Crossfade duration: {:.1}s", app_config.composition.crossfade_duration);
+    println!("     Transition: {}", app_config.composition.transition.name());
 
     // Test 7: Memory and Performance
     println!("\n7. Testing Memory and Performance...");
@@ -181,8 +182,8 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     println!("   Created {} frames for performance test", large_frame_batch.len());
 
     let start_time = std::time::Instant::now();
-    for frame in large_frame_batch.iter_mut() {
-        let _ = style.apply_effect(frame, &style_config);
+    for (i, frame) in large_frame_batch.iter_mut().enumerate() {
+        let _ = style.apply_effect(frame, &style_config, &FrameContext::for_frame(i as u64));
     }
     let processing_time = start_time.elapsed();
 
@@ -194,9 +195,6 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     println!("\n🎉 All video processing tests completed!");
     println!("📝 Ready for full integration with audio analysis!");
 
-    // Cleanup
-    compositor.cleanup().ok();
-
     Ok(())
 }
 
@@ -0,0 +1,335 @@
+//! Beat-detection debug plots
+//!
+//! Draws what the analyzer saw and what the engine did with it onto one PNG,
+//! so a cut in the wrong place can be traced back to a missed beat, a noisy
+//! onset function or a misplaced section boundary. Time runs left to right;
+//! the lanes from top to bottom are:
+//!
+//! - waveform (min/max per column), when the audio is available
+//! - onset detection function, with detected onsets as yellow ticks
+//! - beats: downbeats red, beats white, offbeats grey, onset-only beats cyan;
+//!   tick height follows beat strength
+//! - RMS energy
+//! - phrases, one color per section type
+//!
+//! Final cut positions are drawn as green lines through every lane, and faint
+//! grid lines mark each second (brighter every ten).
+
+use std::path::Path;
+
+use image::{Rgb, RgbImage};
+
+use crate::audio::types::{AudioAnalysis, AudioData, BeatType, PhraseType};
+use crate::composition::engine::CompositionTimeline;
+use crate::error::{CompositionError, Result};
+
+/// Horizontal resolution used by `save_plot`
+pub const DEFAULT_PIXELS_PER_SECOND: f64 = 100.0;
+
+/// Widest image drawn; longer songs are squeezed to fit
+const MAX_WIDTH: u32 = 32_000;
+
+/// Height of each lane and the gap between lanes
+const LANE_HEIGHT: u32 = 120;
+const LANE_GAP: u32 = 4;
+
+const BACKGROUND: Rgb<u8> = Rgb([18, 18, 24]);
+const LANE_BACKGROUND: Rgb<u8> = Rgb([28, 28, 36]);
+const GRID: Rgb<u8> = Rgb([44, 44, 56]);
+const GRID_MAJOR: Rgb<u8> = Rgb([70, 70, 88]);
+const WAVEFORM: Rgb<u8> = Rgb([110, 160, 230]);
+const ONSET_FUNCTION: Rgb<u8> = Rgb([230, 140, 60]);
+const ONSET: Rgb<u8> = Rgb([240, 220, 60]);
+const ENERGY: Rgb<u8> = Rgb([170, 90, 210]);
+const CUT: Rgb<u8> = Rgb([60, 230, 110]);
+
+fn beat_color(beat_type: &BeatType) -> Rgb<u8> {
+    match beat_type {
+        BeatType::Downbeat => Rgb([240, 60, 60]),
+        BeatType::Beat => Rgb([235, 235, 235]),
+        BeatType::Offbeat => Rgb([130, 130, 130]),
+        BeatType::Onset => Rgb([60, 210, 230]),
+    }
+}
+
+fn phrase_color(phrase_type: PhraseType) -> Rgb<u8> {
+    match phrase_type {
+        PhraseType::Intro => Rgb([70, 110, 170]),
+        PhraseType::Verse => Rgb([70, 150, 100]),
+        PhraseType::Chorus => Rgb([200, 90, 70]),
+        PhraseType::Bridge => Rgb([190, 160, 60]),
+        PhraseType::Outro => Rgb([120, 90, 160]),
+        PhraseType::Unknown => Rgb([90, 90, 90]),
+    }
+}
+
+/// Vertical band of the image one lane is drawn into
+#[derive(Debug, Clone, Copy)]
+struct Lane {
+    top: u32,
+    height: u32,
+}
+
+impl Lane {
+    /// Row for `value` in 0..=1, measured up from the lane's bottom edge
+    fn row(&self, value: f32) -> u32 {
+        let value = value.clamp(0.0, 1.0);
+        self.top + self.height - 1 - (value * (self.height - 1) as f32).round() as u32
+    }
+}
+
+struct Canvas {
+    image: RgbImage,
+    pixels_per_second: f64,
+}
+
+impl Canvas {
+    fn column(&self, time: f64) -> Option<u32> {
+        let x = (time * self.pixels_per_second).round();
+        (x >= 0.0 && x < self.image.width() as f64).then_some(x as u32)
+    }
+
+    fn vline(&mut self, x: u32, from: u32, to: u32, color: Rgb<u8>) {
+        for y in from.min(to)..=from.max(to).min(self.image.height() - 1) {
+            self.image.put_pixel(x, y, color);
+        }
+    }
+
+    fn fill(&mut self, x0: u32, x1: u32, lane: Lane, color: Rgb<u8>) {
+        for x in x0..x1.min(self.image.width()) {
+            self.vline(x, lane.top, lane.top + lane.height - 1, color);
+        }
+    }
+
+    /// Connected line through one value per column
+    fn curve(&mut self, lane: Lane, values: &[f32], color: Rgb<u8>) {
+        let mut previous: Option<u32> = None;
+        for (x, &value) in values.iter().enumerate() {
+            let row = lane.row(value);
+            self.vline(x as u32, previous.unwrap_or(row), row, color);
+            previous = Some(row);
+        }
+    }
+}
+
+/// Resample a series sampled at `rate` per second to one value per column
+///
+/// Columns covering several samples take their maximum so short peaks stay
+/// visible; columns between samples hold the previous one.
+fn per_column(series: &[f32], rate: f64, width: u32, pixels_per_second: f64) -> Vec<f32> {
+    (0..width as usize)
+        .map(|x| {
+            let start = (x as f64 / pixels_per_second * rate) as usize;
+            let end = (((x + 1) as f64 / pixels_per_second * rate) as usize).max(start + 1);
+            series
+                .get(start..end.min(series.len()))
+                .map_or(0.0, |window| window.iter().copied().fold(0.0f32, f32::max))
+        })
+        .collect()
+}
+
+fn normalized(values: &mut [f32]) {
+    let max = values.iter().copied().fold(0.0f32, f32::max);
+    if max > 0.0 {
+        values.iter_mut().for_each(|v| *v /= max);
+    }
+}
+
+/// Draw the debug plot for an analysis
+///
+/// `audio` adds the waveform lane and `timeline` the cut lines; both are
+/// optional so a plot can be made straight after analysis.
+pub fn render_plot(
+    analysis: &AudioAnalysis,
+    audio: Option<&AudioData>,
+    timeline: Option<&CompositionTimeline>,
+    pixels_per_second: f64,
+) -> RgbImage {
+    let duration = analysis.duration.max(0.1);
+    let width = ((duration * pixels_per_second).ceil() as u32).clamp(1, MAX_WIDTH);
+    let pixels_per_second = width as f64 / duration;
+
+    let lane_count = if audio.is_some() { 5 } else { 4 };
+    let height = lane_count * (LANE_HEIGHT + LANE_GAP) + LANE_GAP;
+    let mut lanes = (0..lane_count).map(|i| Lane {
+        top: LANE_GAP + i * (LANE_HEIGHT + LANE_GAP),
+        height: LANE_HEIGHT,
+    });
+
+    let mut canvas = Canvas {
+        image: RgbImage::from_pixel(width, height, BACKGROUND),
+        pixels_per_second,
+    };
+
+    let all_lanes: Vec<Lane> = lanes.clone().collect();
+    for &lane in &all_lanes {
+        canvas.fill(0, width, lane, LANE_BACKGROUND);
+    }
+    for second in 0..=duration as u32 {
+        if let Some(x) = canvas.column(second as f64) {
+            let color = if second % 10 == 0 { GRID_MAJOR } else { GRID };
+            for &lane in &all_lanes {
+                canvas.vline(x, lane.top, lane.top + lane.height - 1, color);
+            }
+        }
+    }
+
+    // Waveform: min and max of the mono mix per column
+    if let Some(audio) = audio {
+        let lane = lanes.next().unwrap();
+        let samples = audio.mono_samples();
+        let per_pixel = (audio.sample_rate as f64 / pixels_per_second).max(1.0);
+
+        for x in 0..width {
+            let start = (x as f64 * per_pixel) as usize;
+            let end = (((x + 1) as f64 * per_pixel) as usize).min(samples.len());
+            if start >= end {
+                break;
+            }
+
+            let (low, high) = samples[start..end]
+                .iter()
+                .fold((0.0f32, 0.0f32), |(lo, hi), &s| (lo.min(s), hi.max(s)));
+            canvas.vline(x, lane.row(0.5 + low * 0.5), lane.row(0.5 + high * 0.5), WAVEFORM);
+        }
+    }
+
+    // Onset detection function, spread over the song, with the picked onsets
+    let lane = lanes.next().unwrap();
+    let odf = &analysis.spectral_features.onset_detection_function;
+    if !odf.is_empty() {
+        let mut columns = per_column(odf, odf.len() as f64 / duration, width, pixels_per_second);
+        normalized(&mut columns);
+        canvas.curve(lane, &columns, ONSET_FUNCTION);
+    }
+    for &onset in &analysis.onsets {
+        if let Some(x) = canvas.column(onset) {
+            canvas.vline(x, lane.top, lane.top + lane.height / 5, ONSET);
+        }
+    }
+
+    // Beats, colored by type and as tall as they are strong
+    let lane = lanes.next().unwrap();
+    for beat in &analysis.beats {
+        if let Some(x) = canvas.column(beat.time) {
+            let strength = 0.25 + 0.75 * beat.strength.clamp(0.0, 1.0);
+            canvas.vline(x, lane.row(0.0), lane.row(strength), beat_color(&beat.beat_type));
+        }
+    }
+
+    // RMS energy
+    let lane = lanes.next().unwrap();
+    if analysis.energy_levels.len() > 1 {
+        let step = analysis.energy_levels[1].time - analysis.energy_levels[0].time;
+        let rms: Vec<f32> = analysis.energy_levels.iter().map(|e| e.rms).collect();
+        let mut columns = per_column(&rms, 1.0 / step.max(1e-3), width, pixels_per_second);
+        normalized(&mut columns);
+        for (x, &value) in columns.iter().enumerate() {
+            canvas.vline(x as u32, lane.row(0.0), lane.row(value), ENERGY);
+        }
+    }
+
+    // Phrases as colored blocks, with a gap marking each boundary
+    let lane = lanes.next().unwrap();
+    for phrase in &analysis.phrases {
+        let x0 = canvas.column(phrase.start).unwrap_or(0);
+        let x1 = canvas.column(phrase.end).unwrap_or(width);
+        canvas.fill(x0 + 1, x1, lane, phrase_color(phrase.phrase_type));
+    }
+
+    // Cuts through every lane
+    if let Some(timeline) = timeline {
+        for &cut in &timeline.cuts {
+            if let Some(x) = canvas.column(cut) {
+                canvas.vline(x, 0, height - 1, CUT);
+            }
+        }
+    }
+
+    canvas.image
+}
+
+/// Draw the debug plot at the default resolution and save it as a PNG
+pub fn save_plot<P: AsRef<Path>>(
+    path: P,
+    analysis: &AudioAnalysis,
+    audio: Option<&AudioData>,
+    timeline: Option<&CompositionTimeline>,
+) -> Result<()> {
+    render_plot(analysis, audio, timeline, DEFAULT_PIXELS_PER_SECOND)
+        .save(path.as_ref())
+        .map_err(|e| CompositionError::OutputFailed {
+            reason: format!("Failed to write debug plot {:?}: {}", path.as_ref(), e),
+        })?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::audio::types::{AnalysisBuilder, AudioFormat};
+    use std::path::PathBuf;
+
+    fn analysis() -> AudioAnalysis {
+        let builder = AnalysisBuilder::new(4.0)
+            .beat(1.0, BeatType::Downbeat)
+            .beat(1.5, BeatType::Offbeat)
+            .phrase(0.0, 2.0, PhraseType::Verse)
+            .phrase(2.0, 4.0, PhraseType::Chorus);
+        let mut analysis = (0..40)
+            .fold(builder, |builder, i| builder.energy(i as f64 * 0.1, if i < 20 { 0.1 } else { 0.8 }))
+            .build();
+        analysis.onsets = vec![1.0];
+        analysis.spectral_features.onset_detection_function = vec![0.0, 0.0, 1.0, 0.0];
+        analysis
+    }
+
+    #[test]
+    fn test_plot_layout() {
+        let image = render_plot(&analysis(), None, None, 50.0);
+        assert_eq!(image.width(), 200);
+        assert_eq!(image.height(), 4 * (LANE_HEIGHT + LANE_GAP) + LANE_GAP);
+
+        let audio = AudioData {
+            samples: vec![0.5; 4 * 8000],
+            sample_rate: 8000,
+            channels: 1,
+            duration: 4.0,
+            file_path: PathBuf::from("song.wav"),
+            format: AudioFormat { extension: "wav".to_string(), bit_depth: None, compression: None, bitrate: None },
+        };
+        let image = render_plot(&analysis(), Some(&audio), None, 50.0);
+        assert_eq!(image.height(), 5 * (LANE_HEIGHT + LANE_GAP) + LANE_GAP);
+
+        // Waveform reaches three quarters up its lane
+        let lane = Lane { top: LANE_GAP, height: LANE_HEIGHT };
+        assert_eq!(*image.get_pixel(100, lane.row(0.74)), WAVEFORM);
+    }
+
+    #[test]
+    fn test_beats_phrases_and_cuts_are_drawn() {
+        let mut timeline = CompositionTimeline::new();
+        timeline.add_cut(0.0, 1);
+        timeline.add_cut(3.0, 2);
+
+        let image = render_plot(&analysis(), None, Some(&timeline), 50.0);
+        let lane = |i: u32| Lane { top: LANE_GAP + i * (LANE_HEIGHT + LANE_GAP), height: LANE_HEIGHT };
+
+        // Onset tick at the top of the onset lane, beats colored by type
+        assert_eq!(*image.get_pixel(50, lane(0).top), ONSET);
+        assert_eq!(*image.get_pixel(50, lane(1).row(0.1)), beat_color(&BeatType::Downbeat));
+        assert_eq!(*image.get_pixel(75, lane(1).row(0.1)), beat_color(&BeatType::Offbeat));
+
+        // Energy is louder in the second half
+        assert_eq!(*image.get_pixel(160, lane(2).row(0.5)), ENERGY);
+        assert_ne!(*image.get_pixel(40, lane(2).row(0.5)), ENERGY);
+
+        assert_eq!(*image.get_pixel(50, lane(3).row(0.5)), phrase_color(PhraseType::Verse));
+        assert_eq!(*image.get_pixel(120, lane(3).row(0.5)), phrase_color(PhraseType::Chorus));
+
+        // The cut at 3s crosses every lane
+        for i in 0..4 {
+            assert_eq!(*image.get_pixel(150, lane(i).row(0.5)), CUT);
+        }
+    }
+}
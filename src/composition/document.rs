@@ -0,0 +1,346 @@
+//! Editable timeline documents
+//!
+//! A generated timeline can be written out as JSON or TOML, tweaked by hand
+//! and fed back in to re-render without running automatic cut placement. The
+//! format is picked from the file extension.
+
+use std::collections::BTreeMap;
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+
+use crate::composition::engine::{CompositionTimeline, SegmentOverrides};
+use crate::error::{CompositionError, ConfigError, Result};
+use crate::styles::ConfigValue;
+use crate::video::{TransitionType, VideoClip, VideoProcessor};
+
+/// One segment of the timeline, from its cut to the next
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SegmentEntry {
+    /// Cut time in the composition (seconds)
+    pub start: f64,
+
+    /// Sequence number of the clip shown
+    pub clip: u32,
+
+    /// Clip name, for readability only
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub clip_name: Option<String>,
+
+    /// Where in the clip the segment starts (seconds); centered when missing
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub source_in: Option<f64>,
+
+    /// Transition into this segment
+    #[serde(default)]
+    pub transition: TransitionType,
+
+    /// Style parameters for this segment only
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub style: BTreeMap<String, ConfigValue>,
+}
+
+/// A whole timeline as written to disk
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TimelineDocument {
+    /// Length of the composition (seconds)
+    pub duration: f64,
+
+    /// Segments in playback order
+    pub segments: Vec<SegmentEntry>,
+}
+
+/// On-disk formats, chosen by file extension
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Format {
+    Json,
+    Toml,
+}
+
+impl Format {
+    fn from_path(path: &Path) -> Result<Self> {
+        match path.extension().and_then(|e| e.to_str()).map(|e| e.to_lowercase()).as_deref() {
+            Some("json") => Ok(Self::Json),
+            Some("toml") => Ok(Self::Toml),
+            _ => Err(ConfigError::InvalidValue {
+                key: "timeline".to_string(),
+                value: format!("{} (expected .json or .toml)", path.display()),
+            }.into()),
+        }
+    }
+}
+
+impl TimelineDocument {
+    /// Describe a timeline, filling in the in-points the renderer would choose
+    pub fn from_timeline(timeline: &CompositionTimeline, clips: &[VideoClip], duration: f64) -> Self {
+        let segments = (0..timeline.cuts.len())
+            .map(|i| {
+                let clip_id = timeline.clip_assignments[i];
+                let clip = clips.iter().find(|c| c.sequence_number == clip_id);
+                let overrides = timeline.overrides.get(i).cloned().unwrap_or_default();
+                let segment_duration = timeline.segment_duration(i, duration);
+
+                let source_in = overrides.source_in.or_else(|| {
+                    clip.and_then(|c| c.duration)
+                        .map(|d| VideoProcessor::source_in_point(d, segment_duration))
+                });
+
+                SegmentEntry {
+                    start: timeline.cuts[i],
+                    clip: clip_id,
+                    clip_name: clip.map(|c| c.name.clone()),
+                    source_in,
+                    transition: timeline.transitions.get(i).copied().unwrap_or_default(),
+                    style: overrides.style,
+                }
+            })
+            .collect();
+
+        Self { duration, segments }
+    }
+
+    /// Build the timeline the renderer works from
+    pub fn to_timeline(&self) -> CompositionTimeline {
+        let mut timeline = CompositionTimeline::new();
+
+        for (i, segment) in self.segments.iter().enumerate() {
+            timeline.add_cut_with_transition(segment.start, segment.clip, segment.transition);
+            timeline.set_overrides(i, SegmentOverrides {
+                source_in: segment.source_in,
+                style: segment.style.clone(),
+            });
+        }
+
+        timeline
+    }
+
+    /// Check the document describes a playable timeline
+    pub fn validate(&self) -> Result<()> {
+        let invalid = |details: String| -> Result<()> {
+            Err(CompositionError::InvalidParameters { details }.into())
+        };
+
+        if !(self.duration.is_finite() && self.duration > 0.0) {
+            return invalid(format!("timeline duration must be positive, got {}", self.duration));
+        }
+
+        if let Some(segment) = self.segments.iter().find(|s| !s.start.is_finite()) {
+            return invalid(format!("segment start must be a finite time, got {}", segment.start));
+        }
+
+        match self.segments.first() {
+            None => return invalid("timeline has no segments".to_string()),
+            Some(first) if first.start != 0.0 => {
+                return invalid(format!("first segment must start at 0, got {}", first.start));
+            }
+            _ => {}
+        }
+
+        for (i, pair) in self.segments.windows(2).enumerate() {
+            if pair[1].start <= pair[0].start {
+                return invalid(format!("segment {} starts at {} before the previous one", i + 1, pair[1].start));
+            }
+        }
+
+        if let Some(last) = self.segments.last().filter(|s| s.start >= self.duration) {
+            return invalid(format!("segment starts at {} past the end ({})", last.start, self.duration));
+        }
+
+        let bad_source_in = |t: f64| !(t.is_finite() && t >= 0.0);
+        if let Some(segment) = self.segments.iter().find(|s| s.source_in.is_some_and(bad_source_in)) {
+            return invalid(format!("segment at {} has a negative or non-finite source_in", segment.start));
+        }
+
+        Ok(())
+    }
+
+    /// Check every segment shows one of the loaded clips
+    pub fn check_clips(&self, clips: &[VideoClip]) -> Result<()> {
+        let missing = self
+            .segments
+            .iter()
+            .enumerate()
+            .find(|(_, segment)| !clips.iter().any(|c| c.sequence_number == segment.clip));
+
+        match missing {
+            Some((i, segment)) => {
+                let mut available: Vec<u32> = clips.iter().map(|c| c.sequence_number).collect();
+                available.sort_unstable();
+                Err(CompositionError::InvalidParameters {
+                    details: format!(
+                        "segment {} at {}s uses clip {}, which is not among the loaded clips {:?}",
+                        i, segment.start, segment.clip, available
+                    ),
+                }.into())
+            }
+            None => Ok(()),
+        }
+    }
+
+    /// Read and validate a document from a `.json` or `.toml` file
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let path = path.as_ref();
+        let format = Format::from_path(path)?;
+        let content = std::fs::read_to_string(path)
+            .map_err(|_| ConfigError::FileNotFound { path: path.display().to_string() })?;
+
+        let parse_failed = || ConfigError::ParseFailed { path: path.display().to_string() };
+        let document: Self = match format {
+            Format::Json => serde_json::from_str(&content).map_err(|_| parse_failed())?,
+            Format::Toml => toml::from_str(&content).map_err(|_| parse_failed())?,
+        };
+
+        document.validate()?;
+        Ok(document)
+    }
+
+    /// Write the document as `.json` or `.toml`, depending on the extension
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> Result<()> {
+        let path = path.as_ref();
+
+        let content = match Format::from_path(path)? {
+            Format::Json => serde_json::to_string_pretty(self).map_err(|e| e.to_string()),
+            Format::Toml => toml::to_string_pretty(self).map_err(|e| e.to_string()),
+        }
+        .map_err(|reason| CompositionError::OutputFailed { reason })?;
+
+        std::fs::write(path, content)?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    fn timeline() -> CompositionTimeline {
+        let mut timeline = CompositionTimeline::new();
+        timeline.add_cut(0.0, 1);
+        timeline.add_cut_with_transition(4.0, 2, TransitionType::WhipPan);
+        timeline.add_cut(7.5, 1);
+
+        let mut style = BTreeMap::new();
+        style.insert("intensity".to_string(), ConfigValue::Float(0.3));
+        timeline.set_overrides(1, SegmentOverrides { source_in: Some(2.0), style });
+        timeline
+    }
+
+    fn clips() -> Vec<VideoClip> {
+        let mut long = VideoClip::new("01_street.mp4", 1, "street".to_string());
+        long.duration = Some(20.0);
+        vec![long, VideoClip::new("02_beach.mp4", 2, "beach".to_string())]
+    }
+
+    #[test]
+    fn test_document_lists_segments_with_in_points() {
+        let document = TimelineDocument::from_timeline(&timeline(), &clips(), 10.0);
+
+        assert_eq!(document.segments.len(), 3);
+        assert_eq!(document.segments[0].clip_name.as_deref(), Some("street"));
+
+        // 20s clip, 4s segment: centered in-point
+        assert_eq!(document.segments[0].source_in, Some(8.0));
+        // A hand-set in-point wins over the automatic one
+        assert_eq!(document.segments[1].source_in, Some(2.0));
+        assert_eq!(document.segments[1].transition, TransitionType::WhipPan);
+        assert!(document.segments[1].style.contains_key("intensity"));
+        assert_eq!(document.segments[2].source_in, Some(8.75));
+    }
+
+    #[test]
+    fn test_roundtrip_json_and_toml() {
+        let dir = tempdir().unwrap();
+        let document = TimelineDocument::from_timeline(&timeline(), &clips(), 10.0);
+
+        for name in ["timeline.json", "timeline.toml"] {
+            let path = dir.path().join(name);
+            document.save(&path).unwrap();
+
+            let loaded = TimelineDocument::load(&path).unwrap();
+            let rebuilt = loaded.to_timeline();
+
+            assert_eq!(loaded.duration, 10.0);
+            assert_eq!(rebuilt.cuts, vec![0.0, 4.0, 7.5]);
+            assert_eq!(rebuilt.clip_assignments, vec![1, 2, 1]);
+            assert_eq!(rebuilt.transitions[1], TransitionType::WhipPan);
+            assert_eq!(rebuilt.overrides[1].source_in, Some(2.0));
+
+            let style = rebuilt.overrides[1].apply_style(Default::default());
+            assert!((style.intensity - 0.3).abs() < 1e-6);
+        }
+
+        assert!(document.save(dir.path().join("timeline.txt")).is_err());
+    }
+
+    #[test]
+    fn test_hand_edited_json_loads() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("edited.json");
+        std::fs::write(&path, r#"{
+            "duration": 12.0,
+            "segments": [
+                { "start": 0.0, "clip": 3 },
+                { "start": 5.0, "clip": 1, "transition": "dip_to_black", "style": { "noise_level": 0.9 } }
+            ]
+        }"#).unwrap();
+
+        let timeline = TimelineDocument::load(&path).unwrap().to_timeline();
+
+        assert_eq!(timeline.cuts, vec![0.0, 5.0]);
+        assert_eq!(timeline.transitions[1], TransitionType::DipToBlack);
+        let style = timeline.overrides[1].apply_style(Default::default());
+        assert_eq!(style.get_f32("noise_level"), Some(0.9));
+    }
+
+    #[test]
+    fn test_unknown_clips_are_rejected() {
+        let mut document = TimelineDocument::from_timeline(&timeline(), &clips(), 10.0);
+        assert!(document.check_clips(&clips()).is_ok());
+
+        document.segments[2].clip = 9;
+        let error = document.check_clips(&clips()).unwrap_err().to_string();
+        assert!(error.contains("segment 2"), "{}", error);
+
+        document.segments[2].clip = 0;
+        assert!(document.check_clips(&clips()).is_err());
+    }
+
+    #[test]
+    fn test_invalid_documents_are_rejected() {
+        let mut document = TimelineDocument::from_timeline(&timeline(), &clips(), 10.0);
+        assert!(document.validate().is_ok());
+
+        document.segments.swap(1, 2);
+        assert!(document.validate().is_err());
+
+        let mut document = TimelineDocument::from_timeline(&timeline(), &clips(), 10.0);
+        document.segments[0].start = 1.0;
+        assert!(document.validate().is_err());
+
+        let mut document = TimelineDocument::from_timeline(&timeline(), &clips(), 7.0);
+        document.duration = 7.0;
+        assert!(document.validate().is_err());
+    }
+
+    #[test]
+    fn test_non_finite_times_are_rejected() {
+        let mut document = TimelineDocument::from_timeline(&timeline(), &clips(), 10.0);
+        document.duration = f64::INFINITY;
+        assert!(document.validate().is_err());
+
+        let mut document = TimelineDocument::from_timeline(&timeline(), &clips(), 10.0);
+        document.segments[1].start = f64::NAN;
+        assert!(document.validate().is_err());
+
+        let mut document = TimelineDocument::from_timeline(&timeline(), &clips(), 10.0);
+        document.segments[1].source_in = Some(f64::INFINITY);
+        assert!(document.validate().is_err());
+
+        // TOML spells these literally, so a hand-edited file can carry them
+        let mut value = toml::Value::try_from(TimelineDocument::from_timeline(&timeline(), &clips(), 10.0)).unwrap();
+        value["duration"] = toml::Value::Float(f64::INFINITY);
+        let document: TimelineDocument = toml::from_str(&toml::to_string(&value).unwrap()).unwrap();
+        assert!(document.validate().is_err());
+    }
+}
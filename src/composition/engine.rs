@@ -1,25 +1,100 @@
 // src/composition/engine.rs - Improved video selection logic
 
-use std::path::Path;
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
 use tracing::{info, debug, warn};
 
+use super::diagnostics;
+use super::document::TimelineDocument;
+use super::interchange;
+use super::phrasing::PhrasePlan;
+use super::strategy::{BeatContext, ClipContext, CutStrategy, CutStrategyRegistry};
 use crate::{
-    audio::{AudioLoader, AudioAnalyzer, AudioAnalysis},
-    config::Config,
+    audio::{AnalysisCache, AudioAnalyzer, AudioAnalysis, AudioLoader},
+    config::{CompositionConfig, Config},
     error::{CompositionError, Result},
-    styles::Style,
-    video::{VideoLoader, VideoProcessor, VideoCompositor, VideoSequence, VideoClip},
+    styles::{ConfigValue, MusicContext, Style, StyleConfig},
+    video::{transitions, FrameClock, ProcessedSegment, TransitionType, VideoLoader, VideoParams, VideoProcessor, VideoCompositor, VideoSequence, VideoClip},
 };
 
+/// The audio a video is rendered against
+struct Soundtrack<'a> {
+    path: &'a Path,
+    duration: f64,
+
+    /// Beats, energy and sections for styles to react to
+    music: MusicContext,
+}
+
 /// Main composition engine that orchestrates the entire retro video creation process
 pub struct CompositionEngine {
     config: Config,
     style: Box<dyn Style>,
+    timeline: Option<TimelineDocument>,
+    timeline_export: Option<PathBuf>,
+    analysis_cache: Option<AnalysisCache>,
+    debug_plot: Option<PathBuf>,
+    strategies: CutStrategyRegistry,
 }
 
 impl CompositionEngine {
     pub fn new(config: Config, style: Box<dyn Style>) -> Self {
-        Self { config, style }
+        Self {
+            config,
+            style,
+            timeline: None,
+            timeline_export: None,
+            analysis_cache: Some(AnalysisCache::default_location()),
+            debug_plot: None,
+            strategies: CutStrategyRegistry::new(),
+        }
+    }
+
+    /// Render this timeline instead of generating one from the audio
+    pub fn with_timeline(mut self, timeline: TimelineDocument) -> Self {
+        self.timeline = Some(timeline);
+        self
+    }
+
+    /// Write the timeline being rendered to a file
+    ///
+    /// `.edl` and `.fcpxml` produce cut lists for editing applications; any
+    /// other extension is saved as an editable `.json` or `.toml` document.
+    pub fn with_timeline_export<P: Into<PathBuf>>(mut self, path: P) -> Self {
+        self.timeline_export = Some(path.into());
+        self
+    }
+
+    /// Keep audio analysis results in `cache` instead of the default location
+    pub fn with_analysis_cache(mut self, cache: AnalysisCache) -> Self {
+        self.analysis_cache = Some(cache);
+        self
+    }
+
+    /// Always analyze the audio from scratch and don't store the result
+    pub fn without_analysis_cache(mut self) -> Self {
+        self.analysis_cache = None;
+        self
+    }
+
+    /// Draw the audio analysis and the cuts made from it to a PNG
+    ///
+    /// Only applies when the timeline is generated from the audio.
+    pub fn with_debug_plot<P: Into<PathBuf>>(mut self, path: P) -> Self {
+        self.debug_plot = Some(path.into());
+        self
+    }
+
+    /// Make a custom cut strategy available under `name`
+    ///
+    /// Select it with `composition.cut_strategy`. The factory is called once
+    /// per generated timeline.
+    pub fn with_cut_strategy<F>(mut self, name: impl Into<String>, factory: F) -> Self
+    where
+        F: Fn(&CompositionConfig) -> Box<dyn CutStrategy> + Send + Sync + 'static,
+    {
+        self.strategies.register(name, factory);
+        self
     }
 
     pub async fn compose<P: AsRef<Path>>(
@@ -28,68 +103,150 @@ impl CompositionEngine {
         video_dir: P,
         output_path: P,
     ) -> Result<()> {
-        let audio_path = audio_path.as_ref();
-        let video_dir = video_dir.as_ref();
-        let output_path = output_path.as_ref();
+        let params = self.config.video.params.clone();
+        self.compose_with_params(audio_path.as_ref(), video_dir.as_ref(), output_path.as_ref(), &params).await
+    }
+
+    /// Render a quick low-resolution, low-frame-rate proxy of the composition
+    ///
+    /// Runs the same analysis and timeline generation as `compose`, so the full
+    /// render matches the preview cut for cut. Cuts stay on the full render's
+    /// frame grid; a segment shorter than one preview frame is simply not seen.
+    /// Sizing comes from `config.preview`.
+    pub async fn compose_preview<P: AsRef<Path>>(
+        &self,
+        audio_path: P,
+        video_dir: P,
+        output_path: P,
+    ) -> Result<()> {
+        let params = self.config.preview.apply(&self.config.video.params);
+
+        info!("👀 Preview render at {}x{}, {:.1} fps",
+              params.resolution.0, params.resolution.1, params.fps.as_f64());
+
+        self.compose_with_params(audio_path.as_ref(), video_dir.as_ref(), output_path.as_ref(), &params).await
+    }
 
+    async fn compose_with_params(
+        &self,
+        audio_path: &Path,
+        video_dir: &Path,
+        output_path: &Path,
+        params: &VideoParams,
+    ) -> Result<()> {
         info!("🎬 Starting Retro-Compositor composition");
         info!("   Audio: {:?}", audio_path);
         info!("   Videos: {:?}", video_dir);
         info!("   Output: {:?}", output_path);
         info!("   Style: {}", self.style.name());
 
-        // Pipeline Step 1: Audio Analysis
-        let audio_analysis = self.analyze_audio(audio_path).await?;
+        let (video_sequence, timeline, soundtrack) = match &self.timeline {
+            Some(document) => {
+                info!("⏱️  Using timeline with {} segments from file", document.segments.len());
+                if self.debug_plot.is_some() {
+                    warn!("Debug plot skipped: the timeline was not generated from the audio");
+                }
+
+                // Styles still follow the music; without an analysis they just don't react
+                let music = match self.analyze_audio(audio_path).await {
+                    Ok(analysis) => MusicContext::from_analysis(&analysis),
+                    Err(e) => {
+                        warn!("Rendering without musical context: {}", e);
+                        MusicContext::default()
+                    }
+                };
+
+                let video_sequence = self.load_video_clips(video_dir).await?;
+                document.check_clips(video_sequence.clips())?;
+
+                let soundtrack = Soundtrack { path: audio_path, duration: document.duration, music };
+                (video_sequence, document.to_timeline(), soundtrack)
+            }
+            None => {
+                // Pipeline Step 1: Audio Analysis
+                let audio_analysis = self.analyze_audio(audio_path).await?;
+
+                // Pipeline Step 2: Video Discovery and Loading
+                let video_sequence = self.load_video_clips(video_dir).await?;
+
+                // Pipeline Step 3: Timeline Generation
+                let timeline = self.generate_timeline(&audio_analysis, &video_sequence).await?;
+
+                if let Some(path) = &self.debug_plot {
+                    let audio = AudioLoader::load(audio_path).await?;
+                    diagnostics::save_plot(path, &audio_analysis, Some(&audio), Some(&timeline))?;
+                    info!("   Debug plot written to {:?}", path);
+                }
 
-        // Pipeline Step 2: Video Discovery and Loading
-        let video_sequence = self.load_video_clips(video_dir).await?;
+                let soundtrack = Soundtrack {
+                    path: audio_path,
+                    duration: audio_analysis.duration,
+                    music: MusicContext::from_analysis(&audio_analysis),
+                };
+                (video_sequence, timeline, soundtrack)
+            }
+        };
 
-        // Pipeline Step 3: Timeline Generation
-        let timeline = self.generate_timeline(&audio_analysis, &video_sequence).await?;
+        if let Some(path) = &self.timeline_export {
+            let document = TimelineDocument::from_timeline(&timeline, video_sequence.clips(), soundtrack.duration);
+            self.export_timeline(&document, video_sequence.clips(), audio_path, output_path, path)?;
+            info!("   Timeline written to {:?}", path);
+        }
 
-        // Pipeline Step 4: Video Processing with Effects
-        let processed_segments = self.process_video_with_effects(
+        // Pipeline Step 4: Video Processing with Effects, encoded as it is produced
+        self.render_video(
             &video_sequence,
             &timeline,
-            &audio_analysis
+            &soundtrack,
+            params,
+            output_path,
         ).await?;
 
-        // Pipeline Step 5: Final Output Generation
-        self.generate_final_output(&processed_segments, audio_path, output_path).await?;
-
         info!("🎉 Composition complete! Output saved to: {:?}", output_path);
         Ok(())
     }
 
+    /// Write `document` in the format implied by the extension of `path`
+    ///
+    /// Cut lists always describe the final deliverable, even when rendering a preview.
+    fn export_timeline(
+        &self,
+        document: &TimelineDocument,
+        clips: &[VideoClip],
+        audio_path: &Path,
+        output_path: &Path,
+        path: &Path,
+    ) -> Result<()> {
+        let title = output_path
+            .file_stem()
+            .map(|s| s.to_string_lossy().into_owned())
+            .unwrap_or_else(|| "Retro Composition".to_string());
+
+        let params = &self.config.video.params;
+        let content = match path.extension().and_then(|e| e.to_str()).map(|e| e.to_lowercase()).as_deref() {
+            Some("edl") => interchange::to_edl(document, clips, params.fps, &title),
+            Some("fcpxml") => interchange::to_fcpxml(document, clips, params, &title, Some(audio_path)),
+            _ => return document.save(path),
+        };
+
+        std::fs::write(path, content)?;
+        Ok(())
+    }
+
     // Audio analysis (unchanged)
     async fn analyze_audio(&self, audio_path: &Path) -> Result<AudioAnalysis> {
         info!("🎵 Step 1: Analyzing audio file...");
 
-        debug!("Loading audio from: {:?}", audio_path);
-        let audio_data = AudioLoader::load(audio_path).await
+        let analysis_config = self.config.audio.analysis_config();
+        debug!("Running audio analysis with sensitivity: {:.1}", analysis_config.beat_sensitivity);
+
+        let analyzer = AudioAnalyzer::with_config(analysis_config);
+        let analysis = analyzer.analyze_file(audio_path, self.analysis_cache.as_ref()).await
             .map_err(|e| {
-                warn!("Failed to load audio file: {}", e);
+                warn!("Failed to analyze audio file: {}", e);
                 e
             })?;
 
-        info!("   Loaded: {:.1}s, {} Hz, {} channels",
-              audio_data.duration, audio_data.sample_rate, audio_data.channels);
-
-        let analysis_config = crate::audio::types::AnalysisConfig {
-            window_size: self.config.audio.window_size,
-            hop_size: self.config.audio.hop_size,
-            min_bpm: self.config.audio.min_bpm,
-            max_bpm: self.config.audio.max_bpm,
-            beat_sensitivity: self.config.audio.beat_sensitivity,
-            energy_window_size: 0.1,
-            detect_phrases: true,
-            calculate_spectral_features: true,
-        };
-
-        debug!("Running audio analysis with sensitivity: {:.1}", analysis_config.beat_sensitivity);
-        let analyzer = AudioAnalyzer::with_config(analysis_config);
-        let analysis = analyzer.analyze(&audio_data).await?;
-
         info!("   ✅ Analysis complete:");
         info!("      Beats detected: {}", analysis.beats.len());
         info!("      BPM: {:.1} (confidence: {:.2})", analysis.bpm, analysis.bpm_confidence);
@@ -153,48 +310,138 @@ impl CompositionEngine {
             }.into());
         }
 
+        let strategy_name = &self.config.composition.cut_strategy;
+        let mut strategy = self.strategies
+            .create(strategy_name, &self.config.composition)
+            .ok_or_else(|| CompositionError::InvalidParameters {
+                details: format!("Unknown cut strategy: {}", strategy_name),
+            })?;
+
         let mut timeline = CompositionTimeline::new();
-        let available_clips: Vec<u32> = video_sequence.iter()
+        let clips = video_sequence.clips();
+        let available_clips: Vec<u32> = clips.iter()
             .map(|clip| clip.sequence_number)
             .collect();
 
+        // Song structure drives cut placement, clip pools and cut density
+        let plan = if self.config.composition.phrase_aware_cuts && !audio_analysis.phrases.is_empty() {
+            PhrasePlan::new(&audio_analysis.phrases, clips, &self.config.composition)
+        } else {
+            PhrasePlan::flat(clips, &self.config.composition)
+        };
+
         debug!("Available clips: {:?}", available_clips);
-        debug!("Processing {} beats", audio_analysis.beats.len());
+        debug!("Processing {} beats with the {} strategy", audio_analysis.beats.len(), strategy.name());
+
+        strategy.prepare(audio_analysis, clips);
+        let choose_clip = |strategy: &mut dyn CutStrategy, time: f64, previous: Option<u32>| {
+            strategy.next_clip(&ClipContext {
+                time,
+                phrase_type: plan.phrase_type_at(time),
+                pool: plan.pool_at(time),
+                clips,
+                previous,
+                analysis: audio_analysis,
+                seed: self.config.seed,
+            })
+        };
 
-        // **IMPROVED ALGORITHM**: Use all available clips with smart rotation
-        timeline.add_cut(0.0, available_clips[0]);
+        let transition = self.config.composition.transition;
+        let first_clip = choose_clip(strategy.as_mut(), 0.0, None).unwrap_or(available_clips[0]);
+        timeline.add_cut(0.0, first_clip);
 
-        let mut clip_rotation_index = 0;
+        let boundaries = if strategy.follows_structure() { plan.boundaries() } else { Vec::new() };
+        let mut next_boundary = 0;
         let mut last_cut_time = 0.0;
+        let mut last_clip = first_clip;
         let mut segment_count = 0;
+        // Parallel to the timeline's cuts: phrase cuts (and the opening cut) are never dropped
+        let mut forced = vec![true];
 
-        // Process each beat for potential cuts
-        for beat in &audio_analysis.beats {
-            let time_since_last_cut = beat.time - last_cut_time;
+        // A phrase cut snaps to a beat within half a local beat period of the phrase start
+        let snap_tolerance = |time: f64| -> f64 {
+            let bpm = audio_analysis.tempo_at_time(time);
+            if bpm > 0.0 { 30.0 / bpm as f64 } else { 0.25 }
+        };
+
+        // Process each beat for potential cuts, then phrases starting after the last beat
+        // (an outro over a fade or silence)
+        for beat in audio_analysis.beats.iter().map(Some).chain(std::iter::once(None)) {
+            // Phrase starts always get a cut, on the beat when one is close enough.
+            // A beat can reach several phrase starts when phrases are short or fall
+            // in a gap between beats, and each of them gets its own cut.
+            let mut pending: Vec<(f64, f64, bool)> = Vec::new();
+            while let Some(&boundary) = boundaries.get(next_boundary) {
+                let time = match beat {
+                    Some(beat) => {
+                        let tolerance = snap_tolerance(boundary);
+                        if beat.time < boundary - tolerance {
+                            break;
+                        }
+                        if (beat.time - boundary).abs() <= tolerance { beat.time } else { boundary }
+                    }
+                    None if boundary < audio_analysis.duration => boundary,
+                    None => break,
+                };
+                next_boundary += 1;
+
+                // Two phrase starts snapping to the same beat keep their own times instead
+                let previous = pending.last().map(|&(t, _, _)| t).or(timeline.cuts.last().copied());
+                let time = if previous.is_some_and(|t| t >= time) { boundary } else { time };
+                pending.push((time, boundary, true));
+            }
 
-            // Determine if we should cut at this beat
-            let should_cut = self.should_cut_at_beat(
-                beat,
-                time_since_last_cut,
-                audio_analysis
-            );
+            // The section is judged at the phrase start, even if the cut snapped just before it
+            if pending.is_empty() {
+                let Some(beat) = beat else { continue };
+                let should_cut = strategy.should_cut(&BeatContext {
+                    beat,
+                    time_since_last_cut: beat.time - last_cut_time,
+                    density: plan.density_at(beat.time),
+                    analysis: audio_analysis,
+                    seed: self.config.seed,
+                });
+                if should_cut {
+                    pending.push((beat.time, beat.time, false));
+                }
+            }
+
+            for (cut_time, section_time, is_forced) in pending {
+                // Drop a beat cut that would leave a sliver before the phrase cut
+                if is_forced && !forced.last().copied().unwrap_or(true) {
+                    let min_gap = self.config.composition.min_cut_interval / plan.density_at(cut_time) * 0.5;
+                    if cut_time - last_cut_time < min_gap {
+                        timeline.remove_last_cut();
+                        forced.pop();
+                        last_clip = timeline.clip_assignments.last().copied().unwrap_or(first_clip);
+                    }
+                }
+
+                if cut_time <= 0.0 || timeline.cuts.last().is_some_and(|&t| t >= cut_time) {
+                    continue;
+                }
 
-            if should_cut {
-                // **SMART VIDEO SELECTION** - Rotate through ALL available clips
-                clip_rotation_index = (clip_rotation_index + 1) % available_clips.len();
-                let selected_clip = available_clips[clip_rotation_index];
+                let selected_clip = choose_clip(strategy.as_mut(), section_time, Some(last_clip)).unwrap_or(last_clip);
 
-                timeline.add_cut(beat.time, selected_clip);
-                last_cut_time = beat.time;
+                timeline.add_cut_with_transition(cut_time, selected_clip, transition);
+                forced.push(is_forced);
+                last_cut_time = cut_time;
+                last_clip = selected_clip;
                 segment_count += 1;
 
-                debug!("Cut {} at {:.2}s -> Clip {} (beat strength: {:.2})",
-                       segment_count, beat.time, selected_clip, beat.strength);
+                match beat {
+                    Some(beat) => debug!("Cut {} at {:.2}s -> Clip {} ({:?}, beat strength: {:.2})",
+                                         segment_count, cut_time, selected_clip, plan.phrase_type_at(section_time), beat.strength),
+                    None => debug!("Cut {} at {:.2}s -> Clip {} ({:?}, after the last beat)",
+                                   segment_count, cut_time, selected_clip, plan.phrase_type_at(section_time)),
+                }
             }
         }
 
         // **ENSURE GOOD DISTRIBUTION** - Add clips that haven't been used enough
-        self.ensure_clip_distribution(&mut timeline, &available_clips, audio_analysis.duration);
+        if strategy.follows_structure() {
+            self.ensure_clip_distribution(&mut timeline, &available_clips, &plan, audio_analysis.duration);
+        }
 
         info!("   ✅ Timeline generated:");
         info!("      Total cuts: {}", timeline.cuts.len());
@@ -205,64 +452,12 @@ impl CompositionEngine {
         Ok(timeline)
     }
 
-    /// Determine if we should cut at this beat (improved logic)
-    fn should_cut_at_beat(
-        &self,
-        beat: &crate::audio::types::Beat,
-        time_since_last_cut: f64,
-        audio_analysis: &AudioAnalysis,
-    ) -> bool {
-        let config = &self.config.composition;
-
-        // Force cut if maximum interval exceeded
-        if time_since_last_cut >= config.max_cut_interval {
-            return true;
-        }
-
-        // Don't cut if minimum interval not met
-        if time_since_last_cut < config.min_cut_interval {
-            return false;
-        }
-
-        // **IMPROVED PROBABILITY CALCULATION**
-        let mut cut_probability = 0.0;
-
-        // Beat strength factor (stronger beats more likely to cut)
-        cut_probability += beat.strength * 0.5;
-
-        // Energy factor (high energy sections more likely to cut)
-        let local_energy = audio_analysis.average_energy_in_range(
-            beat.time - 0.5,
-            beat.time + 0.5
-        );
-        cut_probability += local_energy * 0.4;
-
-        // Beat type factor (downbeats more likely to cut)
-        if beat.beat_type == crate::audio::types::BeatType::Downbeat {
-            cut_probability += 0.3;
-        }
-
-        // Time factor (encourage cuts at reasonable intervals)
-        let ideal_interval = (config.min_cut_interval + config.max_cut_interval) / 2.0;
-        let time_factor = if time_since_last_cut >= ideal_interval {
-            0.2 + (time_since_last_cut - ideal_interval) / ideal_interval * 0.3
-        } else {
-            0.1
-        } as f32; // Convert to f32
-        cut_probability += time_factor;
-
-        // Apply beat sync strength from configuration
-        cut_probability *= config.beat_sync_strength;
-
-        // **LOWER THRESHOLD** for more frequent cuts and better video distribution
-        cut_probability >= 0.4
-    }
-
     /// Ensure all clips get used and good distribution
     fn ensure_clip_distribution(
         &self,
         timeline: &mut CompositionTimeline,
         available_clips: &[u32],
+        plan: &PhrasePlan,
         duration: f64,
     ) {
         let clips_used = timeline.unique_clips();
@@ -282,9 +477,10 @@ impl CompositionEngine {
                 // Add cuts at strategic points
                 let strategic_time = duration * (0.3 + i as f64 * 0.2);
 
-                // Only add if not too close to existing cuts
-                if !timeline.cuts.iter().any(|&t| (t - strategic_time).abs() < 2.0) {
-                    timeline.add_cut(strategic_time, unused_clip);
+                // Only add if not too close to existing cuts and the section accepts the clip
+                if plan.allows(strategic_time, unused_clip)
+                    && !timeline.cuts.iter().any(|&t| (t - strategic_time).abs() < 2.0) {
+                    timeline.add_cut_with_transition(strategic_time, unused_clip, self.config.composition.transition);
                     debug!("Added strategic cut at {:.1}s for unused clip {}", strategic_time, unused_clip);
                 }
             }
@@ -299,56 +495,106 @@ impl CompositionEngine {
         }
     }
 
-    // Video processing (unchanged but with better logging)
-    async fn process_video_with_effects(
+    // Video processing and encoding, one segment at a time
+    async fn render_video(
         &self,
         video_sequence: &VideoSequence,
         timeline: &CompositionTimeline,
-        audio_analysis: &AudioAnalysis,
-    ) -> Result<Vec<crate::video::ProcessedSegment>> {
+        soundtrack: &Soundtrack<'_>,
+        params: &VideoParams,
+        output_path: &Path,
+    ) -> Result<()> {
         info!("🎨 Step 4: Processing video with {} style...", self.style.name());
 
-        let mut processor = VideoProcessor::new(self.config.video.params.clone())
+        let duration = soundtrack.duration;
+        let mut processor = VideoProcessor::new(params.clone())
             .map_err(|e| CompositionError::SequencingFailed {
                 reason: format!("Failed to initialize video processor: {}", e)
-            })?;
+            })?
+            .with_music(soundtrack.music.clone())
+            .with_seed(self.config.seed);
 
         let clips: Vec<VideoClip> = video_sequence.clips().to_vec();
-        let mut mapped_timeline = timeline.clone();
-        self.map_timeline_to_available_clips(&mut mapped_timeline, &clips);
+        let mapped_timeline = self.render_timeline(timeline, &clips, duration);
 
-        // **ENHANCED STYLE CONFIG** for more obvious effects
-        let mut enhanced_style_config = self.config.style.clone();
+        // **ENHANCED STYLE CONFIG** for more obvious effects; the style's own
+        // defaults fill in whatever the config leaves unset
+        let mut enhanced_style_config = self.config.style.clone()
+            .with_defaults(&self.style.default_config());
         enhanced_style_config.intensity = 0.9; // Increase intensity
 
-        // Add VHS-specific enhancements
-        enhanced_style_config = enhanced_style_config
-            .set("scanline_intensity", 0.9)
-            .set("color_bleeding", 0.8)
-            .set("noise_level", 0.6)
-            .set("tracking_error", 0.5)
-            .set("chroma_shift", 0.7);
-
         info!("   Using enhanced {} style with intensity {:.1}", 
               self.style.name(), enhanced_style_config.intensity);
 
-        let processed_segments = processor.process_timeline(
+        let compositor = VideoCompositor::new(params.clone());
+        let mut encoder = compositor.begin(Some(soundtrack.path), output_path)
+            .map_err(|e| CompositionError::OutputFailed {
+                reason: format!("Video composition failed: {}", e)
+            })?;
+
+        let half_frames = transitions::transition_frames(
+            self.config.composition.crossfade_duration,
+            params.fps.as_f64(),
+        );
+        let default_transition = self.config.composition.transition;
+
+        // Each segment is held back until the next one arrives, so the
+        // transition across their cut can still rewrite its tail
+        let mut pending: Option<ProcessedSegment> = None;
+        let mut segment_count = 0;
+
+        processor.process_timeline_streaming(
             &mapped_timeline,
             &clips,
             self.style.as_ref(),
             &enhanced_style_config,
-            audio_analysis.duration,
+            duration,
+            |mut segment| {
+                if let Some(mut previous) = pending.take() {
+                    let transition = mapped_timeline.transitions
+                        .get(segment.segment_index)
+                        .copied()
+                        .unwrap_or(default_transition);
+                    transitions::blend_segments(&mut previous, &mut segment, transition, half_frames);
+                    encoder.write_segment(&previous)?;
+                }
+
+                segment_count += 1;
+                pending = Some(segment);
+                Ok(())
+            },
         ).await.map_err(|e| CompositionError::SequencingFailed {
             reason: format!("Video processing failed: {}", e)
         })?;
 
+        let encoded_video = pending
+            .map_or(Ok(()), |last| encoder.write_segment(&last))
+            .and_then(|_| encoder.finish())
+            .map_err(|e| CompositionError::OutputFailed {
+                reason: format!("Video composition failed: {}", e)
+            })?;
+
         info!("   ✅ Video processing complete:");
-        info!("      Segments processed: {}", processed_segments.len());
-        info!("      Total frames: {}", 
-              processed_segments.iter().map(|s| s.frames.len()).sum::<usize>());
+        info!("      Segments processed: {}", segment_count);
         info!("      Style applied: {}", self.style.name());
+        info!("      File saved: {:?}", output_path);
+        info!("      Duration: {:.1}s", encoded_video.duration);
+        info!("      Frame count: {}", encoded_video.frame_count);
+        info!("      File size: {:.1} MB", encoded_video.file_size as f64 / 1024.0 / 1024.0);
 
-        Ok(processed_segments)
+        Ok(())
+    }
+
+    /// Timeline as rendered, with clips mapped to the loaded ones
+    ///
+    /// Cuts are snapped to the frame grid of the final render whatever the
+    /// output rate, so a preview at a lower rate keeps every segment of the
+    /// full render and only maps their frames onto its own clock.
+    fn render_timeline(&self, timeline: &CompositionTimeline, clips: &[VideoClip], duration: f64) -> CompositionTimeline {
+        let mut mapped_timeline = timeline.clone();
+        self.map_timeline_to_available_clips(&mut mapped_timeline, clips);
+        mapped_timeline.snap_to_frames(&FrameClock::new(self.config.video.params.fps), duration);
+        mapped_timeline
     }
 
     fn map_timeline_to_available_clips(
@@ -372,45 +618,13 @@ impl CompositionEngine {
         for assignment in timeline.clip_assignments.iter_mut() {
             // If the requested clip exists, use it; otherwise map to available clips
             if !available_sequences.contains(assignment) {
-                let clip_index = (*assignment as usize - 1) % available_sequences.len();
+                let clip_index = (*assignment as usize).saturating_sub(1) % available_sequences.len();
                 *assignment = available_sequences[clip_index];
             }
         }
 
         debug!("Mapped timeline assignments: {:?}", timeline.clip_assignments);
     }
-
-    // Output generation (unchanged)
-    async fn generate_final_output(
-        &self,
-        processed_segments: &[crate::video::ProcessedSegment],
-        audio_path: &Path,
-        output_path: &Path,
-    ) -> Result<()> {
-        info!("🎬 Step 5: Generating final output...");
-
-        let mut compositor = VideoCompositor::new(self.config.video.params.clone());
-
-        let encoded_video = compositor.compose_video(
-            processed_segments,
-            audio_path,
-            output_path,
-        ).await.map_err(|e| CompositionError::OutputFailed {
-            reason: format!("Video composition failed: {}", e)
-        })?;
-
-        info!("   ✅ Output generation complete:");
-        info!("      File saved: {:?}", output_path);
-        info!("      Duration: {:.1}s", encoded_video.duration);
-        info!("      Frame count: {}", encoded_video.frame_count);
-        info!("      File size: {:.1} MB", encoded_video.file_size as f64 / 1024.0 / 1024.0);
-
-        compositor.cleanup().map_err(|e| CompositionError::OutputFailed {
-            reason: format!("Cleanup failed: {}", e)
-        })?;
-
-        Ok(())
-    }
 }
 
 // Timeline data structures (unchanged)
@@ -418,6 +632,33 @@ impl CompositionEngine {
 pub struct CompositionTimeline {
     pub cuts: Vec<f64>,
     pub clip_assignments: Vec<u32>,
+    /// Transition into each segment (the first entry is unused)
+    pub transitions: Vec<TransitionType>,
+    /// Hand edits to each segment's source in-point and style
+    pub overrides: Vec<SegmentOverrides>,
+}
+
+/// Per-segment edits that take precedence over automatic choices
+#[derive(Debug, Clone, Default)]
+pub struct SegmentOverrides {
+    /// Where in the source clip the segment starts (seconds); centered when unset
+    pub source_in: Option<f64>,
+
+    /// Style parameters for this segment only; `intensity` sets the effect intensity
+    pub style: BTreeMap<String, ConfigValue>,
+}
+
+impl SegmentOverrides {
+    /// Layer these overrides on top of a style configuration
+    pub fn apply_style(&self, mut config: StyleConfig) -> StyleConfig {
+        for (key, value) in &self.style {
+            match (key.as_str(), value.as_f32()) {
+                ("intensity", Some(intensity)) => config.intensity = intensity.clamp(0.0, 1.0),
+                _ => config = config.set(key.clone(), value.clone()),
+            }
+        }
+        config
+    }
 }
 
 impl CompositionTimeline {
@@ -425,25 +666,66 @@ impl CompositionTimeline {
         Self {
             cuts: Vec::new(),
             clip_assignments: Vec::new(),
+            transitions: Vec::new(),
+            overrides: Vec::new(),
         }
     }
 
     pub fn add_cut(&mut self, time: f64, clip_id: u32) {
+        self.add_cut_with_transition(time, clip_id, TransitionType::default());
+    }
+
+    pub fn add_cut_with_transition(&mut self, time: f64, clip_id: u32, transition: TransitionType) {
         self.cuts.push(time);
         self.clip_assignments.push(clip_id);
+        self.transitions.push(transition);
+        self.overrides.push(SegmentOverrides::default());
+    }
+
+    /// Remove the most recently added cut
+    pub fn remove_last_cut(&mut self) {
+        self.cuts.pop();
+        self.clip_assignments.pop();
+        self.transitions.pop();
+        self.overrides.pop();
+    }
+
+    /// Change the transition used at an existing cut
+    pub fn set_transition(&mut self, index: usize, transition: TransitionType) {
+        if let Some(slot) = self.transitions.get_mut(index) {
+            *slot = transition;
+        }
+    }
+
+    /// Replace the overrides of an existing segment
+    pub fn set_overrides(&mut self, index: usize, overrides: SegmentOverrides) {
+        if let Some(slot) = self.overrides.get_mut(index) {
+            *slot = overrides;
+        }
     }
 
     pub fn sort_cuts(&mut self) {
-        let mut paired: Vec<(f64, u32)> = self.cuts
-            .iter()
-            .zip(self.clip_assignments.iter())
-            .map(|(&time, &clip)| (time, clip))
-            .collect();
+        let mut order: Vec<usize> = (0..self.cuts.len()).collect();
+        order.sort_by(|&a, &b| self.cuts[a].total_cmp(&self.cuts[b]));
 
-        paired.sort_by(|a, b| a.0.partial_cmp(&b.0).unwrap());
+        self.cuts = order.iter().map(|&i| self.cuts[i]).collect();
+        self.clip_assignments = order.iter().map(|&i| self.clip_assignments[i]).collect();
+        self.transitions = order.iter().map(|&i| self.transitions[i]).collect();
+        self.overrides = order.iter().map(|&i| self.overrides[i].clone()).collect();
+    }
 
-        self.cuts = paired.iter().map(|&(time, _)| time).collect();
-        self.clip_assignments = paired.iter().map(|&(_, clip)| clip).collect();
+    /// Move every cut onto a frame boundary of `clock`
+    ///
+    /// Segments that end up shorter than one frame are dropped, so every
+    /// remaining segment covers at least one whole frame.
+    pub fn snap_to_frames(&mut self, clock: &FrameClock, total_duration: f64) {
+        let ranges = clock.segment_ranges(&self.cuts, total_duration);
+        let keep: Vec<usize> = (0..ranges.len()).filter(|&i| !ranges[i].is_empty()).collect();
+
+        self.cuts = keep.iter().map(|&i| clock.time_of(ranges[i].start)).collect();
+        self.clip_assignments = keep.iter().map(|&i| self.clip_assignments[i]).collect();
+        self.transitions = keep.iter().map(|&i| self.transitions[i]).collect();
+        self.overrides = keep.iter().map(|&i| self.overrides[i].clone()).collect();
     }
 
     pub fn unique_clips(&self) -> Vec<u32> {
@@ -469,4 +751,207 @@ impl Default for CompositionTimeline {
     fn default() -> Self {
         Self::new()
     }
-}
\ No newline at end of file
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::audio::types::{AnalysisBuilder, BeatType, Phrase, PhraseType};
+    use crate::styles::VhsStyle;
+    use crate::video::FrameRate;
+
+    /// Steady beats at `bpm` with a downbeat every four, at constant energy
+    fn analysis(duration: f64, bpm: f32, phrases: Vec<Phrase>) -> AudioAnalysis {
+        let interval = 60.0 / bpm as f64;
+        let builder = (0..(duration / interval) as usize).fold(AnalysisBuilder::new(duration).bpm(bpm), |builder, i| {
+            builder.beat(i as f64 * interval, if i % 4 == 0 { BeatType::Downbeat } else { BeatType::Beat })
+        });
+        let builder = (0..(duration * 10.0) as usize).fold(builder, |builder, i| builder.energy(i as f64 / 10.0, 0.3));
+        phrases
+            .into_iter()
+            .fold(builder, |builder, phrase| builder.phrase(phrase.start, phrase.end, phrase.phrase_type))
+            .build()
+    }
+
+    fn sequence(names: &[&str]) -> VideoSequence {
+        names
+            .iter()
+            .enumerate()
+            .map(|(i, name)| VideoClip::new(format!("{:02}_{}.mp4", i + 1, name), i as u32 + 1, name.to_string()))
+            .collect()
+    }
+
+    fn phrase(start: f64, end: f64, phrase_type: PhraseType) -> Phrase {
+        Phrase { start, end, phrase_type, confidence: 1.0 }
+    }
+
+    #[tokio::test]
+    async fn test_phrase_aware_timeline_follows_structure() {
+        let engine = CompositionEngine::new(Config::default(), Box::new(VhsStyle::new()));
+        let phrases = vec![
+            phrase(0.0, 10.1, PhraseType::Verse),
+            phrase(10.1, 20.0, PhraseType::Chorus),
+            phrase(20.0, 30.0, PhraseType::Outro),
+        ];
+        let audio = analysis(30.0, 120.0, phrases);
+        let clips = sequence(&["street", "beach", "crowd_hype", "dance_high-energy"]);
+
+        let timeline = engine.generate_timeline(&audio, &clips).await.unwrap();
+
+        // Phrase starts get a cut, snapped to the nearest beat
+        assert!(timeline.cuts.contains(&10.0));
+        assert!(timeline.cuts.contains(&20.0));
+
+        // The chorus only uses high-energy clips and cuts faster than the verse
+        let in_section = |start: f64, end: f64| -> Vec<usize> {
+            (0..timeline.cuts.len())
+                .filter(|&i| timeline.cuts[i] >= start && timeline.cuts[i] < end)
+                .collect()
+        };
+        let chorus = in_section(10.0, 20.0);
+        let verse = in_section(0.0, 10.0);
+        assert!(chorus.iter().all(|&i| [3, 4].contains(&timeline.clip_assignments[i])));
+        assert!(chorus.len() > verse.len());
+    }
+
+    #[tokio::test]
+    async fn test_phrase_after_last_beat_still_gets_a_cut() {
+        let engine = CompositionEngine::new(Config::default(), Box::new(VhsStyle::new()));
+        let mut audio = analysis(30.0, 120.0, vec![
+            phrase(0.0, 24.0, PhraseType::Verse),
+            phrase(24.0, 30.0, PhraseType::Outro),
+        ]);
+
+        // The music fades out at 20s, leaving no beats under the outro
+        audio.beats.retain(|beat| beat.time < 20.0);
+        let clips = sequence(&["street", "beach", "crowd_hype"]);
+
+        let timeline = engine.generate_timeline(&audio, &clips).await.unwrap();
+
+        assert!(timeline.cuts.contains(&24.0));
+        assert_eq!(timeline.cuts.len(), timeline.clip_assignments.len());
+    }
+
+    #[tokio::test]
+    async fn test_every_phrase_start_keeps_its_cut() {
+        let engine = CompositionEngine::new(Config::default(), Box::new(VhsStyle::new()));
+        let mut audio = analysis(30.0, 120.0, vec![
+            phrase(0.0, 10.1, PhraseType::Verse),
+            phrase(10.1, 10.4, PhraseType::Bridge),
+            phrase(10.4, 15.2, PhraseType::Verse),
+            phrase(15.2, 16.4, PhraseType::Bridge),
+            phrase(16.4, 30.0, PhraseType::Outro),
+        ]);
+
+        // A break with no beats covers both of the later phrase starts
+        audio.beats.retain(|beat| beat.time < 14.0 || beat.time >= 18.0);
+        let clips = sequence(&["street", "beach", "crowd_hype"]);
+
+        let timeline = engine.generate_timeline(&audio, &clips).await.unwrap();
+
+        // Phrase starts closer than the minimum gap do not drop each other's cut
+        for cut in [10.0, 10.5, 15.2, 16.4] {
+            assert!(timeline.cuts.contains(&cut), "no cut at {}s in {:?}", cut, timeline.cuts);
+        }
+        assert!(timeline.cuts.windows(2).all(|w| w[0] < w[1]));
+    }
+
+    #[tokio::test]
+    async fn test_flat_timeline_ignores_phrases_when_disabled() {
+        let mut config = Config::default();
+        config.composition.phrase_aware_cuts = false;
+        let engine = CompositionEngine::new(config, Box::new(VhsStyle::new()));
+        let audio = analysis(30.0, 120.0, vec![phrase(10.1, 20.0, PhraseType::Chorus)]);
+        let clips = sequence(&["street", "beach", "crowd_hype"]);
+
+        let timeline = engine.generate_timeline(&audio, &clips).await.unwrap();
+
+        // Uniform density: no section forces a cut or tightens the interval
+        let min_gap = timeline.cuts.windows(2).map(|w| w[1] - w[0]).fold(f64::MAX, f64::min);
+        assert!(min_gap >= Config::default().composition.min_cut_interval);
+        assert_eq!(timeline.unique_clips(), vec![1, 2, 3]);
+        assert_eq!(timeline.cuts.len(), timeline.transitions.len());
+    }
+
+    /// Cuts every two seconds, always to the lowest-numbered clip in the pool
+    struct EveryTwoSeconds;
+
+    impl CutStrategy for EveryTwoSeconds {
+        fn name(&self) -> &str {
+            "every_two_seconds"
+        }
+
+        fn should_cut(&mut self, context: &BeatContext) -> bool {
+            context.time_since_last_cut >= 2.0
+        }
+
+        fn next_clip(&mut self, context: &ClipContext) -> Option<u32> {
+            context.pool.iter().copied().min()
+        }
+
+        fn follows_structure(&self) -> bool {
+            false
+        }
+    }
+
+    #[tokio::test]
+    async fn test_custom_cut_strategy() {
+        let mut config = Config::default();
+        config.composition.cut_strategy = "every_two_seconds".to_string();
+        let audio = analysis(10.0, 120.0, vec![phrase(5.1, 10.0, PhraseType::Chorus)]);
+        let clips = sequence(&["street", "beach", "crowd_hype"]);
+
+        let engine = CompositionEngine::new(config.clone(), Box::new(VhsStyle::new()));
+        assert!(engine.generate_timeline(&audio, &clips).await.is_err());
+
+        let engine = engine.with_cut_strategy("every_two_seconds", |_| Box::new(EveryTwoSeconds));
+        let timeline = engine.generate_timeline(&audio, &clips).await.unwrap();
+
+        // No forced cut at the chorus, but its cuts still draw from the chorus pool
+        assert_eq!(timeline.cuts, vec![0.0, 2.0, 4.0, 6.0, 8.0]);
+        assert_eq!(timeline.clip_assignments, vec![1, 1, 1, 3, 3]);
+    }
+
+    #[test]
+    fn test_preview_renders_the_final_segment_list() {
+        let config = Config::default();
+        let engine = CompositionEngine::new(config.clone(), Box::new(VhsStyle::new()));
+        let clips = sequence(&["street", "beach", "crowd_hype"]);
+
+        // A 0.05s segment is shorter than a 12 fps preview frame but not a 30 fps one
+        let mut timeline = CompositionTimeline::new();
+        timeline.add_cut(0.0, 1);
+        timeline.add_cut(1.01, 2);
+        timeline.add_cut(1.06, 3);
+        timeline.add_cut(2.5, 1);
+
+        let rendered = engine.render_timeline(&timeline, clips.clips(), 4.0);
+        assert_eq!(rendered.cuts, vec![0.0, 1.0, 1.0 + 2.0 / 30.0, 2.5]);
+        assert_eq!(rendered.clip_assignments, vec![1, 2, 3, 1]);
+
+        // The preview maps those same segments onto its own, coarser clock
+        let preview = config.preview.apply(&config.video.params);
+        let preview_clock = FrameClock::new(preview.fps);
+        let ranges = preview_clock.segment_ranges(&rendered.cuts, 4.0);
+        assert_eq!(ranges.len(), rendered.cuts.len());
+        for (range, &cut) in ranges.iter().zip(&rendered.cuts).skip(1) {
+            assert!((preview_clock.time_of(range.start) - cut).abs() <= 0.5 / preview.fps.as_f64() + 1e-9);
+        }
+    }
+
+    #[test]
+    fn test_snap_to_frames_drops_sub_frame_segments() {
+        let mut timeline = CompositionTimeline::new();
+        timeline.add_cut(0.0, 1);
+        timeline.add_cut_with_transition(1.01, 2, TransitionType::WhipPan);
+        timeline.add_cut(1.015, 3);
+        timeline.add_cut(2.49, 1);
+
+        timeline.snap_to_frames(&FrameClock::new(FrameRate::PAL), 3.0);
+
+        // 1.01 and 1.015 both land on frame 25; the empty segment before it goes
+        assert_eq!(timeline.cuts, vec![0.0, 1.0, 2.48]);
+        assert_eq!(timeline.clip_assignments, vec![1, 3, 1]);
+        assert_eq!(timeline.transitions.len(), 3);
+        assert_eq!(timeline.overrides.len(), 3);
+    }
+}
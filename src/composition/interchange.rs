@@ -0,0 +1,418 @@
+//! Cut lists for editing applications
+//!
+//! Writes a timeline as a CMX3600 EDL or as FCPXML so the beat-synced edit can
+//! be imported into Resolve, Premiere or Final Cut and refined there. Both
+//! formats reference the original clip files with the same in-points the
+//! renderer uses, and a clip the renderer loops becomes one event per pass so
+//! no event runs past the end of its media.
+
+use std::fmt::Write as _;
+use std::path::Path;
+
+use crate::composition::document::TimelineDocument;
+use crate::video::{FieldOrder, FrameRate, TransitionType, VideoClip, VideoParams};
+
+/// Record timecode of the first frame, by EDL convention
+const RECORD_START_HOURS: u64 = 1;
+
+/// FCPXML rational time for a frame count, e.g. `120/30s` or `1001/30000s`
+fn rational(rate: FrameRate, frames: u64) -> String {
+    if frames == 0 {
+        "0s".to_string()
+    } else {
+        format!("{}/{}s", frames * rate.den() as u64, rate.num())
+    }
+}
+
+/// Non-drop-frame timecode `HH:MM:SS:FF`
+pub fn timecode(frames: u64, base: u64) -> String {
+    let (seconds, frame) = (frames / base, frames % base);
+    format!("{:02}:{:02}:{:02}:{:02}", seconds / 3600, (seconds / 60) % 60, seconds % 60, frame)
+}
+
+/// One timeline event in whole frames
+struct Event<'a> {
+    clip: Option<&'a VideoClip>,
+    clip_id: u32,
+    record_in: u64,
+    record_out: u64,
+    source_in: u64,
+    transition: TransitionType,
+}
+
+fn events<'a>(document: &TimelineDocument, clips: &'a [VideoClip], rate: FrameRate) -> Vec<Event<'a>> {
+    let end = rate.frame_at(document.duration);
+
+    document
+        .segments
+        .iter()
+        .enumerate()
+        .flat_map(|(i, segment)| {
+            let clip = clips.iter().find(|c| c.sequence_number == segment.clip);
+            let clip_frames = clip.and_then(|c| c.duration).map(|d| rate.frame_at(d)).filter(|&f| f > 0);
+
+            // Record times come from the cut times, so rounding never opens gaps
+            let record_out = document.segments.get(i + 1).map_or(end, |s| rate.frame_at(s.start));
+            let mut record_in = rate.frame_at(segment.start);
+
+            // The renderer clamps the in-point to the clip and loops it when it runs out
+            let source_in = rate.frame_at(segment.source_in.unwrap_or(0.0));
+            let mut source_in = clip_frames.map_or(source_in, |frames| source_in.min(frames) % frames);
+
+            let mut events = Vec::new();
+            while record_in < record_out {
+                let length = clip_frames.map_or(record_out - record_in, |frames| {
+                    (frames - source_in).min(record_out - record_in)
+                });
+
+                events.push(Event {
+                    clip,
+                    clip_id: segment.clip,
+                    record_in,
+                    record_out: record_in + length,
+                    source_in,
+                    transition: if events.is_empty() { segment.transition } else { TransitionType::Cut },
+                });
+                record_in += length;
+                source_in = 0;
+            }
+            events
+        })
+        .collect()
+}
+
+fn clip_file_name(event: &Event) -> String {
+    event
+        .clip
+        .and_then(|c| c.path.file_name())
+        .map(|name| name.to_string_lossy().into_owned())
+        .unwrap_or_else(|| format!("clip_{}", event.clip_id))
+}
+
+/// CMX3600 edit decision list with one video cut per segment
+///
+/// Clips are identified by `FROM CLIP NAME` comments, which Resolve and
+/// Premiere use to relink media. Transitions are noted in comments.
+pub fn to_edl(document: &TimelineDocument, clips: &[VideoClip], rate: FrameRate, title: &str) -> String {
+    let base = rate.timebase() as u64;
+    let record_offset = RECORD_START_HOURS * 3600 * base;
+
+    let mut edl = String::new();
+    let _ = writeln!(edl, "TITLE: {}", title);
+    let _ = writeln!(edl, "FCM: NON-DROP FRAME");
+    let _ = writeln!(edl);
+
+    for (number, event) in events(document, clips, rate).iter().enumerate() {
+        let length = event.record_out - event.record_in;
+
+        let _ = writeln!(
+            edl,
+            "{:03}  AX       V     C        {} {} {} {}",
+            number + 1,
+            timecode(event.source_in, base),
+            timecode(event.source_in + length, base),
+            timecode(record_offset + event.record_in, base),
+            timecode(record_offset + event.record_out, base),
+        );
+        let _ = writeln!(edl, "* FROM CLIP NAME: {}", clip_file_name(event));
+        if let Some(clip) = event.clip {
+            let _ = writeln!(edl, "* SOURCE FILE: {}", clip.path.display());
+        }
+        if number > 0 && event.transition != TransitionType::Cut {
+            let _ = writeln!(edl, "* TRANSITION: {}", event.transition.name());
+        }
+        let _ = writeln!(edl);
+    }
+
+    edl
+}
+
+fn xml_escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&apos;")
+}
+
+/// `file://` URL for a path, percent-encoding every byte outside the RFC 3986 unreserved set and `/`
+fn file_url(path: &Path) -> String {
+    let absolute = path.canonicalize().unwrap_or_else(|_| path.to_path_buf());
+    let mut url = String::from("file://");
+    for byte in absolute.to_string_lossy().bytes() {
+        match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' | b'/' => url.push(byte as char),
+            _ => {
+                let _ = write!(url, "%{:02X}", byte);
+            }
+        }
+    }
+    url
+}
+
+/// FCPXML `<format>` element with the given id, frame rate and raster
+fn format_element(id: &str, rate: FrameRate, resolution: (u32, u32), params: Option<&VideoParams>) -> String {
+    let mut attributes = format!(
+        r#"id="{}" frameDuration="{}" width="{}" height="{}""#,
+        id, rational(rate, 1), resolution.0, resolution.1
+    );
+
+    if let Some(params) = params {
+        if params.pixel_aspect != (1, 1) {
+            let _ = write!(attributes, r#" paspH="{}" paspV="{}""#, params.pixel_aspect.0, params.pixel_aspect.1);
+        }
+        let field_order = match params.field_order {
+            FieldOrder::Progressive => "progressive",
+            FieldOrder::TopFieldFirst => "upper first",
+            FieldOrder::BottomFieldFirst => "lower first",
+        };
+        let _ = write!(attributes, r#" fieldOrder="{}""#, field_order);
+    }
+
+    format!("<format {}/>", attributes)
+}
+
+/// FCPXML 1.9 project with the cuts on the primary storyline
+///
+/// The sequence takes the output frame rate and raster from `params`. Each
+/// source asset gets a format of its own when its frame rate and resolution
+/// are known, and none otherwise so the editor reads them from the media.
+/// The soundtrack, when given, is attached under the first clip so it plays
+/// from the start of the sequence.
+pub fn to_fcpxml(
+    document: &TimelineDocument,
+    clips: &[VideoClip],
+    params: &VideoParams,
+    title: &str,
+    audio_path: Option<&Path>,
+) -> String {
+    let rate = params.fps;
+    let events = events(document, clips, rate);
+    let total = rate.frame_at(document.duration);
+
+    let mut xml = String::new();
+    let _ = writeln!(xml, r#"<?xml version="1.0" encoding="UTF-8"?>"#);
+    let _ = writeln!(xml, "<!DOCTYPE fcpxml>");
+    let _ = writeln!(xml, r#"<fcpxml version="1.9">"#);
+    let _ = writeln!(xml, "  <resources>");
+    let _ = writeln!(xml, "    {}", format_element("r0", rate, params.resolution, Some(params)));
+
+    // One asset per distinct clip, in first-use order, sharing formats between identical sources
+    let mut asset_ids: Vec<u32> = Vec::new();
+    let mut source_formats: Vec<(FrameRate, (u32, u32))> = Vec::new();
+    for event in &events {
+        if asset_ids.contains(&event.clip_id) {
+            continue;
+        }
+        asset_ids.push(event.clip_id);
+
+        let source = event.clip.and_then(|c| {
+            let fps = c.fps.filter(|&fps| fps > 0.0)?;
+            Some((FrameRate::from_f64(fps), c.resolution?))
+        });
+        let format = source.map(|source| {
+            let index = match source_formats.iter().position(|&f| f == source) {
+                Some(index) => index,
+                None => {
+                    source_formats.push(source);
+                    let id = format!("r{}", source_formats.len());
+                    let _ = writeln!(xml, "    {}", format_element(&id, source.0, source.1, None));
+                    source_formats.len() - 1
+                }
+            };
+            format!(r#" format="r{}""#, index + 1)
+        });
+
+        let name = event.clip.map_or_else(|| clip_file_name(event), |c| c.name.clone());
+        let src = event.clip.map_or_else(|| clip_file_name(event), |c| file_url(&c.path));
+        let duration = event.clip.and_then(|c| c.duration).map_or(total, |d| rate.frame_at(d));
+
+        let _ = writeln!(
+            xml,
+            r#"    <asset id="c{}" name="{}" start="0s" duration="{}" hasVideo="1"{}>"#,
+            event.clip_id, xml_escape(&name), rational(rate, duration), format.unwrap_or_default()
+        );
+        let _ = writeln!(xml, r#"      <media-rep kind="original-media" src="{}"/>"#, src);
+        let _ = writeln!(xml, "    </asset>");
+    }
+
+    if let Some(audio) = audio_path {
+        let _ = writeln!(
+            xml,
+            r#"    <asset id="audio" name="{}" start="0s" duration="{}" hasAudio="1">"#,
+            xml_escape(&audio.file_name().map(|n| n.to_string_lossy().into_owned()).unwrap_or_default()),
+            rational(rate, total)
+        );
+        let _ = writeln!(xml, r#"      <media-rep kind="original-media" src="{}"/>"#, file_url(audio));
+        let _ = writeln!(xml, "    </asset>");
+    }
+
+    let _ = writeln!(xml, "  </resources>");
+    let _ = writeln!(xml, "  <library>");
+    let _ = writeln!(xml, r#"    <event name="{}">"#, xml_escape(title));
+    let _ = writeln!(xml, r#"      <project name="{}">"#, xml_escape(title));
+    let _ = writeln!(xml, r#"        <sequence format="r0" duration="{}" tcStart="0s">"#, rational(rate, total));
+    let _ = writeln!(xml, "          <spine>");
+
+    for (i, event) in events.iter().enumerate() {
+        let name = event.clip.map_or_else(|| clip_file_name(event), |c| c.name.clone());
+        let attributes = format!(
+            r#"ref="c{}" name="{}" offset="{}" start="{}" duration="{}""#,
+            event.clip_id,
+            xml_escape(&name),
+            rational(rate, event.record_in),
+            rational(rate, event.source_in),
+            rational(rate, event.record_out - event.record_in),
+        );
+
+        match audio_path.filter(|_| i == 0) {
+            Some(_) => {
+                let _ = writeln!(xml, "            <asset-clip {}>", attributes);
+                let _ = writeln!(
+                    xml,
+                    r#"              <asset-clip ref="audio" lane="-1" offset="{}" start="0s" duration="{}"/>"#,
+                    rational(rate, event.source_in), rational(rate, total)
+                );
+                let _ = writeln!(xml, "            </asset-clip>");
+            }
+            None => {
+                let _ = writeln!(xml, "            <asset-clip {}/>", attributes);
+            }
+        }
+    }
+
+    let _ = writeln!(xml, "          </spine>");
+    let _ = writeln!(xml, "        </sequence>");
+    let _ = writeln!(xml, "      </project>");
+    let _ = writeln!(xml, "    </event>");
+    let _ = writeln!(xml, "  </library>");
+    let _ = writeln!(xml, "</fcpxml>");
+
+    xml
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::composition::document::SegmentEntry;
+    use crate::video::OutputPreset;
+
+    fn segment(start: f64, clip: u32, source_in: f64, transition: TransitionType) -> SegmentEntry {
+        SegmentEntry {
+            start,
+            clip,
+            clip_name: None,
+            source_in: Some(source_in),
+            transition,
+            style: Default::default(),
+        }
+    }
+
+    fn fixture() -> (TimelineDocument, Vec<VideoClip>) {
+        let document = TimelineDocument {
+            duration: 6.0,
+            segments: vec![
+                segment(0.0, 1, 8.0, TransitionType::Crossfade),
+                segment(2.6, 2, 0.4, TransitionType::WhipPan),
+                segment(4.0, 1, 3.0, TransitionType::Cut),
+            ],
+        };
+
+        let mut street = VideoClip::new("/media/01_street & night.mp4", 1, "street & night".to_string());
+        street.duration = Some(20.0);
+        street.fps = Some(23.976);
+        street.resolution = Some((3840, 2160));
+        let beach = VideoClip::new("/media/02_beach.mp4", 2, "beach".to_string());
+
+        (document, vec![street, beach])
+    }
+
+    #[test]
+    fn test_rational_times() {
+        assert_eq!(rational(FrameRate::integer(30), 120), "120/30s");
+        assert_eq!(rational(FrameRate::NTSC, 1), "1001/30000s");
+        assert_eq!(rational(FrameRate::FILM_NTSC, 0), "0s");
+        assert_eq!(timecode(3600 * 25 + 61 * 25 + 7, 25), "01:01:01:07");
+    }
+
+    #[test]
+    fn test_edl_events_follow_cuts() {
+        let (document, clips) = fixture();
+
+        let edl = to_edl(&document, &clips, FrameRate::PAL, "Demo");
+        let events: Vec<&str> = edl.lines().filter(|l| l.starts_with('0')).collect();
+
+        assert!(edl.starts_with("TITLE: Demo\nFCM: NON-DROP FRAME\n"));
+        assert_eq!(events.len(), 3);
+        assert_eq!(
+            events[0],
+            "001  AX       V     C        00:00:08:00 00:00:10:15 01:00:00:00 01:00:02:15"
+        );
+        assert_eq!(
+            events[1],
+            "002  AX       V     C        00:00:00:10 00:00:01:20 01:00:02:15 01:00:04:00"
+        );
+        assert!(edl.contains("* FROM CLIP NAME: 01_street & night.mp4"));
+        assert!(edl.contains("* TRANSITION: whip_pan"));
+        assert!(!edl.contains("* TRANSITION: crossfade"));
+    }
+
+    #[test]
+    fn test_edl_splits_looped_clips() {
+        let (mut document, mut clips) = fixture();
+        clips[1].duration = Some(0.8);
+        document.segments[1].source_in = Some(0.6);
+
+        let edl = to_edl(&document, &clips, FrameRate::PAL, "Demo");
+        let events: Vec<&str> = edl.lines().filter(|l| l.starts_with('0')).collect();
+
+        // 1.4s of a 0.8s clip from 0.6s: the tail, one full pass, then the rest
+        assert_eq!(events.len(), 5);
+        assert!(events[1].ends_with("00:00:00:15 00:00:00:20 01:00:02:15 01:00:02:20"));
+        assert!(events[2].ends_with("00:00:00:00 00:00:00:20 01:00:02:20 01:00:03:15"));
+        assert!(events[3].ends_with("00:00:00:00 00:00:00:10 01:00:03:15 01:00:04:00"));
+        assert_eq!(edl.matches("* TRANSITION: whip_pan").count(), 1);
+    }
+
+    #[test]
+    fn test_fcpxml_spine_matches_timeline() {
+        let (document, clips) = fixture();
+        let params = VideoParams { resolution: (1080, 1920), ..VideoParams::default() };
+
+        let xml = to_fcpxml(&document, &clips, &params, "Demo", Some(Path::new("song.wav")));
+
+        assert!(xml.contains(r#"<format id="r0" frameDuration="1/30s" width="1080" height="1920" fieldOrder="progressive"/>"#));
+        assert!(xml.contains(r#"<format id="r1" frameDuration="1001/24000s" width="3840" height="2160"/>"#));
+        assert_eq!(xml.matches("<asset id=\"c").count(), 2);
+        assert!(xml.contains(r#"name="street &amp; night" start="0s" duration="600/30s" hasVideo="1" format="r1">"#));
+        assert!(xml.contains(r#"<asset id="c2" name="beach" start="0s" duration="180/30s" hasVideo="1">"#));
+        assert!(xml.contains(r#"<media-rep kind="original-media" src="file:///media/02_beach.mp4"/>"#));
+        assert!(!xml.lines().any(|line| line.trim_start().starts_with("<asset ") && line.contains(" src=")));
+        assert!(xml.contains(r#"ref="c2" name="beach" offset="78/30s" start="12/30s" duration="42/30s""#));
+        assert!(xml.contains(r#"<asset-clip ref="audio" lane="-1" offset="240/30s" start="0s" duration="180/30s"/>"#));
+        assert!(xml.contains(r#"<sequence format="r0" duration="180/30s" tcStart="0s">"#));
+        assert_eq!(xml.matches("<asset-clip ref=\"c").count(), 3);
+    }
+
+    #[test]
+    fn test_file_urls_are_percent_encoded() {
+        assert_eq!(file_url(Path::new("/media/02_beach.mp4")), "file:///media/02_beach.mp4");
+        assert_eq!(
+            file_url(Path::new("/media/take #1 at 100% & café.mp4")),
+            "file:///media/take%20%231%20at%20100%25%20%26%20caf%C3%A9.mp4"
+        );
+    }
+
+    #[test]
+    fn test_fcpxml_sequence_carries_broadcast_raster() {
+        let (document, clips) = fixture();
+        let mut params = VideoParams::default();
+        OutputPreset::Ntsc.apply(&mut params);
+
+        let xml = to_fcpxml(&document, &clips, &params, "Demo", None);
+
+        assert!(xml.contains(
+            r#"<format id="r0" frameDuration="1001/30000s" width="720" height="480" paspH="8" paspV="9" fieldOrder="lower first"/>"#
+        ));
+    }
+}
@@ -3,7 +3,14 @@
 //! The composition engine coordinates audio analysis, video processing, and style application
 //! to create synchronized retro video compositions.
 
+pub mod diagnostics;
+pub mod document;
 pub mod engine;
+pub mod interchange;
+pub mod phrasing;
+pub mod strategy;
 
 // Re-exports for convenience
-pub use engine::CompositionEngine;
\ No newline at end of file
+pub use document::TimelineDocument;
+pub use engine::CompositionEngine;
+pub use strategy::{CutStrategy, CutStrategyRegistry};
\ No newline at end of file
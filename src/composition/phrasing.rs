@@ -0,0 +1,256 @@
+//! Phrase-aware cut planning
+//!
+//! Maps the song structure found by audio analysis onto the timeline: every
+//! phrase start gets a cut, each section draws from its own pool of clips, and
+//! the cut interval stretches or tightens with the section type.
+
+use std::collections::HashMap;
+
+use crate::audio::types::{Phrase, PhraseType};
+use crate::config::CompositionConfig;
+use crate::video::types::VideoClip;
+
+/// Clip name tags that put a clip into each section's pool by default
+fn default_pool_tags(phrase_type: PhraseType) -> &'static [&'static str] {
+    match phrase_type {
+        PhraseType::Intro => &["intro", "calm", "low", "slow", "ambient"],
+        PhraseType::Verse => &["verse", "medium", "mid"],
+        PhraseType::Chorus => &["chorus", "high", "energy", "hype", "action", "fast"],
+        PhraseType::Bridge => &["bridge", "calm", "medium", "mid"],
+        PhraseType::Outro => &["outro", "calm", "low", "slow", "ambient"],
+        PhraseType::Unknown => &[],
+    }
+}
+
+/// Relative cut density of each section type (1.0 = configured intervals)
+pub fn cut_density(phrase_type: PhraseType) -> f64 {
+    match phrase_type {
+        PhraseType::Intro => 0.6,
+        PhraseType::Verse => 1.0,
+        PhraseType::Chorus => 2.0,
+        PhraseType::Bridge => 0.8,
+        PhraseType::Outro => 0.5,
+        PhraseType::Unknown => 1.0,
+    }
+}
+
+/// Parse a section name as used in `composition.clip_pools`
+pub fn phrase_type_from_name(name: &str) -> Option<PhraseType> {
+    match name.to_lowercase().as_str() {
+        "intro" => Some(PhraseType::Intro),
+        "verse" => Some(PhraseType::Verse),
+        "chorus" => Some(PhraseType::Chorus),
+        "bridge" => Some(PhraseType::Bridge),
+        "outro" => Some(PhraseType::Outro),
+        "unknown" => Some(PhraseType::Unknown),
+        _ => None,
+    }
+}
+
+/// Clips available to each section type
+#[derive(Debug, Clone)]
+pub struct ClipPools {
+    pools: HashMap<PhraseType, Vec<u32>>,
+    all: Vec<u32>,
+}
+
+impl ClipPools {
+    /// Sort clips into pools by their name tags
+    ///
+    /// Tags from `composition.clip_pools` replace the defaults for a section.
+    /// Sections where no clip matches fall back to every clip.
+    pub fn from_clips(clips: &[VideoClip], config: &CompositionConfig) -> Self {
+        let overrides: HashMap<PhraseType, &Vec<String>> = config
+            .clip_pools
+            .iter()
+            .filter_map(|(name, tags)| phrase_type_from_name(name).map(|t| (t, tags)))
+            .collect();
+
+        let mut pools = HashMap::new();
+
+        for phrase_type in [
+            PhraseType::Intro,
+            PhraseType::Verse,
+            PhraseType::Chorus,
+            PhraseType::Bridge,
+            PhraseType::Outro,
+        ] {
+            let tags: Vec<String> = match overrides.get(&phrase_type) {
+                Some(tags) => tags.iter().map(|t| t.to_lowercase()).collect(),
+                None => default_pool_tags(phrase_type).iter().map(|t| t.to_string()).collect(),
+            };
+
+            let pool: Vec<u32> = clips
+                .iter()
+                .filter(|clip| clip.tags().iter().any(|tag| tags.contains(tag)))
+                .map(|clip| clip.sequence_number)
+                .collect();
+
+            if !pool.is_empty() {
+                pools.insert(phrase_type, pool);
+            }
+        }
+
+        Self {
+            pools,
+            all: clips.iter().map(|clip| clip.sequence_number).collect(),
+        }
+    }
+
+    /// Clips a section may draw from
+    pub fn pool(&self, phrase_type: PhraseType) -> &[u32] {
+        self.pools.get(&phrase_type).unwrap_or(&self.all)
+    }
+
+    /// Every clip, regardless of tags
+    pub fn all(&self) -> &[u32] {
+        &self.all
+    }
+}
+
+/// Section-by-section cut plan for one song
+#[derive(Debug, Clone)]
+pub struct PhrasePlan {
+    phrases: Vec<Phrase>,
+    pools: ClipPools,
+}
+
+impl PhrasePlan {
+    /// Plan cuts around the given phrases
+    pub fn new(phrases: &[Phrase], clips: &[VideoClip], config: &CompositionConfig) -> Self {
+        let mut phrases = phrases.to_vec();
+        phrases.sort_by(|a, b| a.start.total_cmp(&b.start));
+
+        Self {
+            phrases,
+            pools: ClipPools::from_clips(clips, config),
+        }
+    }
+
+    /// Plan that ignores song structure: one pool, uniform density
+    pub fn flat(clips: &[VideoClip], config: &CompositionConfig) -> Self {
+        Self::new(&[], clips, config)
+    }
+
+    /// Phrase playing at the given time
+    pub fn phrase_at(&self, time: f64) -> Option<&Phrase> {
+        self.phrases
+            .iter()
+            .find(|phrase| time >= phrase.start && time < phrase.end)
+    }
+
+    /// Section type at the given time
+    pub fn phrase_type_at(&self, time: f64) -> PhraseType {
+        self.phrase_at(time)
+            .map(|phrase| phrase.phrase_type)
+            .unwrap_or(PhraseType::Unknown)
+    }
+
+    /// Cut density multiplier at the given time
+    pub fn density_at(&self, time: f64) -> f64 {
+        cut_density(self.phrase_type_at(time))
+    }
+
+    /// Phrase start times after the beginning of the song, where cuts are forced
+    pub fn boundaries(&self) -> Vec<f64> {
+        self.phrases
+            .iter()
+            .map(|phrase| phrase.start)
+            .filter(|&start| start > 0.0)
+            .collect()
+    }
+
+    /// Clips the section playing at the given time may draw from
+    pub fn pool_at(&self, time: f64) -> &[u32] {
+        self.pools.pool(self.phrase_type_at(time))
+    }
+
+    /// Whether a clip is allowed in the section playing at the given time
+    pub fn allows(&self, time: f64, clip_id: u32) -> bool {
+        self.pool_at(time).contains(&clip_id)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn clips(names: &[&str]) -> Vec<VideoClip> {
+        names
+            .iter()
+            .enumerate()
+            .map(|(i, name)| VideoClip::new(format!("{:02}_{}.mp4", i + 1, name), i as u32 + 1, name.to_string()))
+            .collect()
+    }
+
+    fn phrase(start: f64, end: f64, phrase_type: PhraseType) -> Phrase {
+        Phrase { start, end, phrase_type, confidence: 1.0 }
+    }
+
+    #[test]
+    fn test_chorus_pool_uses_high_energy_clips() {
+        let clips = clips(&["intro", "street", "high-energy_dance", "beach", "crowd_hype"]);
+        let pools = ClipPools::from_clips(&clips, &CompositionConfig::default());
+
+        assert_eq!(pools.pool(PhraseType::Chorus), &[3, 5]);
+        assert_eq!(pools.pool(PhraseType::Intro), &[1]);
+
+        // Nothing tagged for verses, so they draw from everything
+        assert_eq!(pools.pool(PhraseType::Verse), &[1, 2, 3, 4, 5]);
+    }
+
+    #[test]
+    fn test_configured_pool_tags_override_defaults() {
+        let clips = clips(&["street", "beach", "crowd_hype"]);
+        let mut config = CompositionConfig::default();
+        config.clip_pools.insert("chorus".to_string(), vec!["Beach".to_string()]);
+
+        let pools = ClipPools::from_clips(&clips, &config);
+
+        assert_eq!(pools.pool(PhraseType::Chorus), &[2]);
+    }
+
+    #[test]
+    fn test_plan_pools_follow_sections() {
+        let clips = clips(&["calm_intro", "street", "chorus_a", "chorus_b"]);
+        let phrases = [
+            phrase(0.0, 8.0, PhraseType::Intro),
+            phrase(8.0, 16.0, PhraseType::Chorus),
+        ];
+        let plan = PhrasePlan::new(&phrases, &clips, &CompositionConfig::default());
+
+        assert_eq!(plan.pool_at(1.0), &[1]);
+        assert_eq!(plan.pool_at(9.0), &[3, 4]);
+        assert_eq!(plan.pool_at(20.0), &[1, 2, 3, 4]);
+
+        assert!(plan.allows(12.0, 4));
+        assert!(!plan.allows(12.0, 2));
+        assert_eq!(plan.boundaries(), vec![8.0]);
+    }
+
+    #[test]
+    fn test_density_follows_section() {
+        let clips = clips(&["a", "b"]);
+        let phrases = [
+            phrase(0.0, 8.0, PhraseType::Verse),
+            phrase(8.0, 16.0, PhraseType::Chorus),
+        ];
+        let plan = PhrasePlan::new(&phrases, &clips, &CompositionConfig::default());
+
+        assert!(plan.density_at(10.0) > plan.density_at(2.0));
+        assert_eq!(plan.density_at(20.0), 1.0);
+        assert_eq!(PhrasePlan::flat(&clips, &CompositionConfig::default()).density_at(10.0), 1.0);
+    }
+
+    #[test]
+    fn test_plan_survives_nan_phrase_start() {
+        let clips = clips(&["a", "b"]);
+        let phrases = [
+            phrase(8.0, 16.0, PhraseType::Chorus),
+            phrase(f64::NAN, 8.0, PhraseType::Verse),
+        ];
+        let plan = PhrasePlan::new(&phrases, &clips, &CompositionConfig::default());
+
+        assert_eq!(plan.boundaries(), vec![8.0]);
+    }
+}
@@ -0,0 +1,741 @@
+//! Cut-selection strategies
+//!
+//! Timeline generation asks two questions on the way through the song: should
+//! this beat get a cut, and which clip plays next? A `CutStrategy` answers
+//! both. The engine keeps the parts that are the same for every strategy:
+//! walking the beats, forcing cuts at phrase starts and handing each section
+//! its clip pool.
+//!
+//! Built-in strategies, selected with `composition.cut_strategy`:
+//!
+//! - `round_robin`: scored beat cuts, rotating through each section's pool
+//! - `weighted_random`: scored beat cuts, clips drawn by `composition.clip_weights`
+//! - `sequential_story`: clips in file order, each given an equal share of the song
+//! - `energy_matched`: scored beat cuts, calm clips for quiet passages and
+//!   high-energy clips for loud ones
+//! - `every_n_bars`: a cut every `composition.bars_per_cut` bars, rotating clips
+//!
+//! Custom strategies are added with `CutStrategyRegistry::register` or
+//! `CompositionEngine::with_cut_strategy`.
+
+use std::collections::HashMap;
+
+use rand::{rngs::SmallRng, Rng, SeedableRng};
+
+use crate::audio::types::{AudioAnalysis, Beat, BeatType, PhraseType};
+use crate::config::{CompositionConfig, CutWeights};
+use crate::styles::noise::hash;
+use crate::video::types::VideoClip;
+
+/// A beat the engine is considering for a cut
+#[derive(Debug, Clone, Copy)]
+pub struct BeatContext<'a> {
+    pub beat: &'a Beat,
+
+    /// Seconds since the previous cut
+    pub time_since_last_cut: f64,
+
+    /// Cut density multiplier of the current section (1.0 = configured intervals)
+    pub density: f64,
+
+    pub analysis: &'a AudioAnalysis,
+
+    /// Render seed; random decisions must come from it to keep renders reproducible
+    pub seed: u64,
+}
+
+/// A cut that needs a clip
+#[derive(Debug, Clone, Copy)]
+pub struct ClipContext<'a> {
+    /// Time the section is judged at; the phrase start for forced phrase cuts
+    pub time: f64,
+
+    /// Section playing at `time`
+    pub phrase_type: PhraseType,
+
+    /// Clips the section may draw from, never empty
+    pub pool: &'a [u32],
+
+    /// Every clip in the sequence
+    pub clips: &'a [VideoClip],
+
+    /// Clip playing before the cut, `None` for the first segment
+    pub previous: Option<u32>,
+
+    pub analysis: &'a AudioAnalysis,
+
+    /// Render seed; random choices must come from it to keep renders reproducible
+    pub seed: u64,
+}
+
+/// Decides when to cut and which clip to cut to
+///
+/// A fresh instance is created for every timeline, so strategies can keep
+/// state between calls. `next_clip` is called for every cut, including the
+/// first segment and cuts the engine forces at phrase starts.
+pub trait CutStrategy: Send {
+    /// Name the strategy is registered under
+    fn name(&self) -> &str;
+
+    /// Called once before the first beat with the song and the clips to use
+    fn prepare(&mut self, _analysis: &AudioAnalysis, _clips: &[VideoClip]) {}
+
+    /// Whether to cut on this beat
+    fn should_cut(&mut self, context: &BeatContext) -> bool;
+
+    /// Clip for the segment starting at the cut; `None` keeps the previous clip
+    fn next_clip(&mut self, context: &ClipContext) -> Option<u32>;
+
+    /// Whether the engine may add cuts of its own: forced cuts at phrase starts
+    /// and extra cuts for clips the strategy left unused
+    fn follows_structure(&self) -> bool {
+        true
+    }
+}
+
+/// The default cut decision: score each beat and cut above a threshold
+///
+/// Outside the section's minimum and maximum interval the decision is fixed;
+/// in between, strong beats, loud passages and downbeats score higher, and
+/// the score grows the longer the current segment has run.
+#[derive(Debug, Clone)]
+pub struct BeatScore {
+    min_cut_interval: f64,
+    max_cut_interval: f64,
+    beat_sync_strength: f32,
+    energy_based: bool,
+    weights: CutWeights,
+}
+
+impl BeatScore {
+    pub fn from_config(config: &CompositionConfig) -> Self {
+        Self {
+            min_cut_interval: config.min_cut_interval,
+            max_cut_interval: config.max_cut_interval,
+            beat_sync_strength: config.beat_sync_strength,
+            energy_based: config.energy_based_cuts,
+            weights: config.cut_weights,
+        }
+    }
+
+    /// Whether the beat scores high enough to cut on
+    pub fn should_cut(&self, context: &BeatContext) -> bool {
+        let beat = context.beat;
+
+        // Busier sections shrink the cut interval, calmer ones stretch it
+        let min_cut_interval = self.min_cut_interval / context.density;
+        let max_cut_interval = self.max_cut_interval / context.density;
+
+        if context.time_since_last_cut >= max_cut_interval {
+            return true;
+        }
+        if context.time_since_last_cut < min_cut_interval {
+            return false;
+        }
+
+        let mut score = beat.strength * self.weights.beat_strength;
+
+        if self.energy_based {
+            score += context.analysis.average_energy_in_range(beat.time - 0.5, beat.time + 0.5)
+                * self.weights.energy;
+        }
+
+        if beat.beat_type == BeatType::Downbeat {
+            score += self.weights.downbeat;
+        }
+
+        // Past the middle of the interval range, waiting makes a cut ever more likely
+        let ideal_interval = (min_cut_interval + max_cut_interval) / 2.0;
+        let waited = if context.time_since_last_cut >= ideal_interval {
+            0.2 + (context.time_since_last_cut - ideal_interval) / ideal_interval * 0.3
+        } else {
+            0.1
+        };
+        score += waited as f32;
+
+        score * self.beat_sync_strength >= self.weights.threshold
+    }
+}
+
+/// Per-section rotation through clip pools
+#[derive(Debug, Clone, Default)]
+struct Rotation {
+    positions: HashMap<PhraseType, usize>,
+}
+
+impl Rotation {
+    /// Next clip of the section's pool, skipping `previous` when the pool has others
+    fn next(&mut self, context: &ClipContext) -> Option<u32> {
+        let pool = context.pool;
+        if pool.is_empty() {
+            return None;
+        }
+
+        let index = self.positions.entry(context.phrase_type).or_insert(0);
+        let mut clip = pool[*index % pool.len()];
+        *index += 1;
+
+        if Some(clip) == context.previous && pool.len() > 1 {
+            clip = pool[*index % pool.len()];
+            *index += 1;
+        }
+
+        Some(clip)
+    }
+}
+
+/// Pool without the previous clip, unless that would leave it empty
+fn fresh_candidates(context: &ClipContext) -> Vec<u32> {
+    let others: Vec<u32> = context.pool.iter().copied().filter(|&id| Some(id) != context.previous).collect();
+    if others.is_empty() { context.pool.to_vec() } else { others }
+}
+
+/// Scored beat cuts, cycling through each section's clip pool in order
+#[derive(Debug, Clone)]
+pub struct RoundRobin {
+    score: BeatScore,
+    rotation: Rotation,
+}
+
+impl RoundRobin {
+    pub fn new(config: &CompositionConfig) -> Self {
+        Self {
+            score: BeatScore::from_config(config),
+            rotation: Rotation::default(),
+        }
+    }
+}
+
+impl CutStrategy for RoundRobin {
+    fn name(&self) -> &str {
+        "round_robin"
+    }
+
+    fn should_cut(&mut self, context: &BeatContext) -> bool {
+        self.score.should_cut(context)
+    }
+
+    fn next_clip(&mut self, context: &ClipContext) -> Option<u32> {
+        self.rotation.next(context)
+    }
+}
+
+/// Salt for the random stream of `WeightedRandom`
+const WEIGHTED_SALT: u64 = 0x5745_4947_4854;
+
+/// Scored beat cuts, drawing clips at random in proportion to their weight
+///
+/// A clip's weight is the largest `composition.clip_weights` entry among its
+/// name tags, or 1.0 when none match; a weight of 0 keeps a clip out unless
+/// nothing else is left. Each pick is drawn from the render seed and the cut
+/// number, so a seed always gives the same sequence.
+#[derive(Debug, Clone)]
+pub struct WeightedRandom {
+    score: BeatScore,
+    tag_weights: HashMap<String, f64>,
+    clip_weights: HashMap<u32, f64>,
+    picks: u64,
+}
+
+impl WeightedRandom {
+    pub fn new(config: &CompositionConfig) -> Self {
+        Self {
+            score: BeatScore::from_config(config),
+            tag_weights: config
+                .clip_weights
+                .iter()
+                .map(|(tag, &weight)| (tag.to_lowercase(), weight))
+                .collect(),
+            clip_weights: HashMap::new(),
+            picks: 0,
+        }
+    }
+}
+
+impl CutStrategy for WeightedRandom {
+    fn name(&self) -> &str {
+        "weighted_random"
+    }
+
+    fn prepare(&mut self, _analysis: &AudioAnalysis, clips: &[VideoClip]) {
+        self.clip_weights = clips
+            .iter()
+            .map(|clip| {
+                let weight = clip
+                    .tags()
+                    .iter()
+                    .filter_map(|tag| self.tag_weights.get(tag).copied())
+                    .reduce(f64::max)
+                    .unwrap_or(1.0);
+                (clip.sequence_number, weight)
+            })
+            .collect();
+    }
+
+    fn should_cut(&mut self, context: &BeatContext) -> bool {
+        self.score.should_cut(context)
+    }
+
+    fn next_clip(&mut self, context: &ClipContext) -> Option<u32> {
+        let candidates = fresh_candidates(context);
+        let weight = |id: &u32| self.clip_weights.get(id).copied().unwrap_or(1.0);
+        let total: f64 = candidates.iter().map(weight).sum();
+
+        let mut rng = SmallRng::seed_from_u64(hash(WEIGHTED_SALT, context.seed, self.picks));
+        self.picks += 1;
+
+        // An overflowing sum cannot be sampled, so fall back to a uniform pick
+        if !(total.is_finite() && total > 0.0) {
+            return candidates.get(rng.gen_range(0..candidates.len().max(1))).copied();
+        }
+
+        let mut pick = rng.gen_range(0.0..total);
+        for id in &candidates {
+            pick -= weight(id);
+            if pick < 0.0 {
+                return Some(*id);
+            }
+        }
+        candidates.last().copied()
+    }
+}
+
+/// Clips in file order, each given an equal share of the song
+///
+/// The song is split into one chapter per clip and the cut into each chapter
+/// lands on the first beat after it starts. Section pools and phrase cuts are
+/// ignored so the clips play as a story from first to last.
+#[derive(Debug, Clone, Default)]
+pub struct SequentialStory {
+    order: Vec<u32>,
+    chapter_length: f64,
+    chapter: usize,
+}
+
+impl SequentialStory {
+    pub fn new(_config: &CompositionConfig) -> Self {
+        Self::default()
+    }
+}
+
+impl CutStrategy for SequentialStory {
+    fn name(&self) -> &str {
+        "sequential_story"
+    }
+
+    fn prepare(&mut self, analysis: &AudioAnalysis, clips: &[VideoClip]) {
+        self.order = clips.iter().map(|clip| clip.sequence_number).collect();
+        self.order.sort_unstable();
+        self.chapter_length = analysis.duration / self.order.len().max(1) as f64;
+        self.chapter = 0;
+    }
+
+    fn should_cut(&mut self, context: &BeatContext) -> bool {
+        self.chapter + 1 < self.order.len()
+            && context.beat.time >= (self.chapter + 1) as f64 * self.chapter_length
+    }
+
+    fn next_clip(&mut self, context: &ClipContext) -> Option<u32> {
+        if context.previous.is_some() && self.chapter_length > 0.0 {
+            let chapter = (context.time / self.chapter_length) as usize;
+            self.chapter = chapter.max(self.chapter + 1).min(self.order.len().saturating_sub(1));
+        }
+        self.order.get(self.chapter).copied()
+    }
+
+    fn follows_structure(&self) -> bool {
+        false
+    }
+}
+
+/// Clip name tags that rate a clip's energy from 0 (calm) to 1 (intense)
+fn tag_energy(tag: &str) -> Option<f32> {
+    match tag {
+        "calm" | "low" | "slow" | "ambient" => Some(0.0),
+        "medium" | "mid" => Some(0.5),
+        "high" | "energy" | "hype" | "action" | "fast" => Some(1.0),
+        _ => None,
+    }
+}
+
+/// Scored beat cuts, choosing the clip whose energy best matches the music
+///
+/// Clip energy comes from name tags (calm, medium, hype, ...), untagged clips
+/// count as medium. Music energy is the RMS over the second after the cut,
+/// relative to the loudest passage of the song. Ties go to the clip that has
+/// gone longest without being used.
+#[derive(Debug, Clone)]
+pub struct EnergyMatched {
+    score: BeatScore,
+    clip_energy: HashMap<u32, f32>,
+    peak_rms: f32,
+    last_used: HashMap<u32, usize>,
+    cuts: usize,
+}
+
+impl EnergyMatched {
+    pub fn new(config: &CompositionConfig) -> Self {
+        Self {
+            score: BeatScore::from_config(config),
+            clip_energy: HashMap::new(),
+            peak_rms: 0.0,
+            last_used: HashMap::new(),
+            cuts: 0,
+        }
+    }
+}
+
+impl CutStrategy for EnergyMatched {
+    fn name(&self) -> &str {
+        "energy_matched"
+    }
+
+    fn prepare(&mut self, analysis: &AudioAnalysis, clips: &[VideoClip]) {
+        self.peak_rms = analysis.energy_levels.iter().map(|level| level.rms).fold(0.0, f32::max);
+        self.clip_energy = clips
+            .iter()
+            .map(|clip| {
+                let levels: Vec<f32> = clip.tags().iter().filter_map(|tag| tag_energy(tag)).collect();
+                let energy = if levels.is_empty() {
+                    0.5
+                } else {
+                    levels.iter().sum::<f32>() / levels.len() as f32
+                };
+                (clip.sequence_number, energy)
+            })
+            .collect();
+    }
+
+    fn should_cut(&mut self, context: &BeatContext) -> bool {
+        self.score.should_cut(context)
+    }
+
+    fn next_clip(&mut self, context: &ClipContext) -> Option<u32> {
+        let level = if self.peak_rms > 0.0 {
+            context.analysis.average_energy_in_range(context.time, context.time + 1.0) / self.peak_rms
+        } else {
+            0.5
+        };
+
+        let clip = fresh_candidates(context).into_iter().min_by(|a, b| {
+            let distance = |id: &u32| (self.clip_energy.get(id).copied().unwrap_or(0.5) - level).abs();
+            let recency = |id: &u32| self.last_used.get(id).map_or(0, |&cut| cut + 1);
+            distance(a).total_cmp(&distance(b)).then(recency(a).cmp(&recency(b)))
+        })?;
+
+        self.cuts += 1;
+        self.last_used.insert(clip, self.cuts);
+        Some(clip)
+    }
+}
+
+/// A cut on every `bars_per_cut`-th downbeat, rotating through each section's pool
+///
+/// Bars are counted from the previous cut, so a forced cut at a phrase start
+/// restarts the count.
+#[derive(Debug, Clone)]
+pub struct EveryNBars {
+    bars_per_cut: u32,
+    bars: u32,
+    rotation: Rotation,
+}
+
+impl EveryNBars {
+    pub fn new(config: &CompositionConfig) -> Self {
+        Self {
+            bars_per_cut: config.bars_per_cut.max(1),
+            bars: 0,
+            rotation: Rotation::default(),
+        }
+    }
+}
+
+impl CutStrategy for EveryNBars {
+    fn name(&self) -> &str {
+        "every_n_bars"
+    }
+
+    fn should_cut(&mut self, context: &BeatContext) -> bool {
+        if context.beat.beat_type != BeatType::Downbeat || context.time_since_last_cut <= 0.0 {
+            return false;
+        }
+        self.bars += 1;
+        self.bars >= self.bars_per_cut
+    }
+
+    fn next_clip(&mut self, context: &ClipContext) -> Option<u32> {
+        self.bars = 0;
+        self.rotation.next(context)
+    }
+}
+
+type StrategyFactory = Box<dyn Fn(&CompositionConfig) -> Box<dyn CutStrategy> + Send + Sync>;
+
+/// Registry of cut strategies by name
+///
+/// Holds a factory per strategy; `create` builds a fresh instance configured
+/// from the composition settings.
+pub struct CutStrategyRegistry {
+    strategies: HashMap<String, StrategyFactory>,
+}
+
+/// Registry key for a strategy name, so "Every-N-Bars" finds "every_n_bars"
+fn normalize(name: &str) -> String {
+    name.to_lowercase().replace('-', "_")
+}
+
+impl CutStrategyRegistry {
+    /// Create a registry with all built-in strategies
+    pub fn new() -> Self {
+        let mut registry = Self {
+            strategies: HashMap::new(),
+        };
+
+        registry.register("round_robin", |config| Box::new(RoundRobin::new(config)));
+        registry.register("weighted_random", |config| Box::new(WeightedRandom::new(config)));
+        registry.register("sequential_story", |config| Box::new(SequentialStory::new(config)));
+        registry.register("energy_matched", |config| Box::new(EnergyMatched::new(config)));
+        registry.register("every_n_bars", |config| Box::new(EveryNBars::new(config)));
+        registry
+    }
+
+    /// Register a strategy, replacing any registered under the same name
+    pub fn register<F>(&mut self, name: impl Into<String>, factory: F)
+    where
+        F: Fn(&CompositionConfig) -> Box<dyn CutStrategy> + Send + Sync + 'static,
+    {
+        self.strategies.insert(normalize(&name.into()), Box::new(factory));
+    }
+
+    /// New instance of the named strategy, or None if it isn't registered
+    pub fn create(&self, name: &str, config: &CompositionConfig) -> Option<Box<dyn CutStrategy>> {
+        self.strategies.get(&normalize(name)).map(|factory| factory(config))
+    }
+
+    /// Get all available strategy names
+    pub fn available_strategies(&self) -> Vec<String> {
+        self.strategies.keys().cloned().collect()
+    }
+
+    /// Check if a strategy is available
+    pub fn has_strategy(&self, name: &str) -> bool {
+        self.strategies.contains_key(&normalize(name))
+    }
+}
+
+impl Default for CutStrategyRegistry {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::audio::types::AnalysisBuilder;
+
+    /// 120 BPM in 4/4 with a quiet first half and a loud second half
+    fn analysis(duration: f64) -> AudioAnalysis {
+        let builder = (0..(duration * 2.0) as usize).fold(AnalysisBuilder::new(duration), |builder, i| {
+            builder.beat(i as f64 * 0.5, if i % 4 == 0 { BeatType::Downbeat } else { BeatType::Beat })
+        });
+        (0..(duration * 10.0) as usize)
+            .fold(builder, |builder, i| {
+                builder.energy(i as f64 / 10.0, if (i as f64) < duration * 5.0 { 0.1 } else { 0.8 })
+            })
+            .build()
+    }
+
+    fn clips(names: &[&str]) -> Vec<VideoClip> {
+        names
+            .iter()
+            .enumerate()
+            .map(|(i, name)| VideoClip::new(format!("{:02}_{}.mp4", i + 1, name), i as u32 + 1, name.to_string()))
+            .collect()
+    }
+
+    /// Drive a strategy over the beats the way the engine does, without phrases
+    fn run(strategy: &mut dyn CutStrategy, analysis: &AudioAnalysis, clips: &[VideoClip]) -> Vec<(f64, u32)> {
+        run_seeded(strategy, analysis, clips, 0)
+    }
+
+    fn run_seeded(
+        strategy: &mut dyn CutStrategy,
+        analysis: &AudioAnalysis,
+        clips: &[VideoClip],
+        seed: u64,
+    ) -> Vec<(f64, u32)> {
+        let pool: Vec<u32> = clips.iter().map(|clip| clip.sequence_number).collect();
+        let clip_at = |strategy: &mut dyn CutStrategy, time: f64, previous: Option<u32>| {
+            strategy.next_clip(&ClipContext {
+                time,
+                phrase_type: PhraseType::Unknown,
+                pool: &pool,
+                clips,
+                previous,
+                analysis,
+                seed,
+            })
+        };
+
+        strategy.prepare(analysis, clips);
+        let mut cuts = vec![(0.0, clip_at(strategy, 0.0, None).unwrap())];
+
+        for beat in &analysis.beats {
+            let (last_time, last_clip) = *cuts.last().unwrap();
+            let context = BeatContext {
+                beat,
+                time_since_last_cut: beat.time - last_time,
+                density: 1.0,
+                analysis,
+                seed,
+            };
+            if beat.time > 0.0 && strategy.should_cut(&context) {
+                cuts.push((beat.time, clip_at(strategy, beat.time, Some(last_clip)).unwrap()));
+            }
+        }
+        cuts
+    }
+
+    #[test]
+    fn test_registry_builds_builtins_and_custom_strategies() {
+        let mut registry = CutStrategyRegistry::new();
+        let config = CompositionConfig::default();
+
+        for name in ["round_robin", "weighted_random", "sequential_story", "energy_matched", "every_n_bars"] {
+            assert_eq!(registry.create(name, &config).unwrap().name(), name);
+        }
+        assert!(registry.has_strategy("every-n-bars"));
+        assert!(registry.create("shuffle", &config).is_none());
+
+        registry.register("shuffle", |config| Box::new(WeightedRandom::new(config)));
+        assert!(registry.has_strategy("shuffle"));
+        assert_eq!(registry.available_strategies().len(), 6);
+
+        // Names are matched the same way whether registering or looking up
+        registry.register("My-Cut", |config| Box::new(RoundRobin::new(config)));
+        assert!(registry.has_strategy("my_cut"));
+        assert_eq!(registry.create("MY-CUT", &config).unwrap().name(), "round_robin");
+    }
+
+    #[test]
+    fn test_round_robin_rotates_within_section_pool() {
+        let analysis = analysis(4.0);
+        let clips = clips(&["a", "b", "c", "d"]);
+        let mut strategy = RoundRobin::new(&CompositionConfig::default());
+        let mut next = |time: f64, pool: &[u32], previous: Option<u32>| {
+            strategy.next_clip(&ClipContext {
+                time,
+                phrase_type: if time < 8.0 { PhraseType::Intro } else { PhraseType::Chorus },
+                pool,
+                clips: &clips,
+                previous,
+                analysis: &analysis,
+                seed: 0,
+            })
+        };
+
+        assert_eq!(next(1.0, &[1], None), Some(1));
+        assert_eq!(next(9.0, &[3, 4], Some(1)), Some(3));
+        assert_eq!(next(10.0, &[3, 4], Some(3)), Some(4));
+        assert_eq!(next(11.0, &[3, 4], Some(4)), Some(3));
+    }
+
+    #[test]
+    fn test_every_n_bars_cuts_on_downbeats() {
+        let analysis = analysis(20.0);
+        let clips = clips(&["a", "b", "c"]);
+        let config = CompositionConfig { bars_per_cut: 2, ..Default::default() };
+
+        let cuts = run(&mut EveryNBars::new(&config), &analysis, &clips);
+
+        // Bars are 2s long at 120 BPM in 4/4
+        let times: Vec<f64> = cuts.iter().map(|&(time, _)| time).collect();
+        assert_eq!(times, vec![0.0, 4.0, 8.0, 12.0, 16.0]);
+        assert_eq!(cuts.iter().map(|&(_, clip)| clip).collect::<Vec<_>>(), vec![1, 2, 3, 1, 2]);
+    }
+
+    #[test]
+    fn test_sequential_story_plays_clips_in_order() {
+        let analysis = analysis(30.0);
+        let clips = clips(&["c", "a", "b"]);
+
+        let cuts = run(&mut SequentialStory::new(&CompositionConfig::default()), &analysis, &clips);
+
+        assert_eq!(cuts, vec![(0.0, 1), (10.0, 2), (20.0, 3)]);
+    }
+
+    #[test]
+    fn test_energy_matched_follows_loudness() {
+        let analysis = analysis(40.0);
+        let clips = clips(&["calm_beach", "street", "crowd_hype", "ambient_sky", "action_fast"]);
+
+        let cuts = run(&mut EnergyMatched::new(&CompositionConfig::default()), &analysis, &clips);
+
+        let calm = [1, 4];
+        let intense = [3, 5];
+        assert!(cuts.len() > 4);
+        for &(time, clip) in &cuts {
+            if time < 19.0 {
+                assert!(calm.contains(&clip), "clip {} at {}s", clip, time);
+            } else if time >= 20.0 {
+                assert!(intense.contains(&clip), "clip {} at {}s", clip, time);
+            }
+        }
+    }
+
+    #[test]
+    fn test_weighted_random_follows_weights() {
+        let analysis = analysis(4.0);
+        let clips = clips(&["street", "beach_favorite", "crowd_outtake"]);
+        let mut config = CompositionConfig::default();
+        config.clip_weights.insert("favorite".to_string(), 8.0);
+        config.clip_weights.insert("outtake".to_string(), 0.0);
+
+        let mut strategy = WeightedRandom::new(&config);
+        strategy.prepare(&analysis, &clips);
+
+        let mut counts = HashMap::new();
+        for _ in 0..900 {
+            let clip = strategy
+                .next_clip(&ClipContext {
+                    time: 1.0,
+                    phrase_type: PhraseType::Unknown,
+                    pool: &[1, 2, 3],
+                    clips: &clips,
+                    previous: None,
+                    analysis: &analysis,
+                    seed: 0,
+                })
+                .unwrap();
+            *counts.entry(clip).or_insert(0) += 1;
+        }
+
+        assert!(!counts.contains_key(&3));
+        assert!(counts[&2] > counts[&1] * 4);
+    }
+
+    #[test]
+    fn test_weighted_random_survives_overflowing_weights() {
+        let analysis = analysis(4.0);
+        let clips = clips(&["a_huge", "b_huge"]);
+        let mut config = CompositionConfig::default();
+        config.clip_weights.insert("huge".to_string(), f64::MAX);
+
+        let mut strategy = WeightedRandom::new(&config);
+        let cuts = run(&mut strategy, &analysis, &clips);
+        assert!(cuts.iter().all(|(_, clip)| [1, 2].contains(clip)));
+    }
+
+    #[test]
+    fn test_weighted_random_is_reproducible_per_seed() {
+        let analysis = analysis(60.0);
+        let clips = clips(&["a", "b", "c", "d", "e"]);
+        let config = CompositionConfig::default();
+        let render = |seed: u64| run_seeded(&mut WeightedRandom::new(&config), &analysis, &clips, seed);
+
+        let once = render(7);
+        assert_eq!(once, render(7));
+        assert_ne!(once, render(8));
+    }
+}
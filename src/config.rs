@@ -1,15 +1,51 @@
+use std::collections::HashMap;
 use std::path::Path;
 use serde::{Deserialize, Serialize};
 
 use crate::{
+    audio::AnalysisConfig,
     error::{ConfigError, Result},
     styles::StyleConfig,
-    video::VideoParams,
+    video::{FieldOrder, FrameRate, OutputPreset, TransitionType, VideoParams},
 };
 
+/// TOML integers are signed 64-bit, so seeds above `i64::MAX` are written as strings
+mod seed_format {
+    use serde::{Deserialize, Deserializer, Serializer};
+
+    pub fn serialize<S: Serializer>(seed: &u64, serializer: S) -> std::result::Result<S::Ok, S::Error> {
+        match i64::try_from(*seed) {
+            Ok(seed) => serializer.serialize_i64(seed),
+            Err(_) => serializer.serialize_str(&seed.to_string()),
+        }
+    }
+
+    /// Accepts plain numbers (`1234`) as well as strings (`"18446744073709551615"`)
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> std::result::Result<u64, D::Error> {
+        #[derive(Deserialize)]
+        #[serde(untagged)]
+        enum Repr {
+            Number(u64),
+            Text(String),
+        }
+
+        match Repr::deserialize(deserializer)? {
+            Repr::Number(seed) => Ok(seed),
+            Repr::Text(text) => text
+                .parse()
+                .map_err(|_| serde::de::Error::custom(format!("invalid seed {:?}", text))),
+        }
+    }
+}
+
 /// Main configuration for the Retro-Compositor
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct Config {
+    /// Seed for every random choice in a render: style noise, glitches and
+    /// random clip picks. The same inputs and seed give byte-identical frames.
+    #[serde(default, with = "seed_format")]
+    pub seed: u64,
+
     /// Audio analysis settings
     pub audio: AudioConfig,
 
@@ -21,28 +57,27 @@ pub struct Config {
 
     /// Default style configuration
     pub style: StyleConfig,
-}
 
-impl Default for Config {
-    fn default() -> Self {
-        Self {
-            audio: AudioConfig::default(),
-            video: VideoConfig::default(),
-            composition: CompositionConfig::default(),
-            style: StyleConfig::default(),
-        }
-    }
+    /// Settings for quick preview renders
+    #[serde(default)]
+    pub preview: PreviewConfig,
 }
 
 impl Config {
-    /// Load configuration from a TOML file
+    /// Load configuration from a TOML file, rejecting invalid settings
     pub fn from_file<P: AsRef<Path>>(path: P) -> Result<Self> {
         let path = path.as_ref();
         let content = std::fs::read_to_string(path)
             .map_err(|_| ConfigError::FileNotFound { path: path.display().to_string() })?;
 
-        let config: Config = toml::from_str(&content)
+        let mut config: Config = toml::from_str(&content)
             .map_err(|_| ConfigError::ParseFailed { path: path.display().to_string() })?;
+
+        if let Some(preset) = config.video.preset {
+            config.video.set_preset(preset);
+        }
+
+        config.validate()?;
         Ok(config)
     }
 
@@ -63,6 +98,7 @@ impl Config {
         self.audio.validate()?;
         self.video.validate()?;
         self.composition.validate()?;
+        self.preview.validate()?;
         Ok(())
     }
 }
@@ -107,6 +143,20 @@ impl Default for AudioConfig {
 }
 
 impl AudioConfig {
+    /// Analyzer settings for these options
+    pub fn analysis_config(&self) -> AnalysisConfig {
+        AnalysisConfig {
+            window_size: self.window_size,
+            hop_size: self.hop_size,
+            min_bpm: self.min_bpm,
+            max_bpm: self.max_bpm,
+            beat_sensitivity: self.beat_sensitivity,
+            energy_window_size: 0.1,
+            detect_phrases: true,
+            calculate_spectral_features: true,
+        }
+    }
+
     fn validate(&self) -> Result<()> {
         if self.sample_rate == 0 {
             return Err(ConfigError::InvalidValue {
@@ -139,6 +189,10 @@ pub struct VideoConfig {
     /// Video processing parameters
     pub params: VideoParams,
 
+    /// Named output format; overrides `params.resolution` and `params.fit_mode`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub preset: Option<OutputPreset>,
+
     /// Maximum clip duration in seconds
     pub max_clip_duration: f64,
 
@@ -156,6 +210,7 @@ impl Default for VideoConfig {
     fn default() -> Self {
         Self {
             params: VideoParams::default(),
+            preset: None,
             max_clip_duration: 30.0,
             min_clip_duration: 0.5,
             processing_threads: num_cpus::get(),
@@ -165,6 +220,12 @@ impl Default for VideoConfig {
 }
 
 impl VideoConfig {
+    /// Switch to a named output format
+    pub fn set_preset(&mut self, preset: OutputPreset) {
+        preset.apply(&mut self.params);
+        self.preset = Some(preset);
+    }
+
     fn validate(&self) -> Result<()> {
         if self.max_clip_duration <= self.min_clip_duration {
             return Err(ConfigError::InvalidValue {
@@ -190,9 +251,13 @@ pub struct CompositionConfig {
     /// How closely to sync cuts to beats (0.0-1.0)
     pub beat_sync_strength: f32,
 
-    /// Allow cuts on musical phrases (not just beats)
+    /// Force cuts at phrase starts and vary clip pools and cut density per section
     pub phrase_aware_cuts: bool,
 
+    /// Clip name tags allowed in each section, keyed by phrase type (e.g. "chorus")
+    #[serde(default)]
+    pub clip_pools: HashMap<String, Vec<String>>,
+
     /// Minimum time between cuts (seconds)
     pub min_cut_interval: f64,
 
@@ -203,7 +268,71 @@ pub struct CompositionConfig {
     pub energy_based_cuts: bool,
 
     /// Crossfade duration between clips (seconds)
+    ///
+    /// Split evenly across the cut, and at most half of `min_cut_interval`;
+    /// longer values are rejected when the config is loaded. Segments too
+    /// short for it, such as those in dense sections, get a transition of at
+    /// most half their length.
     pub crossfade_duration: f64,
+
+    /// Transition used at cuts that don't choose their own
+    #[serde(default)]
+    pub transition: TransitionType,
+
+    /// How cuts are placed and clips chosen: round_robin, weighted_random,
+    /// sequential_story, energy_matched, every_n_bars or a registered custom name
+    #[serde(default = "default_cut_strategy")]
+    pub cut_strategy: String,
+
+    /// Weights used to score beats as cut candidates
+    #[serde(default)]
+    pub cut_weights: CutWeights,
+
+    /// Bars between cuts for the every_n_bars strategy
+    #[serde(default = "default_bars_per_cut")]
+    pub bars_per_cut: u32,
+
+    /// Relative pick weight of clips by name tag for the weighted_random strategy
+    #[serde(default)]
+    pub clip_weights: HashMap<String, f64>,
+}
+
+fn default_cut_strategy() -> String {
+    "round_robin".to_string()
+}
+
+fn default_bars_per_cut() -> u32 {
+    4
+}
+
+/// Weights of the beat score that decides whether to cut on a beat
+///
+/// A beat between the minimum and maximum cut interval is cut on when its
+/// score, scaled by `beat_sync_strength`, reaches `threshold`.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct CutWeights {
+    /// Weight of the beat's detected strength (0.0-1.0)
+    pub beat_strength: f32,
+
+    /// Weight of the RMS energy around the beat
+    pub energy: f32,
+
+    /// Bonus for the first beat of a bar
+    pub downbeat: f32,
+
+    /// Score needed to cut
+    pub threshold: f32,
+}
+
+impl Default for CutWeights {
+    fn default() -> Self {
+        Self {
+            beat_strength: 0.5,
+            energy: 0.4,
+            downbeat: 0.3,
+            threshold: 0.4,
+        }
+    }
 }
 
 impl Default for CompositionConfig {
@@ -211,10 +340,16 @@ impl Default for CompositionConfig {
         Self {
             beat_sync_strength: 0.8,
             phrase_aware_cuts: true,
+            clip_pools: HashMap::new(),
             min_cut_interval: 1.0,
             max_cut_interval: 8.0,
             energy_based_cuts: true,
             crossfade_duration: 0.1,
+            transition: TransitionType::default(),
+            cut_strategy: default_cut_strategy(),
+            cut_weights: CutWeights::default(),
+            bars_per_cut: default_bars_per_cut(),
+            clip_weights: HashMap::new(),
         }
     }
 }
@@ -235,6 +370,96 @@ impl CompositionConfig {
             }.into());
         }
 
+        // A transition may take at most half of the minimum cut interval
+        if !(0.0..=self.min_cut_interval / 2.0).contains(&self.crossfade_duration) {
+            return Err(ConfigError::InvalidValue {
+                key: "composition.crossfade_duration".to_string(),
+                value: self.crossfade_duration.to_string()
+            }.into());
+        }
+
+        if self.bars_per_cut == 0 {
+            return Err(ConfigError::InvalidValue {
+                key: "composition.bars_per_cut".to_string(),
+                value: self.bars_per_cut.to_string()
+            }.into());
+        }
+
+        if let Some((tag, weight)) = self.clip_weights.iter().find(|(_, w)| !(w.is_finite() && **w >= 0.0)) {
+            return Err(ConfigError::InvalidValue {
+                key: format!("composition.clip_weights.{}", tag),
+                value: weight.to_string()
+            }.into());
+        }
+
+        Ok(())
+    }
+}
+
+/// Preview render configuration
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PreviewConfig {
+    /// Fraction of the output resolution to render at (0.0-1.0]
+    pub scale: f64,
+
+    /// Highest frame rate to render at
+    pub max_fps: f64,
+
+    /// Encoder quality setting (0-100)
+    pub quality: u8,
+}
+
+impl Default for PreviewConfig {
+    fn default() -> Self {
+        Self {
+            scale: 0.25,
+            max_fps: 12.0,
+            quality: 50,
+        }
+    }
+}
+
+impl PreviewConfig {
+    /// Output parameters for a preview of a render with `params`
+    ///
+    /// Keeps the aspect ratio and fit mode so the preview frames the same shots,
+    /// but always renders progressive frames.
+    pub fn apply(&self, params: &VideoParams) -> VideoParams {
+        // Encoders want even dimensions
+        let scale_dimension = |d: u32| (((d as f64 * self.scale) / 2.0).round() as u32 * 2).max(2);
+
+        VideoParams {
+            fps: params.fps.min(FrameRate::from_f64(self.max_fps)),
+            resolution: (scale_dimension(params.resolution.0), scale_dimension(params.resolution.1)),
+            quality: self.quality,
+            field_order: FieldOrder::Progressive,
+            draft: true,
+            ..params.clone()
+        }
+    }
+
+    fn validate(&self) -> Result<()> {
+        if !(self.scale > 0.0 && self.scale <= 1.0) {
+            return Err(ConfigError::InvalidValue {
+                key: "preview.scale".to_string(),
+                value: self.scale.to_string()
+            }.into());
+        }
+
+        if !(self.max_fps.is_finite() && self.max_fps > 0.0) {
+            return Err(ConfigError::InvalidValue {
+                key: "preview.max_fps".to_string(),
+                value: self.max_fps.to_string()
+            }.into());
+        }
+
+        if self.quality > 100 {
+            return Err(ConfigError::InvalidValue {
+                key: "preview.quality".to_string(),
+                value: self.quality.to_string()
+            }.into());
+        }
+
         Ok(())
     }
 }
@@ -266,6 +491,18 @@ mod tests {
         assert_eq!(original_config.video.params.fps, loaded_config.video.params.fps);
     }
 
+    #[test]
+    fn test_from_file_rejects_invalid_config() {
+        let dir = tempdir().unwrap();
+        let file_path = dir.path().join("invalid.toml");
+
+        let mut config = Config::default();
+        config.composition.bars_per_cut = 0;
+        config.save_to_file(&file_path).unwrap();
+
+        assert!(Config::from_file(&file_path).is_err());
+    }
+
     #[test]
     fn test_invalid_audio_config() {
         let mut config = Config::default();
@@ -280,4 +517,110 @@ mod tests {
         config.audio.max_bpm = 100.0;
         assert!(config.validate().is_err());
     }
+
+    #[test]
+    fn test_invalid_crossfade_duration() {
+        let mut config = Config::default();
+        config.composition.crossfade_duration = -0.5;
+        assert!(config.validate().is_err());
+
+        config.composition.crossfade_duration = config.composition.min_cut_interval * 0.75;
+        assert!(config.validate().is_err());
+
+        config.composition.crossfade_duration = config.composition.min_cut_interval / 2.0;
+        assert!(config.validate().is_ok());
+
+        // An oversized crossfade in a config file is refused, not clamped at render time
+        let dir = tempdir().unwrap();
+        let file_path = dir.path().join("long_crossfade.toml");
+        config.composition.crossfade_duration = config.composition.min_cut_interval;
+        config.save_to_file(&file_path).unwrap();
+        assert!(Config::from_file(&file_path).is_err());
+    }
+
+    #[test]
+    fn test_invalid_clip_weights() {
+        for weight in [f64::NAN, f64::INFINITY, -1.0] {
+            let mut config = Config::default();
+            config.composition.clip_weights.insert("broken".to_string(), weight);
+            assert!(config.validate().is_err());
+        }
+    }
+
+    #[test]
+    fn test_preset_in_file_sets_resolution() {
+        let dir = tempdir().unwrap();
+        let file_path = dir.path().join("vertical.toml");
+
+        let mut value = toml::Value::try_from(Config::default()).unwrap();
+        value["video"].as_table_mut().unwrap().insert("preset".to_string(), "vertical".into());
+        std::fs::write(&file_path, toml::to_string(&value).unwrap()).unwrap();
+
+        let config = Config::from_file(&file_path).unwrap();
+        assert_eq!(config.video.preset, Some(OutputPreset::Vertical));
+        assert_eq!(config.video.params.resolution, (1080, 1920));
+    }
+
+    #[test]
+    fn test_preview_params_scale_down() {
+        let mut config = Config::default();
+        config.video.set_preset(OutputPreset::Vertical);
+
+        let preview = config.preview.apply(&config.video.params);
+
+        assert_eq!(preview.resolution, (270, 480));
+        assert_eq!(preview.fps, FrameRate::integer(12));
+        assert_eq!(preview.fit_mode, config.video.params.fit_mode);
+        assert!(preview.draft);
+
+        config.video.set_preset(OutputPreset::Pal);
+        let preview = config.preview.apply(&config.video.params);
+        assert_eq!(preview.pixel_aspect, (16, 15));
+        assert_eq!(preview.field_order, FieldOrder::Progressive);
+
+        config.preview.scale = 0.0;
+        assert!(config.validate().is_err());
+    }
+
+    #[test]
+    fn test_invalid_preview_config() {
+        for max_fps in [f64::NAN, f64::INFINITY, 0.0] {
+            let mut config = Config::default();
+            config.preview.max_fps = max_fps;
+            assert!(config.validate().is_err());
+        }
+
+        let mut config = Config::default();
+        config.preview.quality = 101;
+        assert!(config.validate().is_err());
+    }
+
+    #[test]
+    fn test_seed_roundtrip_and_default() {
+        let dir = tempdir().unwrap();
+        let file_path = dir.path().join("seeded.toml");
+
+        let config = Config { seed: 1234, ..Config::default() };
+        config.save_to_file(&file_path).unwrap();
+        assert_eq!(Config::from_file(&file_path).unwrap().seed, 1234);
+
+        // Past i64::MAX the seed no longer fits a TOML integer
+        let config = Config { seed: u64::MAX, ..Config::default() };
+        config.save_to_file(&file_path).unwrap();
+        assert_eq!(Config::from_file(&file_path).unwrap().seed, u64::MAX);
+
+        let mut value = toml::Value::try_from(Config::default()).unwrap();
+        value.as_table_mut().unwrap().remove("seed");
+        let config: Config = value.try_into().unwrap();
+        assert_eq!(config.seed, 0);
+    }
+
+    #[test]
+    fn test_transition_defaults_when_missing() {
+        let mut value = toml::Value::try_from(Config::default()).unwrap();
+        value["composition"].as_table_mut().unwrap().remove("transition");
+
+        let config: Config = value.try_into().unwrap();
+        assert_eq!(config.composition.transition, TransitionType::Crossfade);
+    }
 }
\ No newline at end of file
@@ -44,9 +44,9 @@
 //! You can create custom retro styles by implementing the [`Style`](styles::Style) trait:
 //!
 //! ```rust,no_run
-//! use retro_compositor::styles::{Style, StyleConfig};
+//! use retro_compositor::styles::{FrameContext, Style, StyleConfig};
 //! use retro_compositor::video::types::Frame;
-//! use anyhow::Result;
+//! use retro_compositor::Result;
 //!
 //! struct MyCustomStyle;
 //!
@@ -55,8 +55,13 @@
 //!         "my_custom"
 //!     }
 //!
-//!     fn apply_effect(&self, frame: &mut Frame, config: &StyleConfig) -> Result<()> {
-//!         // Your custom effect implementation
+//!     fn description(&self) -> &str {
+//!         "My custom retro look"
+//!     }
+//!
+//!     fn apply_effect(&self, frame: &mut Frame, config: &StyleConfig, context: &FrameContext) -> Result<()> {
+//!         // Your custom effect implementation; `context` has the frame's
+//!         // time, beat phase, energy and section
 //!         Ok(())
 //!     }
 //! }
@@ -1,15 +1,14 @@
 use std::path::PathBuf;
-// TODO: Implement actual audio analysis
-// For now, we'll create a placeholder message
 use anyhow::Result;
-use clap::Parser;
+use clap::{Args, CommandFactory, Parser, Subcommand};
 use tracing::{info, Level};
-use tracing_subscriber;
 
 use retro_compositor::{
-    composition::CompositionEngine,
+    audio::{report::ReportFormat, AnalysisCache, AudioAnalyzer, AudioLoader},
+    composition::{diagnostics, CompositionEngine, CutStrategyRegistry, TimelineDocument},
     config::Config,
     styles::StyleRegistry,
+    video::OutputPreset,
 };
 
 #[derive(Parser)]
@@ -17,9 +16,38 @@ use retro_compositor::{
     name = "retro-compositor",
     version,
     about = "Transform your music into retro-styled video compositions",
-    long_about = "Retro-Compositor automatically creates nostalgic video compositions by analyzing audio tracks and intelligently cutting between video clips in sync with the music."
+    long_about = "Retro-Compositor automatically creates nostalgic video compositions by analyzing audio tracks and intelligently cutting between video clips in sync with the music.",
+    args_conflicts_with_subcommands = true,
+    arg_required_else_help = true
 )]
 struct Cli {
+    #[command(subcommand)]
+    command: Option<Command>,
+
+    // Without a subcommand, compose: `retro-compositor -a song.wav -v clips -o out.mp4`
+    #[command(flatten)]
+    compose: Option<ComposeArgs>,
+
+    /// Configuration file (optional)
+    #[arg(short, long, global = true)]
+    config: Option<PathBuf>,
+
+    /// Enable verbose logging
+    #[arg(long, global = true)]
+    verbose: bool,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Cut video clips to a song and render the result
+    Compose(ComposeArgs),
+
+    /// Analyze a song and print its beats, tempo, energy and sections
+    Analyze(AnalyzeArgs),
+}
+
+#[derive(Args)]
+struct ComposeArgs {
     /// Audio file path (WAV, MP3, FLAC)
     #[arg(short, long)]
     audio: PathBuf,
@@ -36,36 +64,83 @@ struct Cli {
     #[arg(short, long, default_value = "vhs")]
     style: String,
 
-    /// Configuration file (optional)
+    /// Output format preset (landscape, vertical, square, portrait, retro, ntsc, pal)
     #[arg(short, long)]
-    config: Option<PathBuf>,
+    preset: Option<String>,
 
-    /// Enable verbose logging
+    /// Cut strategy (round_robin, weighted_random, sequential_story, energy_matched, every_n_bars)
+    #[arg(long)]
+    cut_strategy: Option<String>,
+
+    /// Seed for noise, glitches and random clip picks; the same seed renders the same video
+    #[arg(long)]
+    seed: Option<u64>,
+
+    /// Render a fast low-resolution preview instead of the final video
+    #[arg(long)]
+    preview: bool,
+
+    /// Render this edited timeline (.json or .toml) instead of generating one
+    #[arg(long)]
+    timeline: Option<PathBuf>,
+
+    /// Write the rendered timeline for editing: .json or .toml, or .edl / .fcpxml for an NLE
+    #[arg(long)]
+    export_timeline: Option<PathBuf>,
+
+    /// Analyze the audio from scratch instead of reusing a cached analysis
+    #[arg(long)]
+    no_cache: bool,
+
+    /// Draw beats, onsets, energy, sections and cuts to this PNG
+    #[arg(long)]
+    debug_plot: Option<PathBuf>,
+}
+
+#[derive(Args)]
+struct AnalyzeArgs {
+    /// Audio file path (WAV, MP3, FLAC)
+    audio: PathBuf,
+
+    /// Output format (json, csv, labels)
+    #[arg(short, long, default_value = "json")]
+    format: String,
+
+    /// Write the report to this file instead of stdout
     #[arg(short, long)]
-    verbose: bool,
+    output: Option<PathBuf>,
+
+    /// Also write an Audacity label track of beats and sections
+    #[arg(long)]
+    labels: Option<PathBuf>,
+
+    /// Also draw the waveform, onsets, beats, energy and sections to this PNG
+    #[arg(long)]
+    plot: Option<PathBuf>,
+
+    /// Analyze the audio from scratch instead of reusing a cached analysis
+    #[arg(long)]
+    no_cache: bool,
 }
 
 #[tokio::main]
 async fn main() -> Result<()> {
     let cli = Cli::parse();
 
-    // Initialize logging
+    // Initialize logging; stdout is kept for reports
     let log_level = if cli.verbose { Level::DEBUG } else { Level::INFO };
     tracing_subscriber::fmt()
         .with_max_level(log_level)
+        .with_writer(std::io::stderr)
         .init();
 
     info!("Starting Retro-Compositor v{}", env!("CARGO_PKG_VERSION"));
-    info!("Audio: {:?}", cli.audio);
-    info!("Videos: {:?}", cli.videos);
-    info!("Output: {:?}", cli.output);
-    info!("Style: {}", cli.style);
 
     // Load configuration
-    let config = match cli.config {
+    let config = match &cli.config {
         Some(config_path) => {
             info!("Loading configuration from {:?}", config_path);
-            Config::from_file(&config_path)?
+            Config::from_file(config_path)?
         }
         None => {
             info!("Using default configuration");
@@ -73,22 +148,119 @@ async fn main() -> Result<()> {
         }
     };
 
+    match (cli.command, cli.compose) {
+        (Some(Command::Compose(args)), _) | (None, Some(args)) => compose(args, config).await,
+        (Some(Command::Analyze(args)), _) => analyze(args, config).await,
+        (None, None) => {
+            Cli::command().print_help()?;
+            Ok(())
+        }
+    }
+}
+
+async fn compose(args: ComposeArgs, mut config: Config) -> Result<()> {
+    info!("Audio: {:?}", args.audio);
+    info!("Videos: {:?}", args.videos);
+    info!("Output: {:?}", args.output);
+    info!("Style: {}", args.style);
+
+    if let Some(name) = &args.preset {
+        let preset = OutputPreset::from_name(name)
+            .ok_or_else(|| anyhow::anyhow!("Unknown preset: {}", name))?;
+        config.video.set_preset(preset);
+
+        let (width, height) = preset.resolution();
+        info!("Preset: {} ({}, {}x{})", preset.name(), preset.aspect(), width, height);
+    }
+
+    if let Some(name) = &args.cut_strategy {
+        if !CutStrategyRegistry::new().has_strategy(name) {
+            anyhow::bail!("Unknown cut strategy: {}", name);
+        }
+        config.composition.cut_strategy = name.clone();
+    }
+
+    if let Some(seed) = args.seed {
+        config.seed = seed;
+    }
+
+    // Command-line overrides must leave the configuration valid too
+    config.validate()?;
+
     // Initialize style registry and get the requested style
     let style_registry = StyleRegistry::new();
     let style = style_registry
-        .get_style(&cli.style)
-        .ok_or_else(|| anyhow::anyhow!("Unknown style: {}", cli.style))?;
+        .get_style(&args.style)
+        .ok_or_else(|| anyhow::anyhow!("Unknown style: {}", args.style))?;
 
     info!("Using {} style", style.name());
 
     // Create and run the composition engine
-    let engine = CompositionEngine::new(config, style);
+    let mut engine = CompositionEngine::new(config, style);
+
+    if let Some(path) = &args.timeline {
+        info!("Loading timeline from {:?}", path);
+        engine = engine.with_timeline(TimelineDocument::load(path)?);
+    }
+
+    if let Some(path) = &args.export_timeline {
+        engine = engine.with_timeline_export(path);
+    }
+
+    if args.no_cache {
+        engine = engine.without_analysis_cache();
+    }
+
+    if let Some(path) = &args.debug_plot {
+        engine = engine.with_debug_plot(path);
+    }
 
     info!("Starting composition process...");
-    engine
-        .compose(&cli.audio, &cli.videos, &cli.output)
+    if args.preview {
+        engine
+            .compose_preview(&args.audio, &args.videos, &args.output)
+            .await?;
+    } else {
+        engine
+            .compose(&args.audio, &args.videos, &args.output)
+            .await?;
+    }
+
+    info!("Composition complete! Output saved to: {:?}", args.output);
+    Ok(())
+}
+
+async fn analyze(args: AnalyzeArgs, config: Config) -> Result<()> {
+    let format = ReportFormat::from_name(&args.format)
+        .ok_or_else(|| anyhow::anyhow!("Unknown format: {}", args.format))?;
+
+    let cache = (!args.no_cache).then(AnalysisCache::default_location);
+    let analysis = AudioAnalyzer::with_config(config.audio.analysis_config())
+        .analyze_file(&args.audio, cache.as_ref())
         .await?;
 
-    info!("Composition complete! Output saved to: {:?}", cli.output);
+    info!("{} beats at {:.1} BPM, {} sections",
+          analysis.beats.len(), analysis.bpm, analysis.phrases.len());
+
+    let report = format.render(&analysis);
+    match &args.output {
+        Some(path) => {
+            std::fs::write(path, report)?;
+            info!("Report written to {:?}", path);
+        }
+        None => print!("{}", report),
+    }
+
+    if let Some(path) = &args.labels {
+        std::fs::write(path, ReportFormat::Labels.render(&analysis))?;
+        info!("Labels written to {:?}", path);
+    }
+
+    if let Some(path) = &args.plot {
+        let audio = AudioLoader::load(&args.audio).await?;
+        diagnostics::save_plot(path, &analysis, Some(&audio), None)?;
+        info!("Plot written to {:?}", path);
+    }
+
     Ok(())
-}
\ No newline at end of file
+}
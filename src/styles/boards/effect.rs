@@ -1,19 +1,193 @@
 use crate::{
     error::Result,
-    styles::{Style, StyleConfig},
+    styles::{FrameContext, Style, StyleConfig},
+    styles::noise::hash_unit,
     styles::traits::StyleMetadata,
     video::types::Frame,
 };
 
+use super::font;
+use super::{LINE_STRENGTH, EDGE_THRESHOLD, HATCH_DENSITY, PAPER_TEXTURE, PANEL_BORDER, SHOW_SHOT_NUMBER};
+
+/// Off-white sketch paper
+const PAPER_COLOR: [f32; 3] = [246.0, 241.0, 228.0];
+
+/// Soft graphite gray used for lines, hatching and the border
+const GRAPHITE_COLOR: [f32; 3] = [58.0, 58.0, 64.0];
+
+/// Salt for the paper grain so it never lines up with the hatching
+const PAPER_SALT: u64 = 11;
+
+/// Salt for the hand-drawn wobble in the hatching
+const HATCH_SALT: u64 = 12;
+
 /// Boards-style video effect implementation
 ///
-/// Creates a high-contrast, bold aesthetic with geometric overlays and vibrant colors
+/// Turns footage into storyboard panels: pencil outlines from edge detection,
+/// hatch shading by luminance on textured paper, framed with a numbered border.
 pub struct BoardsStyle;
 
 impl BoardsStyle {
     pub fn new() -> Self {
         Self
     }
+
+    /// Luminance of every pixel, lightly blurred so sensor noise doesn't become lines
+    fn luminance_map(&self, frame: &Frame) -> Vec<f32> {
+        let width = frame.width() as usize;
+        let height = frame.height() as usize;
+
+        let raw: Vec<f32> = frame
+            .as_image()
+            .pixels()
+            .map(|p| (0.299 * p[0] as f32 + 0.587 * p[1] as f32 + 0.114 * p[2] as f32) / 255.0)
+            .collect();
+
+        let mut blurred = vec![0.0f32; raw.len()];
+        for y in 0..height {
+            for x in 0..width {
+                let mut sum = 0.0;
+                let mut count = 0.0;
+                for ny in y.saturating_sub(1)..=(y + 1).min(height - 1) {
+                    for nx in x.saturating_sub(1)..=(x + 1).min(width - 1) {
+                        sum += raw[ny * width + nx];
+                        count += 1.0;
+                    }
+                }
+                blurred[y * width + x] = sum / count;
+            }
+        }
+
+        blurred
+    }
+
+    /// Sobel gradient magnitude for every pixel
+    fn edge_map(&self, luma: &[f32], width: usize, height: usize) -> Vec<f32> {
+        let at = |x: isize, y: isize| {
+            let x = x.clamp(0, width as isize - 1) as usize;
+            let y = y.clamp(0, height as isize - 1) as usize;
+            luma[y * width + x]
+        };
+
+        let mut edges = vec![0.0f32; luma.len()];
+        for y in 0..height as isize {
+            for x in 0..width as isize {
+                let gx = -at(x - 1, y - 1) - 2.0 * at(x - 1, y) - at(x - 1, y + 1)
+                    + at(x + 1, y - 1) + 2.0 * at(x + 1, y) + at(x + 1, y + 1);
+                let gy = -at(x - 1, y - 1) - 2.0 * at(x, y - 1) - at(x + 1, y - 1)
+                    + at(x - 1, y + 1) + 2.0 * at(x, y + 1) + at(x + 1, y + 1);
+                edges[y as usize * width + x as usize] = (gx * gx + gy * gy).sqrt();
+            }
+        }
+
+        edges
+    }
+
+    /// Pencil darkness (0.0-1.0) contributed by hatching at a pixel
+    ///
+    /// Darker tones add more layers: diagonal strokes, then cross-hatching,
+    /// then dense horizontal strokes in the deepest shadows.
+    fn hatch_darkness(&self, x: u32, y: u32, luma: f32, spacing: u32, density: f32) -> f32 {
+        if density <= 0.0 {
+            return 0.0;
+        }
+
+        // Slight per-row wobble so strokes look hand-drawn
+        let wobble = (hash_unit(HATCH_SALT, y as u64 / 4, 0) * 2.0) as i64;
+        let on_stroke = |offset: i64| (offset + wobble).rem_euclid(spacing as i64) == 0;
+        let (x, y) = (x as i64, y as i64);
+
+        let shade = 1.0 - luma;
+        let mut darkness: f32 = 0.0;
+
+        if shade > 0.3 && on_stroke(x + y) {
+            darkness = darkness.max(0.55);
+        }
+        if shade > 0.55 && on_stroke(x - y) {
+            darkness = darkness.max(0.6);
+        }
+        if shade > 0.8 && on_stroke(y * 2) {
+            darkness = darkness.max(0.7);
+        }
+
+        darkness * density
+    }
+
+    /// Paper color at a pixel with fibrous grain and slow blotches
+    fn paper_color(&self, x: u32, y: u32, texture: f32) -> [f32; 3] {
+        if texture <= 0.0 {
+            return PAPER_COLOR;
+        }
+
+        let fine = hash_unit(PAPER_SALT, x as u64, y as u64) - 0.5;
+
+        // Bilinear value noise on a coarse grid for uneven tooth
+        let cell = 24;
+        let (cx, cy) = ((x / cell) as u64, (y / cell) as u64);
+        let fx = (x % cell) as f32 / cell as f32;
+        let fy = (y % cell) as f32 / cell as f32;
+        let corner = |dx: u64, dy: u64| hash_unit(PAPER_SALT + 1, cx + dx, cy + dy) - 0.5;
+        let top = corner(0, 0) + (corner(1, 0) - corner(0, 0)) * fx;
+        let bottom = corner(0, 1) + (corner(1, 1) - corner(0, 1)) * fx;
+        let coarse = top + (bottom - top) * fy;
+
+        let variation = (fine * 14.0 + coarse * 18.0) * texture;
+        PAPER_COLOR.map(|c| (c + variation).clamp(0.0, 255.0))
+    }
+
+    /// Draw the panel border and shot label on top of the sketch
+    fn draw_panel(&self, frame: &mut Frame, shot_number: Option<u32>) {
+        let width = frame.width();
+        let height = frame.height();
+        let margin = (height / 24).max(2);
+        let thickness = (height / 270).max(1);
+
+        // Too small to frame: the margins would cover the whole sketch
+        if width <= 2 * margin || height <= 2 * margin {
+            return;
+        }
+
+        // Clear the margin to plain paper
+        for y in 0..height {
+            for x in 0..width {
+                let inside = x >= margin && x < width - margin && y >= margin && y < height - margin;
+                if !inside {
+                    frame.set_pixel(x, y, PAPER_COLOR.map(|c| c as u8));
+                }
+            }
+        }
+
+        // Panel outline
+        let graphite = GRAPHITE_COLOR.map(|c| c as u8);
+        for t in 0..thickness {
+            let (left, right) = (margin.saturating_sub(t + 1), (width - margin + t).min(width - 1));
+            let (top, bottom) = (margin.saturating_sub(t + 1), (height - margin + t).min(height - 1));
+            for x in left..=right {
+                frame.set_pixel(x, top, graphite);
+                frame.set_pixel(x, bottom, graphite);
+            }
+            for y in top..=bottom {
+                frame.set_pixel(left, y, graphite);
+                frame.set_pixel(right, y, graphite);
+            }
+        }
+
+        // Shot label sits in the top margin
+        if let Some(shot) = shot_number {
+            let scale = (margin / (font::GLYPH_HEIGHT + 2)).max(1);
+            let label = format!("SHOT {:02}", shot);
+            let label_y = (margin.saturating_sub(font::GLYPH_HEIGHT * scale)) / 2;
+            if font::text_width(&label, scale) + margin < width {
+                font::draw_text(frame, margin, label_y, scale, &label, graphite);
+            }
+        }
+    }
+}
+
+impl Default for BoardsStyle {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl Style for BoardsStyle {
@@ -22,32 +196,244 @@ impl Style for BoardsStyle {
     }
 
     fn description(&self) -> &str {
-        "High contrast, bold colors with geometric overlays and modern aesthetic"
+        "Storyboard panels with pencil outlines, hatch shading, paper texture and numbered borders"
     }
 
-    fn apply_effect(&self, _frame: &mut Frame, _config: &StyleConfig) -> Result<()> {
-        // TODO: Implement boards effects
-        // - High contrast adjustment
-        // - Color saturation boost
-        // - Geometric overlays
-        // - Sharp edges enhancement
-        // - Modern color grading
+    fn apply_effect(&self, frame: &mut Frame, config: &StyleConfig, context: &FrameContext) -> Result<()> {
+        let intensity = config.intensity.clamp(0.0, 1.0);
+        if intensity <= 0.0 {
+            return Ok(());
+        }
+
+        let line_strength = config.get_f32_or(LINE_STRENGTH, 0.9);
+        let edge_threshold = config.get_f32_or(EDGE_THRESHOLD, 0.25);
+        let mut hatch_density = config.get_f32_or(HATCH_DENSITY, 0.7);
+        let mut paper_texture = config.get_f32_or(PAPER_TEXTURE, 0.5);
+
+        // Drafts keep the pencil outlines but draw on plain, unhatched paper
+        if context.draft {
+            hatch_density = 0.0;
+            paper_texture = 0.0;
+        }
+        let panel_border = config.get_bool_or(PANEL_BORDER, true);
+        let show_shot_number = config.get_bool_or(SHOW_SHOT_NUMBER, true);
+
+        let width = frame.width();
+        let height = frame.height();
+        let luma = self.luminance_map(frame);
+        let edges = self.edge_map(&luma, width as usize, height as usize);
+
+        // Hatch spacing follows resolution so 720p and 4K read the same
+        let spacing = (height / 120).max(3);
+
+        for y in 0..height {
+            for x in 0..width {
+                let index = (y * width + x) as usize;
+
+                // Pencil outline from edge strength
+                let edge = edges[index];
+                let line = smoothstep(edge_threshold, edge_threshold * 2.5, edge) * line_strength;
+
+                // Quantize tone into a few pencil-friendly levels before hatching
+                let tone = (luma[index] * 4.0).round() / 4.0;
+                let hatch = self.hatch_darkness(x, y, tone, spacing, hatch_density);
+
+                let darkness = line.max(hatch).clamp(0.0, 1.0);
+                let paper = self.paper_color(x, y, paper_texture);
+
+                let pixel = frame.get_pixel_mut(x, y);
+                for channel in 0..3 {
+                    let sketch = paper[channel] + (GRAPHITE_COLOR[channel] - paper[channel]) * darkness;
+                    let original = pixel[channel] as f32;
+                    pixel[channel] = (original + (sketch - original) * intensity).clamp(0.0, 255.0) as u8;
+                }
+            }
+        }
+
+        if panel_border {
+            let shot_number = context.clip_id.filter(|_| show_shot_number);
+            self.draw_panel(frame, shot_number);
+        }
+
         Ok(())
     }
 
     fn metadata(&self) -> StyleMetadata {
         StyleMetadata {
             gpu_accelerated: false,
-            performance_impact: 0.3,
+            performance_impact: 0.5,
             composable: true,
             required_parameters: vec![],
             optional_parameters: vec![
-                ("contrast_boost".to_string(), "Contrast enhancement level (0.0-1.0)".to_string()),
-                ("saturation_boost".to_string(), "Color saturation boost (0.0-1.0)".to_string()),
-                ("geometric_overlay".to_string(), "Geometric overlay intensity (0.0-1.0)".to_string()),
-                ("edge_enhancement".to_string(), "Edge sharpening strength (0.0-1.0)".to_string()),
-                ("modern_grading".to_string(), "Modern color grading intensity (0.0-1.0)".to_string()),
+                (LINE_STRENGTH.to_string(), "Darkness of pencil outlines (0.0-1.0)".to_string()),
+                (EDGE_THRESHOLD.to_string(), "Edge strength needed to draw a line (0.0-1.0)".to_string()),
+                (HATCH_DENSITY.to_string(), "Strength of luminance hatch shading (0.0-1.0)".to_string()),
+                (PAPER_TEXTURE.to_string(), "Amount of paper grain (0.0-1.0)".to_string()),
+                (PANEL_BORDER.to_string(), "Draw a storyboard panel border (true/false)".to_string()),
+                (SHOW_SHOT_NUMBER.to_string(), "Label the panel with the clip's shot number (true/false)".to_string()),
             ],
         }
     }
-}
\ No newline at end of file
+}
+
+fn smoothstep(edge0: f32, edge1: f32, x: f32) -> f32 {
+    let t = ((x - edge0) / (edge1 - edge0)).clamp(0.0, 1.0);
+    t * t * (3.0 - 2.0 * t)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sketch_config() -> StyleConfig {
+        StyleConfig::with_intensity(1.0)
+            .set(PAPER_TEXTURE, 0.0)
+            .set(PANEL_BORDER, false)
+    }
+
+    fn is_dark(pixel: [u8; 3]) -> bool {
+        pixel.iter().all(|&c| c < 150)
+    }
+
+    #[test]
+    fn test_flat_bright_frame_becomes_blank_paper() {
+        let style = BoardsStyle::new();
+        let mut frame = Frame::new_filled(64, 48, [250, 250, 250]);
+
+        style.apply_effect(&mut frame, &sketch_config(), &FrameContext::default()).unwrap();
+
+        let paper = PAPER_COLOR.map(|c| c as u8);
+        assert!(frame.as_image().pixels().all(|p| p.0 == paper));
+    }
+
+    #[test]
+    fn test_edges_become_pencil_lines() {
+        let style = BoardsStyle::new();
+        let mut frame = Frame::new_filled(64, 48, [250, 250, 250]);
+        for y in 0..48 {
+            for x in 32..64 {
+                frame.set_pixel(x, y, [235, 235, 235]);
+            }
+        }
+        // A strong vertical boundary in the middle
+        for y in 0..48 {
+            frame.set_pixel(31, y, [0, 0, 0]);
+        }
+
+        style.apply_effect(&mut frame, &sketch_config().set(HATCH_DENSITY, 0.0), &FrameContext::default()).unwrap();
+
+        assert!(is_dark(frame.get_pixel(30, 24)) || is_dark(frame.get_pixel(32, 24)));
+        assert!(!is_dark(frame.get_pixel(10, 24)));
+        assert!(!is_dark(frame.get_pixel(50, 24)));
+    }
+
+    #[test]
+    fn test_darker_tones_get_more_hatching() {
+        let style = BoardsStyle::new();
+        let config = sketch_config().set(LINE_STRENGTH, 0.0);
+
+        let paper = PAPER_COLOR.map(|c| c as u8);
+        let count_marked = |color: [u8; 3]| {
+            let mut frame = Frame::new_filled(120, 120, color);
+            style.apply_effect(&mut frame, &config, &FrameContext::default()).unwrap();
+            frame.as_image().pixels().filter(|p| p.0 != paper).count()
+        };
+
+        let light = count_marked([230, 230, 230]);
+        let mid = count_marked([120, 120, 120]);
+        let dark = count_marked([20, 20, 20]);
+
+        assert_eq!(light, 0);
+        assert!(mid > 0);
+        assert!(dark > mid, "dark {} should be denser than mid {}", dark, mid);
+    }
+
+    #[test]
+    fn test_panel_border_and_shot_number() {
+        let style = BoardsStyle::new();
+        let config = StyleConfig::with_intensity(1.0).set(PAPER_TEXTURE, 0.0);
+        let shot = FrameContext { clip_id: Some(7), ..FrameContext::default() };
+
+        let mut with_number = Frame::new_filled(320, 240, [250, 250, 250]);
+        style.apply_effect(&mut with_number, &config, &shot).unwrap();
+
+        let mut without_number = Frame::new_filled(320, 240, [250, 250, 250]);
+        style.apply_effect(&mut without_number, &config.clone().set(SHOW_SHOT_NUMBER, false), &shot).unwrap();
+
+        // Border line sits just inside the margin
+        let margin = 240 / 24;
+        assert!(is_dark(with_number.get_pixel(160, margin - 1)));
+        assert!(is_dark(with_number.get_pixel(margin - 1, 120)));
+
+        // The label only differs in the top margin
+        let differing: Vec<(u32, u32)> = (0..240)
+            .flat_map(|y| (0..320).map(move |x| (x, y)))
+            .filter(|&(x, y)| with_number.get_pixel(x, y) != without_number.get_pixel(x, y))
+            .collect();
+        assert!(!differing.is_empty());
+        assert!(differing.iter().all(|&(_, y)| y < margin));
+    }
+
+    #[test]
+    fn test_tiny_frames_skip_the_panel() {
+        let style = BoardsStyle::new();
+        let shot = FrameContext { clip_id: Some(3), ..FrameContext::default() };
+
+        for (width, height) in [(1, 1), (3, 48), (64, 4)] {
+            let mut frame = Frame::new_filled(width, height, [250, 250, 250]);
+            style.apply_effect(&mut frame, &StyleConfig::with_intensity(1.0), &shot).unwrap();
+        }
+    }
+
+    #[test]
+    fn test_draft_draws_outlines_on_plain_paper() {
+        let style = BoardsStyle::new();
+        let draft = FrameContext { draft: true, ..FrameContext::default() };
+
+        // Dark square on mid gray: hatched tones and an outline in a full render
+        let mut source = Frame::new_filled(96, 72, [120, 120, 120]);
+        for y in 24..48 {
+            for x in 32..64 {
+                source.set_pixel(x, y, [20, 20, 20]);
+            }
+        }
+        let config = StyleConfig::with_intensity(1.0).set(PANEL_BORDER, false);
+        let interior = |frame: &Frame| -> Vec<[u8; 3]> {
+            (30..42).flat_map(|y| (38..58).map(move |x| (x, y))).map(|(x, y)| frame.get_pixel(x, y)).collect()
+        };
+
+        let mut full = source.clone();
+        style.apply_effect(&mut full, &config, &FrameContext::default()).unwrap();
+        let mut drafted = source;
+        style.apply_effect(&mut drafted, &config, &draft).unwrap();
+
+        let paper = PAPER_COLOR.map(|c| c as u8);
+        assert!(interior(&full).iter().any(|&p| p != paper));
+        assert!(interior(&drafted).iter().all(|&p| p == paper));
+        assert!((30..42).any(|y| is_dark(drafted.get_pixel(32, y)) || is_dark(drafted.get_pixel(31, y))));
+    }
+
+    #[test]
+    fn test_zero_intensity_leaves_frame_untouched() {
+        let style = BoardsStyle::new();
+        let mut frame = Frame::new_filled(32, 24, [10, 120, 200]);
+        let original = frame.clone();
+
+        style.apply_effect(&mut frame, &StyleConfig::with_intensity(0.0), &FrameContext::default()).unwrap();
+
+        assert_eq!(frame.to_rgb_bytes(), original.to_rgb_bytes());
+    }
+
+    #[test]
+    fn test_metadata_lists_parameters() {
+        let params: Vec<String> = BoardsStyle::new()
+            .metadata()
+            .optional_parameters
+            .into_iter()
+            .map(|(name, _)| name)
+            .collect();
+
+        assert!(params.contains(&PANEL_BORDER.to_string()));
+        assert!(params.contains(&HATCH_DENSITY.to_string()));
+    }
+}
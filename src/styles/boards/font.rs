@@ -0,0 +1,69 @@
+//! Tiny 5x7 bitmap font for burning labels into frames
+
+use crate::video::types::Frame;
+
+/// Width of a glyph in font pixels
+pub const GLYPH_WIDTH: u32 = 5;
+
+/// Height of a glyph in font pixels
+pub const GLYPH_HEIGHT: u32 = 7;
+
+/// Look up the 5x7 bitmap for a character (one byte per row, low 5 bits used)
+pub fn glyph(ch: char) -> Option<[u8; 7]> {
+    let rows = match ch.to_ascii_uppercase() {
+        '0' => [0b01110, 0b10001, 0b10011, 0b10101, 0b11001, 0b10001, 0b01110],
+        '1' => [0b00100, 0b01100, 0b00100, 0b00100, 0b00100, 0b00100, 0b01110],
+        '2' => [0b01110, 0b10001, 0b00001, 0b00010, 0b00100, 0b01000, 0b11111],
+        '3' => [0b11111, 0b00010, 0b00100, 0b00010, 0b00001, 0b10001, 0b01110],
+        '4' => [0b00010, 0b00110, 0b01010, 0b10010, 0b11111, 0b00010, 0b00010],
+        '5' => [0b11111, 0b10000, 0b11110, 0b00001, 0b00001, 0b10001, 0b01110],
+        '6' => [0b00110, 0b01000, 0b10000, 0b11110, 0b10001, 0b10001, 0b01110],
+        '7' => [0b11111, 0b00001, 0b00010, 0b00100, 0b01000, 0b01000, 0b01000],
+        '8' => [0b01110, 0b10001, 0b10001, 0b01110, 0b10001, 0b10001, 0b01110],
+        '9' => [0b01110, 0b10001, 0b10001, 0b01111, 0b00001, 0b00010, 0b01100],
+        'H' => [0b10001, 0b10001, 0b10001, 0b11111, 0b10001, 0b10001, 0b10001],
+        'O' => [0b01110, 0b10001, 0b10001, 0b10001, 0b10001, 0b10001, 0b01110],
+        'S' => [0b01111, 0b10000, 0b10000, 0b01110, 0b00001, 0b00001, 0b11110],
+        'T' => [0b11111, 0b00100, 0b00100, 0b00100, 0b00100, 0b00100, 0b00100],
+        ' ' => [0; 7],
+        _ => return None,
+    };
+    Some(rows)
+}
+
+/// Width in pixels of `text` rendered at the given scale
+pub fn text_width(text: &str, scale: u32) -> u32 {
+    let count = text.chars().count() as u32;
+    if count == 0 {
+        return 0;
+    }
+    (count * (GLYPH_WIDTH + 1) - 1) * scale
+}
+
+/// Draw `text` with its top-left corner at (x, y); unknown characters are skipped
+pub fn draw_text(frame: &mut Frame, x: u32, y: u32, scale: u32, text: &str, color: [u8; 3]) {
+    let scale = scale.max(1);
+
+    for (index, ch) in text.chars().enumerate() {
+        let Some(rows) = glyph(ch) else { continue };
+        let origin_x = x + index as u32 * (GLYPH_WIDTH + 1) * scale;
+
+        for (row, bits) in rows.iter().enumerate() {
+            for col in 0..GLYPH_WIDTH {
+                if bits & (1 << (GLYPH_WIDTH - 1 - col)) == 0 {
+                    continue;
+                }
+
+                for sy in 0..scale {
+                    for sx in 0..scale {
+                        let px = origin_x + col * scale + sx;
+                        let py = y + row as u32 * scale + sy;
+                        if px < frame.width() && py < frame.height() {
+                            frame.set_pixel(px, py, color);
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
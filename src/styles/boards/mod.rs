@@ -1,3 +1,18 @@
+//! # Boards Style Implementation
+//!
+//! Renders footage as storyboard panels: pencil lines from edge detection,
+//! hatch shading driven by luminance, paper texture and an optional panel
+//! border labelled with the shot number.
+
 mod effect;
+pub(crate) mod font;
+
+pub use effect::BoardsStyle;
 
-pub use effect::BoardsStyle;
\ No newline at end of file
+// Boards-specific parameter constants
+pub const LINE_STRENGTH: &str = "line_strength";
+pub const EDGE_THRESHOLD: &str = "edge_threshold";
+pub const HATCH_DENSITY: &str = "hatch_density";
+pub const PAPER_TEXTURE: &str = "paper_texture";
+pub const PANEL_BORDER: &str = "panel_border";
+pub const SHOW_SHOT_NUMBER: &str = "show_shot_number";
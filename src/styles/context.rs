@@ -0,0 +1,188 @@
+//! Per-frame context for styles
+//!
+//! Styles see more than a single frame: where it sits in the render, where it
+//! falls against the beat, how loud the music is and which section is playing.
+//! Any style can pulse on beats or calm down in quiet passages using this
+//! context, without the processor knowing anything about the style.
+
+use crate::audio::types::{AudioAnalysis, PhraseType};
+
+/// Where a frame sits in the render and in the music
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct FrameContext {
+    /// Output time of the frame in seconds
+    pub time: f64,
+
+    /// Index of the frame in the output video
+    pub frame_index: u64,
+
+    /// Index of the timeline segment the frame belongs to
+    pub segment_index: usize,
+
+    /// Position within the segment, from 0.0 at its first frame towards 1.0
+    pub segment_progress: f32,
+
+    /// Sequence number of the clip shown, if the frame comes from one
+    pub clip_id: Option<u32>,
+
+    /// Position between the previous and the next beat, 0.0 on the beat
+    pub beat_phase: f32,
+
+    /// Seconds since the previous beat; infinite before the first beat
+    pub time_since_beat: f64,
+
+    /// RMS energy at the frame relative to the loudest passage (0.0-1.0)
+    pub energy: f32,
+
+    /// Section of the song playing at the frame
+    pub phrase_type: PhraseType,
+
+    /// Render seed (`Config::seed`); styles draw all their noise from it
+    pub seed: u64,
+
+    /// Draft render (previews); styles may skip or cheapen their costliest passes
+    pub draft: bool,
+}
+
+impl Default for FrameContext {
+    fn default() -> Self {
+        Self {
+            time: 0.0,
+            frame_index: 0,
+            segment_index: 0,
+            segment_progress: 0.0,
+            clip_id: None,
+            beat_phase: 0.0,
+            time_since_beat: f64::INFINITY,
+            energy: 0.0,
+            phrase_type: PhraseType::Unknown,
+            seed: 0,
+            draft: false,
+        }
+    }
+}
+
+impl FrameContext {
+    /// Context for a standalone frame at the given index, with no music
+    pub fn for_frame(frame_index: u64) -> Self {
+        Self { frame_index, ..Self::default() }
+    }
+
+    /// Whether the frame falls within `window` seconds after a beat
+    pub fn on_beat(&self, window: f64) -> bool {
+        self.time_since_beat < window
+    }
+}
+
+/// Musical timeline of a song, for looking up the context of each frame
+///
+/// Holds only beat times, the energy curve and the sections, so it is cheap
+/// to keep around for a whole render.
+#[derive(Debug, Clone, Default)]
+pub struct MusicContext {
+    beats: Vec<f64>,
+    energy: Vec<(f64, f32)>,
+    peak_rms: f32,
+    phrases: Vec<(f64, f64, PhraseType)>,
+}
+
+impl MusicContext {
+    /// Extract the musical timeline from an analysis
+    pub fn from_analysis(analysis: &AudioAnalysis) -> Self {
+        let mut beats: Vec<f64> = analysis.beats.iter().map(|beat| beat.time).collect();
+        beats.sort_by(|a, b| a.total_cmp(b));
+
+        Self {
+            beats,
+            energy: analysis.energy_levels.iter().map(|level| (level.time, level.rms)).collect(),
+            peak_rms: analysis.energy_levels.iter().map(|level| level.rms).fold(0.0, f32::max),
+            phrases: analysis
+                .phrases
+                .iter()
+                .map(|phrase| (phrase.start, phrase.end, phrase.phrase_type))
+                .collect(),
+        }
+    }
+
+    /// Fill in the musical fields of `context` from its `time`
+    pub fn apply(&self, mut context: FrameContext) -> FrameContext {
+        let time = context.time;
+
+        // Beats at or before the frame
+        let passed = self.beats.partition_point(|&beat| beat <= time);
+        if passed > 0 {
+            let previous = self.beats[passed - 1];
+            context.time_since_beat = time - previous;
+            context.beat_phase = match self.beats.get(passed) {
+                Some(&next) if next > previous => ((time - previous) / (next - previous)) as f32,
+                _ => 0.0,
+            };
+        }
+
+        if self.peak_rms > 0.0 {
+            let index = self.energy.partition_point(|&(at, _)| at <= time);
+            if let Some(&(_, rms)) = self.energy.get(index.saturating_sub(1)) {
+                context.energy = (rms / self.peak_rms).clamp(0.0, 1.0);
+            }
+        }
+
+        context.phrase_type = self
+            .phrases
+            .iter()
+            .find(|&&(start, end, _)| time >= start && time < end)
+            .map_or(PhraseType::Unknown, |&(_, _, phrase_type)| phrase_type);
+
+        context
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::audio::types::{AnalysisBuilder, BeatType};
+
+    fn analysis() -> AudioAnalysis {
+        let builder = AnalysisBuilder::new(4.0)
+            .phrase(0.0, 2.0, PhraseType::Verse)
+            .phrase(2.0, 4.0, PhraseType::Chorus);
+        let builder = (1..8).fold(builder, |builder, i| builder.beat(i as f64 * 0.5, BeatType::Beat));
+        (0..40)
+            .fold(builder, |builder, i| builder.energy(i as f64 * 0.1, if i < 20 { 0.2 } else { 0.8 }))
+            .build()
+    }
+
+    #[test]
+    fn test_music_context_at_frame_time() {
+        let music = MusicContext::from_analysis(&analysis());
+        let at = |time: f64| music.apply(FrameContext { time, ..FrameContext::default() });
+
+        // Before the first beat
+        let intro = at(0.25);
+        assert_eq!(intro.time_since_beat, f64::INFINITY);
+        assert_eq!(intro.beat_phase, 0.0);
+        assert_eq!(intro.energy, 0.25);
+        assert_eq!(intro.phrase_type, PhraseType::Verse);
+
+        // A quarter of the way from the beat at 2.5s to the one at 3.0s
+        let chorus = at(2.625);
+        assert_eq!(chorus.time_since_beat, 0.125);
+        assert_eq!(chorus.beat_phase, 0.25);
+        assert_eq!(chorus.energy, 1.0);
+        assert_eq!(chorus.phrase_type, PhraseType::Chorus);
+        assert!(chorus.on_beat(0.2));
+        assert!(!chorus.on_beat(0.1));
+
+        // No music at all leaves the defaults
+        assert_eq!(MusicContext::default().apply(FrameContext::for_frame(3)), FrameContext::for_frame(3));
+    }
+
+    #[test]
+    fn test_music_context_survives_nan_beat() {
+        let mut analysis = analysis();
+        analysis.beats[3].time = f64::NAN;
+
+        let music = MusicContext::from_analysis(&analysis);
+        let frame = music.apply(FrameContext { time: 1.25, ..FrameContext::default() });
+        assert_eq!(frame.time_since_beat, 0.25);
+    }
+}
@@ -1,10 +1,56 @@
+use rand::{rngs::SmallRng, Rng, SeedableRng};
+
 use crate::{
     error::Result,
-    styles::{Style, StyleConfig},
+    styles::{FrameContext, Style, StyleConfig},
+    styles::noise::hash,
     styles::traits::StyleMetadata,
     video::types::Frame,
 };
 
+use super::{GRAIN_INTENSITY, SCRATCH_FREQUENCY, COLOR_FADE, LIGHT_LEAKS, VIGNETTE_STRENGTH};
+
+/// Number of frames a scratch stays on screen before a new one replaces it
+const SCRATCH_LIFETIME: u64 = 18;
+
+/// Number of frames a dust speck stays on screen
+const DUST_LIFETIME: u64 = 4;
+
+/// Maximum number of simultaneous scratches at full frequency
+const MAX_SCRATCHES: u64 = 4;
+
+/// Maximum number of simultaneous dust specks at full frequency
+const MAX_DUST: u64 = 24;
+
+// Salts that keep the random streams for each artifact independent
+const GRAIN_SALT: u64 = 1;
+const SCRATCH_SALT: u64 = 2;
+const DUST_SALT: u64 = 3;
+
+/// A vertical scratch running down part of the frame
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Scratch {
+    /// Horizontal position in pixels
+    x: f32,
+    /// First row covered by the scratch
+    y_start: u32,
+    /// Last row covered by the scratch
+    y_end: u32,
+    /// Whether the scratch is bright (emulsion removed) or dark (dirt in the gate)
+    bright: bool,
+    /// Opacity of the scratch (0.0-1.0)
+    opacity: f32,
+}
+
+/// A small round dust speck
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct DustSpeck {
+    x: u32,
+    y: u32,
+    radius: u32,
+    bright: bool,
+}
+
 /// Film-style video effect implementation
 ///
 /// Recreates the look of aged film with grain, scratches, color fading, and light leaks
@@ -14,6 +60,260 @@ impl FilmStyle {
     pub fn new() -> Self {
         Self
     }
+
+    /// Fade colors towards a washed-out, slightly yellowed print
+    fn apply_color_fade(&self, frame: &mut Frame, amount: f32) {
+        if amount <= 0.0 {
+            return;
+        }
+
+        let desaturation = amount * 0.6;
+        let black_lift = amount * 30.0;
+        let white_crush = 1.0 - amount * 0.12;
+
+        for y in 0..frame.height() {
+            for x in 0..frame.width() {
+                let pixel = frame.get_pixel_mut(x, y);
+                let r = pixel[0] as f32;
+                let g = pixel[1] as f32;
+                let b = pixel[2] as f32;
+                let luma = 0.299 * r + 0.587 * g + 0.114 * b;
+
+                // Pull colors towards luminance, then compress the range
+                let fade = |c: f32| (c + (luma - c) * desaturation) * white_crush + black_lift;
+
+                // Dye layers fade unevenly - cyan goes first, leaving a warm cast
+                pixel[0] = (fade(r) * (1.0 + amount * 0.06)).clamp(0.0, 255.0) as u8;
+                pixel[1] = (fade(g) * (1.0 + amount * 0.02)).clamp(0.0, 255.0) as u8;
+                pixel[2] = (fade(b) * (1.0 - amount * 0.10)).clamp(0.0, 255.0) as u8;
+            }
+        }
+    }
+
+    /// Add monochrome film grain, strongest in the midtones
+    fn apply_grain(&self, frame: &mut Frame, intensity: f32, seed: u64, frame_index: u64) {
+        if intensity <= 0.0 {
+            return;
+        }
+
+        let mut rng = SmallRng::seed_from_u64(hash(hash(GRAIN_SALT, seed, 0), frame_index, 0));
+        let amplitude = intensity * 40.0;
+
+        for y in 0..frame.height() {
+            for x in 0..frame.width() {
+                let pixel = frame.get_pixel_mut(x, y);
+                let luma = (0.299 * pixel[0] as f32 + 0.587 * pixel[1] as f32 + 0.114 * pixel[2] as f32) / 255.0;
+
+                // Grain is most visible in the midtones and fades in highlights/shadows
+                let midtone_weight = 1.0 - (luma - 0.5).abs() * 1.2;
+
+                // Sum of two uniforms gives a softer, triangular distribution
+                let noise = (rng.gen::<f32>() + rng.gen::<f32>() - 1.0) * amplitude * midtone_weight;
+
+                for channel in pixel.iter_mut().take(3) {
+                    *channel = (*channel as f32 + noise).clamp(0.0, 255.0) as u8;
+                }
+            }
+        }
+    }
+
+    /// Compute the scratches visible on a given frame
+    ///
+    /// Each scratch slot picks a new scratch every `SCRATCH_LIFETIME` frames, so
+    /// consecutive frames share the same scratches with only a slight drift.
+    fn scratches_for_frame(&self, width: u32, height: u32, frequency: f32, seed: u64, frame_index: u64) -> Vec<Scratch> {
+        let slots = (frequency * MAX_SCRATCHES as f32).ceil() as u64;
+        let salt = hash(SCRATCH_SALT, seed, 0);
+        let mut scratches = Vec::new();
+
+        for slot in 0..slots {
+            // Stagger slots so they don't all change on the same frame
+            let offset = hash(salt, slot, 0) % SCRATCH_LIFETIME;
+            let epoch = (frame_index + offset) / SCRATCH_LIFETIME;
+            let age = (frame_index + offset) % SCRATCH_LIFETIME;
+
+            let mut rng = SmallRng::seed_from_u64(hash(salt, slot, epoch));
+
+            if rng.gen::<f32>() > frequency {
+                continue;
+            }
+
+            let base_x = rng.gen_range(0.0..width as f32);
+            let drift = rng.gen_range(-0.3..0.3);
+            let y_start = rng.gen_range(0..=height / 3);
+            let y_end = rng.gen_range((height * 2 / 3)..height.max(1));
+
+            scratches.push(Scratch {
+                x: (base_x + drift * age as f32).clamp(0.0, (width - 1) as f32),
+                y_start,
+                y_end,
+                bright: rng.gen::<f32>() < 0.6,
+                opacity: rng.gen_range(0.35..0.8),
+            });
+        }
+
+        scratches
+    }
+
+    /// Compute the dust specks visible on a given frame
+    fn dust_for_frame(&self, width: u32, height: u32, frequency: f32, seed: u64, frame_index: u64) -> Vec<DustSpeck> {
+        let slots = (frequency * MAX_DUST as f32).round() as u64;
+        let salt = hash(DUST_SALT, seed, 0);
+        let mut specks = Vec::new();
+
+        for slot in 0..slots {
+            let offset = hash(salt, slot, 0) % DUST_LIFETIME;
+            let epoch = (frame_index + offset) / DUST_LIFETIME;
+
+            let mut rng = SmallRng::seed_from_u64(hash(salt, slot, epoch));
+
+            // Dust comes and goes - only some slots are occupied at any time
+            if rng.gen::<f32>() > 0.5 {
+                continue;
+            }
+
+            specks.push(DustSpeck {
+                x: rng.gen_range(0..width),
+                y: rng.gen_range(0..height),
+                radius: rng.gen_range(1..=3),
+                bright: rng.gen::<f32>() < 0.3,
+            });
+        }
+
+        specks
+    }
+
+    /// Draw scratches and dust onto the frame
+    fn apply_scratches(&self, frame: &mut Frame, frequency: f32, seed: u64, frame_index: u64) {
+        if frequency <= 0.0 {
+            return;
+        }
+
+        let width = frame.width();
+        let height = frame.height();
+
+        for scratch in self.scratches_for_frame(width, height, frequency, seed, frame_index) {
+            let x = scratch.x.round() as u32;
+            let target = if scratch.bright { 235.0 } else { 20.0 };
+
+            for y in scratch.y_start..=scratch.y_end.min(height - 1) {
+                // Scratches have a soft one-pixel halo either side
+                for (dx, weight) in [(-1i32, 0.35f32), (0, 1.0), (1, 0.35)] {
+                    let px = x as i32 + dx;
+                    if px < 0 || px >= width as i32 {
+                        continue;
+                    }
+
+                    let alpha = scratch.opacity * weight;
+                    let pixel = frame.get_pixel_mut(px as u32, y);
+                    for channel in pixel.iter_mut().take(3) {
+                        *channel = (*channel as f32 * (1.0 - alpha) + target * alpha) as u8;
+                    }
+                }
+            }
+        }
+
+        for speck in self.dust_for_frame(width, height, frequency, seed, frame_index) {
+            let target = if speck.bright { 220.0 } else { 15.0 };
+            let r = speck.radius as i32;
+
+            for dy in -r..=r {
+                for dx in -r..=r {
+                    if dx * dx + dy * dy > r * r {
+                        continue;
+                    }
+
+                    let px = speck.x as i32 + dx;
+                    let py = speck.y as i32 + dy;
+                    if px < 0 || py < 0 || px >= width as i32 || py >= height as i32 {
+                        continue;
+                    }
+
+                    let pixel = frame.get_pixel_mut(px as u32, py as u32);
+                    for channel in pixel.iter_mut().take(3) {
+                        *channel = (*channel as f32 * 0.2 + target * 0.8) as u8;
+                    }
+                }
+            }
+        }
+    }
+
+    /// Add a warm light leak bleeding in from the frame edge
+    fn apply_light_leaks(&self, frame: &mut Frame, intensity: f32, frame_index: u64) {
+        if intensity <= 0.0 {
+            return;
+        }
+
+        let width = frame.width() as f32;
+        let height = frame.height() as f32;
+
+        // The leak slowly wanders along the right edge
+        let phase = frame_index as f32 * 0.02;
+        let center_x = width * (0.95 + phase.sin() * 0.05);
+        let center_y = height * (0.3 + (phase * 0.7).cos() * 0.2);
+        let radius = width.max(height) * 0.6;
+
+        // Flicker slightly from frame to frame
+        let flicker = 0.85 + 0.15 * (frame_index as f32 * 0.9).sin().abs();
+        let strength = intensity * flicker;
+
+        let leak_color = [255.0, 120.0, 40.0];
+
+        for y in 0..frame.height() {
+            for x in 0..frame.width() {
+                let dx = x as f32 - center_x;
+                let dy = y as f32 - center_y;
+                let distance = (dx * dx + dy * dy).sqrt() / radius;
+
+                if distance >= 1.0 {
+                    continue;
+                }
+
+                let falloff = (1.0 - distance).powi(2) * strength;
+                let pixel = frame.get_pixel_mut(x, y);
+
+                // Screen blend keeps highlights from clipping harshly
+                for (channel, &leak) in pixel.iter_mut().take(3).zip(leak_color.iter()) {
+                    let base = *channel as f32 / 255.0;
+                    let overlay = leak / 255.0 * falloff;
+                    *channel = ((1.0 - (1.0 - base) * (1.0 - overlay)) * 255.0) as u8;
+                }
+            }
+        }
+    }
+
+    /// Darken the corners like an old projector lens
+    fn apply_vignette(&self, frame: &mut Frame, strength: f32) {
+        if strength <= 0.0 {
+            return;
+        }
+
+        let center_x = frame.width() as f32 / 2.0;
+        let center_y = frame.height() as f32 / 2.0;
+        let max_distance = (center_x * center_x + center_y * center_y).sqrt();
+
+        for y in 0..frame.height() {
+            for x in 0..frame.width() {
+                let dx = x as f32 - center_x;
+                let dy = y as f32 - center_y;
+                let distance = (dx * dx + dy * dy).sqrt() / max_distance;
+
+                let edge = smoothstep(0.35, 1.0, distance);
+                let factor = 1.0 - strength * 0.75 * edge;
+
+                let pixel = frame.get_pixel_mut(x, y);
+                for channel in pixel.iter_mut().take(3) {
+                    *channel = (*channel as f32 * factor) as u8;
+                }
+            }
+        }
+    }
+}
+
+impl Default for FilmStyle {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl Style for FilmStyle {
@@ -25,13 +325,27 @@ impl Style for FilmStyle {
         "Aged film aesthetic with grain, scratches, color fading, and light leaks"
     }
 
-    fn apply_effect(&self, _frame: &mut Frame, _config: &StyleConfig) -> Result<()> {
-        // TODO: Implement film effects
-        // - Film grain
-        // - Scratches and dust
-        // - Color fading/sepia
-        // - Light leaks
-        // - Vignetting
+    fn apply_effect(&self, frame: &mut Frame, config: &StyleConfig, context: &FrameContext) -> Result<()> {
+        let intensity = config.intensity;
+
+        let grain_intensity = config.get_f32_or(GRAIN_INTENSITY, 0.5);
+        let scratch_frequency = config.get_f32_or(SCRATCH_FREQUENCY, 0.3);
+        let color_fade = config.get_f32_or(COLOR_FADE, 0.4);
+        let light_leaks = config.get_f32_or(LIGHT_LEAKS, 0.2);
+        let vignette_strength = config.get_f32_or(VIGNETTE_STRENGTH, 0.5);
+        let seed = context.seed;
+        let frame_index = context.frame_index;
+
+        // Grade first, then damage the print, then the optical effects on top
+        self.apply_color_fade(frame, color_fade * intensity);
+        self.apply_light_leaks(frame, light_leaks * intensity, frame_index);
+        self.apply_scratches(frame, (scratch_frequency * intensity).min(1.0), seed, frame_index);
+        // Per-pixel grain is the costliest pass and barely shows at preview sizes
+        if !context.draft {
+            self.apply_grain(frame, grain_intensity * intensity, seed, frame_index);
+        }
+        self.apply_vignette(frame, vignette_strength * intensity);
+
         Ok(())
     }
 
@@ -42,12 +356,175 @@ impl Style for FilmStyle {
             composable: true,
             required_parameters: vec![],
             optional_parameters: vec![
-                ("grain_intensity".to_string(), "Amount of film grain (0.0-1.0)".to_string()),
-                ("scratch_frequency".to_string(), "Frequency of scratches (0.0-1.0)".to_string()),
-                ("color_fade".to_string(), "Amount of color fading (0.0-1.0)".to_string()),
-                ("light_leaks".to_string(), "Intensity of light leaks (0.0-1.0)".to_string()),
-                ("vignette_strength".to_string(), "Vignette effect strength (0.0-1.0)".to_string()),
+                (GRAIN_INTENSITY.to_string(), "Amount of film grain (0.0-1.0)".to_string()),
+                (SCRATCH_FREQUENCY.to_string(), "Frequency of scratches (0.0-1.0)".to_string()),
+                (COLOR_FADE.to_string(), "Amount of color fading (0.0-1.0)".to_string()),
+                (LIGHT_LEAKS.to_string(), "Intensity of light leaks (0.0-1.0)".to_string()),
+                (VIGNETTE_STRENGTH.to_string(), "Vignette effect strength (0.0-1.0)".to_string()),
             ],
         }
     }
-}
\ No newline at end of file
+}
+
+fn smoothstep(edge0: f32, edge1: f32, x: f32) -> f32 {
+    let t = ((x - edge0) / (edge1 - edge0)).clamp(0.0, 1.0);
+    t * t * (3.0 - 2.0 * t)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn gray_frame() -> Frame {
+        Frame::new_filled(64, 48, [128, 128, 128])
+    }
+
+    #[test]
+    fn test_zero_intensity_leaves_frame_untouched() {
+        let style = FilmStyle::new();
+        let mut frame = Frame::new_filled(32, 24, [200, 100, 50]);
+        let original = frame.clone();
+
+        style.apply_effect(&mut frame, &StyleConfig::with_intensity(0.0), &FrameContext::default()).unwrap();
+
+        assert_eq!(frame.to_rgb_bytes(), original.to_rgb_bytes());
+    }
+
+    #[test]
+    fn test_draft_skips_grain() {
+        let style = FilmStyle::new();
+        let config = StyleConfig::with_intensity(1.0);
+        let draft = FrameContext { draft: true, ..FrameContext::default() };
+
+        let mut drafted = gray_frame();
+        style.apply_effect(&mut drafted, &config, &draft).unwrap();
+
+        let mut without_grain = gray_frame();
+        style.apply_effect(&mut without_grain, &config.clone().set(GRAIN_INTENSITY, 0.0), &FrameContext::default()).unwrap();
+
+        assert_eq!(drafted.to_rgb_bytes(), without_grain.to_rgb_bytes());
+    }
+
+    #[test]
+    fn test_vignette_darkens_corners() {
+        let style = FilmStyle::new();
+        let mut frame = gray_frame();
+
+        style.apply_vignette(&mut frame, 1.0);
+
+        let center = frame.get_pixel(32, 24)[0];
+        let corner = frame.get_pixel(0, 0)[0];
+        assert_eq!(center, 128);
+        assert!(corner < center / 2, "corner {} should be much darker than center {}", corner, center);
+    }
+
+    #[test]
+    fn test_color_fade_reduces_saturation() {
+        let style = FilmStyle::new();
+        let mut frame = Frame::new_filled(8, 8, [220, 40, 40]);
+
+        style.apply_color_fade(&mut frame, 1.0);
+
+        let pixel = frame.get_pixel(4, 4);
+        let spread_before = 220 - 40;
+        let spread_after = pixel[0] as i32 - pixel[1].min(pixel[2]) as i32;
+        assert!(spread_after < spread_before);
+        // Blacks are lifted
+        assert!(pixel[2] > 40);
+    }
+
+    #[test]
+    fn test_scratches_persist_across_consecutive_frames() {
+        let style = FilmStyle::new();
+
+        let mut shared = 0;
+        let mut total = 0;
+
+        for frame_index in 0..60 {
+            let current = style.scratches_for_frame(640, 480, 1.0, 0, frame_index);
+            let next = style.scratches_for_frame(640, 480, 1.0, 0, frame_index + 1);
+
+            for scratch in &current {
+                total += 1;
+                if next.iter().any(|s| (s.x - scratch.x).abs() <= 1.0 && s.y_start == scratch.y_start) {
+                    shared += 1;
+                }
+            }
+        }
+
+        assert!(total > 0);
+        // Most scratches should still be in place on the following frame
+        assert!(shared as f32 / total as f32 > 0.8, "only {}/{} scratches persisted", shared, total);
+    }
+
+    #[test]
+    fn test_scratches_eventually_change() {
+        let style = FilmStyle::new();
+
+        let first = style.scratches_for_frame(640, 480, 1.0, 0, 0);
+        let later = style.scratches_for_frame(640, 480, 1.0, 0, SCRATCH_LIFETIME * 3);
+
+        assert_ne!(first, later);
+    }
+
+    #[test]
+    fn test_dust_persists_for_its_lifetime() {
+        let style = FilmStyle::new();
+
+        let a = style.dust_for_frame(640, 480, 1.0, 0, 10);
+        let b = style.dust_for_frame(640, 480, 1.0, 0, 11);
+        let shared = a.iter().filter(|speck| b.contains(speck)).count();
+
+        assert!(!a.is_empty());
+        assert!(shared > 0);
+    }
+
+    #[test]
+    fn test_grain_is_deterministic_per_frame() {
+        let style = FilmStyle::new();
+
+        let mut a = gray_frame();
+        let mut b = gray_frame();
+        let mut c = gray_frame();
+        let mut d = gray_frame();
+        style.apply_grain(&mut a, 0.8, 0, 5);
+        style.apply_grain(&mut b, 0.8, 0, 5);
+        style.apply_grain(&mut c, 0.8, 0, 6);
+        style.apply_grain(&mut d, 0.8, 1, 5);
+
+        assert_eq!(a.to_rgb_bytes(), b.to_rgb_bytes());
+        assert_ne!(a.to_rgb_bytes(), c.to_rgb_bytes());
+        assert_ne!(a.to_rgb_bytes(), d.to_rgb_bytes());
+        assert_ne!(a.to_rgb_bytes(), gray_frame().to_rgb_bytes());
+    }
+
+    #[test]
+    fn test_light_leak_warms_frame_edge() {
+        let style = FilmStyle::new();
+        let mut frame = gray_frame();
+
+        style.apply_light_leaks(&mut frame, 1.0, 0);
+
+        let edge = frame.get_pixel(63, 14);
+        assert!(edge[0] > edge[2], "leak should be warm: {:?}", edge);
+        assert!(edge[0] > 128);
+    }
+
+    #[test]
+    fn test_full_effect_uses_config_parameters() {
+        let style = FilmStyle::new();
+        let config = StyleConfig::with_intensity(1.0)
+            .set(GRAIN_INTENSITY, 0.0)
+            .set(SCRATCH_FREQUENCY, 0.0)
+            .set(COLOR_FADE, 0.0)
+            .set(LIGHT_LEAKS, 0.0)
+            .set(VIGNETTE_STRENGTH, 1.0);
+
+        let mut frame = gray_frame();
+        style.apply_effect(&mut frame, &config, &FrameContext::default()).unwrap();
+
+        // Only the vignette is active, so the center stays untouched
+        assert_eq!(frame.get_pixel(32, 24), [128, 128, 128]);
+        assert!(frame.get_pixel(0, 0)[0] < 128);
+    }
+}
@@ -1,3 +1,15 @@
+//! # Film Style Implementation
+//!
+//! Recreates the look of aged film stock with grain, scratches and dust,
+//! faded color, light leaks and a lens vignette.
+
 mod effect;
 
-pub use effect::FilmStyle;
\ No newline at end of file
+pub use effect::FilmStyle;
+
+// Film-specific parameter constants
+pub const GRAIN_INTENSITY: &str = "grain_intensity";
+pub const SCRATCH_FREQUENCY: &str = "scratch_frequency";
+pub const COLOR_FADE: &str = "color_fade";
+pub const LIGHT_LEAKS: &str = "light_leaks";
+pub const VIGNETTE_STRENGTH: &str = "vignette_strength";
@@ -7,8 +7,8 @@
 //!
 //! - **VHS**: Scan lines, color bleeding, tracking errors, noise
 //! - **Film**: Grain, scratches, color fading, light leaks
-//! - **Vintage**: Sepia tones, vignetting, soft focus
-//! - **Boards**: High contrast, bold colors, geometric overlays
+//! - **Vintage**: Era grading presets (70s Kodachrome, 80s pastel, 50s Technicolor, sepia)
+//! - **Boards**: Storyboard pencil sketch with hatching, paper texture and numbered panels
 //!
 //! ## Usage
 //!
@@ -22,9 +22,12 @@
 //! // Apply style to frames during video processing
 //! ```
 
+pub mod context;
 pub mod registry;
 pub mod traits;
 
+pub(crate) mod noise;
+
 // Style implementations
 pub mod vhs;
 pub mod film;
@@ -33,10 +36,11 @@ pub mod boards;
 
 // Re-exports for convenience
 pub use registry::StyleRegistry;
-pub use traits::{Style, StyleConfig, StyleMetadata};
+pub use context::{FrameContext, MusicContext};
+pub use traits::{ConfigValue, Style, StyleConfig, StyleMetadata};
 
 // Re-export all built-in styles
 pub use vhs::VhsStyle;
 pub use film::FilmStyle;
-pub use vintage::VintageStyle;
+pub use vintage::{VintageStyle, VintagePreset};
 pub use boards::BoardsStyle;
\ No newline at end of file
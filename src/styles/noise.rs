@@ -0,0 +1,17 @@
+//! Deterministic hashing helpers shared by the built-in styles
+
+/// Mix three values into a well-distributed 64-bit seed (splitmix64 finalizer)
+pub(crate) fn hash(a: u64, b: u64, c: u64) -> u64 {
+    let mut z = a
+        .wrapping_mul(0x9E37_79B9_7F4A_7C15)
+        .wrapping_add(b.wrapping_mul(0xBF58_476D_1CE4_E5B9))
+        .wrapping_add(c.wrapping_mul(0x94D0_49BB_1331_11EB));
+    z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+    z ^ (z >> 31)
+}
+
+/// Hash three values to a float in [0.0, 1.0)
+pub(crate) fn hash_unit(a: u64, b: u64, c: u64) -> f32 {
+    (hash(a, b, c) >> 40) as f32 / (1u64 << 24) as f32
+}
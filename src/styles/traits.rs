@@ -1,7 +1,7 @@
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 
-use crate::{error::Result, video::types::Frame};
+use crate::{error::Result, styles::context::FrameContext, video::types::Frame};
 
 /// Core trait that all retro styles must implement
 pub trait Style: Send + Sync {
@@ -17,11 +17,12 @@ pub trait Style: Send + Sync {
     ///
     /// * `frame` - The video frame to modify in-place
     /// * `config` - Style-specific configuration parameters
+    /// * `context` - Where the frame sits in the render and in the music
     ///
     /// # Returns
     ///
     /// Returns `Ok(())` if the effect was applied successfully, or an error if processing failed.
-    fn apply_effect(&self, frame: &mut Frame, config: &StyleConfig) -> Result<()>;
+    fn apply_effect(&self, frame: &mut Frame, config: &StyleConfig, context: &FrameContext) -> Result<()>;
 
     /// Get the default configuration for this style
     fn default_config(&self) -> StyleConfig {
@@ -99,11 +100,26 @@ impl StyleConfig {
         self
     }
 
+    /// Fill in parameters that aren't set here from `defaults`
+    ///
+    /// Parameters already set and the intensity are kept.
+    pub fn with_defaults(mut self, defaults: &StyleConfig) -> Self {
+        for (key, value) in &defaults.parameters {
+            self.parameters.entry(key.clone()).or_insert_with(|| value.clone());
+        }
+        self
+    }
+
     /// Get a parameter value as a specific type
     pub fn get_f32(&self, key: &str) -> Option<f32> {
         self.parameters.get(key).and_then(|v| v.as_f32())
     }
 
+    /// Get a parameter value as an integer
+    pub fn get_i32(&self, key: &str) -> Option<i32> {
+        self.parameters.get(key).and_then(|v| v.as_i32())
+    }
+
     /// Get a parameter value as a boolean
     pub fn get_bool(&self, key: &str) -> Option<bool> {
         self.parameters.get(key).and_then(|v| v.as_bool())
@@ -1,16 +1,26 @@
 // src/styles/vhs/effect.rs - Enhanced VHS effects
 
-use rand::Rng;
+use rand::{rngs::SmallRng, Rng, SeedableRng};
 
 use crate::{
     error::Result,
-    styles::{Style, StyleConfig},
+    styles::{FrameContext, Style, StyleConfig},
+    styles::noise::hash,
     styles::traits::StyleMetadata,
     video::types::Frame,
 };
 
 use super::{SCANLINE_INTENSITY, COLOR_BLEEDING, TRACKING_ERROR, NOISE_LEVEL, CHROMA_SHIFT, SATURATION_BOOST};
 
+// Salts that keep the random streams for each artifact independent
+const TRACKING_SALT: u64 = 1;
+const NOISE_SALT: u64 = 2;
+
+/// Random stream for one artifact on one frame of a render
+fn frame_rng(salt: u64, seed: u64, frame_index: u64) -> SmallRng {
+    SmallRng::seed_from_u64(hash(salt, seed, frame_index))
+}
+
 /// VHS-style video effect implementation with enhanced visual impact
 pub struct VhsStyle;
 
@@ -87,9 +97,8 @@ impl VhsStyle {
     }
 
     /// Apply **ENHANCED** tracking errors
-    fn apply_tracking_error(&self, frame: &mut Frame, intensity: f32) {
+    fn apply_tracking_error(&self, frame: &mut Frame, intensity: f32, rng: &mut SmallRng) {
         let height = frame.height();
-        let mut rng = rand::thread_rng();
 
         // **ENHANCED**: More frequent and varied tracking errors
         for y in 0..height {
@@ -103,11 +112,11 @@ impl VhsStyle {
                     rng.gen_range(-8..=8) // Occasional large glitches
                 };
 
-                self.displace_scanline(frame, y, displacement);
+                self.displace_scanline(frame, y, displacement, rng);
 
                 // **ENHANCED**: Sometimes affect multiple consecutive lines
                 if rng.gen::<f32>() < 0.3 && y < height - 1 {
-                    self.displace_scanline(frame, y + 1, displacement / 2);
+                    self.displace_scanline(frame, y + 1, displacement / 2, rng);
                 }
             }
         }
@@ -159,7 +168,7 @@ impl VhsStyle {
         }
     }
 
-    fn displace_scanline(&self, frame: &mut Frame, y: u32, displacement: i32) {
+    fn displace_scanline(&self, frame: &mut Frame, y: u32, displacement: i32, rng: &mut SmallRng) {
         let width = frame.width() as i32;
 
         if displacement == 0 { return; }
@@ -183,7 +192,7 @@ impl VhsStyle {
             } else {
                 // **ENHANCED**: Fill displaced areas with "snow"
                 let target_pixel = frame.get_pixel_mut(x as u32, y);
-                let noise = rand::thread_rng().gen_range(0..=64);
+                let noise = rng.gen_range(0..=64);
                 target_pixel[0] = noise;
                 target_pixel[1] = noise;
                 target_pixel[2] = noise;
@@ -192,10 +201,9 @@ impl VhsStyle {
     }
 
     /// Apply **ENHANCED** VHS-style noise
-    fn apply_noise(&self, frame: &mut Frame, intensity: f32) {
+    fn apply_noise(&self, frame: &mut Frame, intensity: f32, rng: &mut SmallRng) {
         let height = frame.height();
         let width = frame.width();
-        let mut rng = rand::thread_rng();
 
         // **ENHANCED**: More varied noise patterns
         for y in 0..height {
@@ -211,9 +219,8 @@ impl VhsStyle {
                     if noise_type < 0.6 {
                         // Regular grain noise
                         let noise = rng.gen_range(-30..=30);
-                        for channel in 0..3 {
-                            let new_value = (pixel[channel] as i16 + noise).clamp(0, 255);
-                            pixel[channel] = new_value as u8;
+                        for channel in pixel.iter_mut().take(3) {
+                            *channel = (*channel as i16 + noise).clamp(0, 255) as u8;
                         }
                     } else if noise_type < 0.8 {
                         // "Snow" noise (bright dots)
@@ -236,15 +243,14 @@ impl VhsStyle {
         if intensity > 0.6 && rng.gen::<f32>() < 0.2 {
             let band_start = rng.gen_range(0..height);
             let band_height = rng.gen_range(2..=8);
-            self.apply_noise_band(frame, band_start, band_height, intensity);
+            self.apply_noise_band(frame, band_start, band_height, intensity, rng);
         }
     }
 
     /// **NEW**: Apply horizontal noise bands
-    fn apply_noise_band(&self, frame: &mut Frame, start_y: u32, height: u32, intensity: f32) {
+    fn apply_noise_band(&self, frame: &mut Frame, start_y: u32, height: u32, intensity: f32, rng: &mut SmallRng) {
         let width = frame.width();
         let frame_height = frame.height();
-        let mut rng = rand::thread_rng();
 
         for y in start_y..=(start_y + height).min(frame_height - 1) {
             for x in 0..width {
@@ -252,9 +258,8 @@ impl VhsStyle {
                     let pixel = frame.get_pixel_mut(x, y);
                     let noise = rng.gen_range(-50..=50);
 
-                    for channel in 0..3 {
-                        let new_value = (pixel[channel] as i16 + noise).clamp(0, 255);
-                        pixel[channel] = new_value as u8;
+                    for channel in pixel.iter_mut().take(3) {
+                        *channel = (*channel as i16 + noise).clamp(0, 255) as u8;
                     }
                 }
             }
@@ -365,6 +370,12 @@ impl VhsStyle {
     }
 }
 
+impl Default for VhsStyle {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl Style for VhsStyle {
     fn name(&self) -> &str {
         "vhs"
@@ -374,23 +385,45 @@ impl Style for VhsStyle {
         "Enhanced VHS video tape aesthetic with pronounced scan lines, color bleeding, tracking errors, and noise"
     }
 
-    fn apply_effect(&self, frame: &mut Frame, config: &StyleConfig) -> Result<()> {
+    fn default_config(&self) -> StyleConfig {
+        StyleConfig::default()
+            .set(SCANLINE_INTENSITY, 0.9)
+            .set(COLOR_BLEEDING, 0.8)
+            .set(NOISE_LEVEL, 0.6)
+            .set(TRACKING_ERROR, 0.5)
+            .set(CHROMA_SHIFT, 0.7)
+    }
+
+    fn apply_effect(&self, frame: &mut Frame, config: &StyleConfig, context: &FrameContext) -> Result<()> {
         let intensity = config.intensity;
 
         // Get VHS-specific parameters with enhanced defaults
         let scanline_intensity = config.get_f32_or(SCANLINE_INTENSITY, 0.9);
         let color_bleeding = config.get_f32_or(COLOR_BLEEDING, 0.8);
-        let tracking_error = config.get_f32_or(TRACKING_ERROR, 0.5);
-        let noise_level = config.get_f32_or(NOISE_LEVEL, 0.6);
+        let mut tracking_error = config.get_f32_or(TRACKING_ERROR, 0.5);
+        let mut noise_level = config.get_f32_or(NOISE_LEVEL, 0.6);
         let chroma_shift = config.get_f32_or(CHROMA_SHIFT, 0.7);
         let saturation_boost = config.get_f32_or(SATURATION_BOOST, 0.4);
 
+        // The tape wobbles over the course of a shot, and tracking slips just
+        // after each beat, harder in loud passages
+        let wobble = context.segment_progress * std::f32::consts::PI;
+        tracking_error += (wobble * 2.0).sin() * 0.1;
+        noise_level += (wobble * 3.0).sin() * 0.05;
+        if context.on_beat(0.1) {
+            tracking_error += 0.4 * context.energy;
+        }
+
+        // Glitches depend only on the render seed and the frame, never on thread scheduling
+        let seed = context.seed;
+        let frame_index = context.frame_index;
+
         // **ENHANCED**: Apply effects in optimal order for maximum visual impact
         self.apply_scanlines(frame, scanline_intensity * intensity);
         self.apply_color_bleeding(frame, color_bleeding * intensity);
         self.apply_chroma_shift(frame, chroma_shift * intensity);
-        self.apply_tracking_error(frame, tracking_error * intensity);
-        self.apply_noise(frame, noise_level * intensity);
+        self.apply_tracking_error(frame, tracking_error * intensity, &mut frame_rng(TRACKING_SALT, seed, frame_index));
+        self.apply_noise(frame, noise_level * intensity, &mut frame_rng(NOISE_SALT, seed, frame_index));
         self.apply_saturation_boost(frame, saturation_boost * intensity);
 
         // **NEW**: Add color temperature shift for authentic VHS look
@@ -415,4 +448,52 @@ impl Style for VhsStyle {
             ],
         }
     }
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn render(context: FrameContext) -> Vec<u8> {
+        let mut frame = Frame::new_filled(64, 48, [120, 90, 60]);
+        let config = StyleConfig::with_intensity(1.0)
+            .set(TRACKING_ERROR, 0.5)
+            .set(NOISE_LEVEL, 1.0);
+
+        VhsStyle::new().apply_effect(&mut frame, &config, &context).unwrap();
+        frame.to_rgb_bytes()
+    }
+
+    fn frame(seed: u64, frame_index: u64) -> FrameContext {
+        FrameContext { seed, ..FrameContext::for_frame(frame_index) }
+    }
+
+    #[test]
+    fn test_glitches_are_reproducible_per_seed_and_frame() {
+        assert_eq!(render(frame(42, 7)), render(frame(42, 7)));
+        assert_ne!(render(frame(42, 7)), render(frame(43, 7)));
+        assert_ne!(render(frame(42, 7)), render(frame(42, 8)));
+    }
+
+    #[test]
+    fn test_loud_beats_add_tracking_errors() {
+        let off_beat = frame(42, 7);
+        let on_beat = FrameContext { time_since_beat: 0.02, energy: 1.0, ..off_beat };
+
+        assert_eq!(render(off_beat), render(FrameContext { energy: 1.0, ..off_beat }));
+        assert_ne!(render(off_beat), render(on_beat));
+    }
+
+    #[test]
+    fn test_config_overrides_default_parameters() {
+        let defaults = VhsStyle::new().default_config();
+        let config = StyleConfig::with_intensity(0.3)
+            .set(NOISE_LEVEL, 0.1)
+            .with_defaults(&defaults);
+
+        assert_eq!(config.intensity, 0.3);
+        assert_eq!(config.get_f32(NOISE_LEVEL), Some(0.1));
+        assert_eq!(config.get_f32(SCANLINE_INTENSITY), Some(0.9));
+        assert_eq!(config.parameters.len(), defaults.parameters.len());
+    }
+}
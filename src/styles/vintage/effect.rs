@@ -1,19 +1,144 @@
 use crate::{
-    error::Result,
-    styles::{Style, StyleConfig},
+    error::{Result, StyleError},
+    styles::{FrameContext, Style, StyleConfig},
     styles::traits::StyleMetadata,
     video::types::Frame,
 };
 
+use super::preset::{GradeParams, VintagePreset};
+use super::{PRESET, HALATION, SPLIT_TONING, VIGNETTE_STRENGTH, CONTRAST_BOOST, WARMTH};
+
+/// Halation is computed on a downscaled copy of the frame for speed
+const HALATION_DOWNSCALE: u32 = 4;
+
 /// Vintage-style video effect implementation
 ///
-/// Creates a nostalgic vintage look with sepia tones, vignetting, and soft focus
+/// Grades footage to look like a specific era of film stock. Each preset maps to
+/// a tone curve, split toning, a contrast curve with soft highlight roll-off and
+/// a halation glow around bright areas.
 pub struct VintageStyle;
 
 impl VintageStyle {
     pub fn new() -> Self {
         Self
     }
+
+    /// Resolve the preset selected in the config
+    fn preset_from_config(&self, config: &StyleConfig) -> Result<VintagePreset> {
+        match config.get_string(PRESET) {
+            None => Ok(VintagePreset::default()),
+            Some(name) => VintagePreset::from_name(name).ok_or_else(|| {
+                StyleError::InvalidConfig {
+                    details: format!(
+                        "Unknown vintage preset '{}'. Available presets: kodachrome, pastel, technicolor, sepia",
+                        name
+                    ),
+                }
+                .into()
+            }),
+        }
+    }
+
+    /// Apply the preset's grading parameters, with any overrides from the config
+    fn grade_from_config(&self, config: &StyleConfig) -> Result<GradeParams> {
+        let mut grade = self.preset_from_config(config)?.grade();
+
+        grade.halation = config.get_f32_or(HALATION, grade.halation);
+        grade.split_toning = config.get_f32_or(SPLIT_TONING, grade.split_toning);
+        grade.vignette = config.get_f32_or(VIGNETTE_STRENGTH, grade.vignette);
+        grade.contrast *= 1.0 + config.get_f32_or(CONTRAST_BOOST, 0.0) * 0.5;
+
+        let warmth = config.get_f32_or(WARMTH, 0.0);
+        grade.gain[0] *= 1.0 + warmth * 0.08;
+        grade.gain[2] *= 1.0 - warmth * 0.12;
+
+        Ok(grade)
+    }
+
+    /// Grade a single normalized RGB value (everything except halation and vignette)
+    fn grade_pixel(&self, rgb: [f32; 3], grade: &GradeParams) -> [f32; 3] {
+        let luma = luminance(rgb);
+
+        // Saturation (or full monochrome for toned prints)
+        let saturation = if grade.monochrome { 0.0 } else { grade.saturation };
+        let mut out = rgb.map(|c| luma + (c - luma) * saturation);
+
+        // Contrast around mid-gray with a soft shoulder and lifted toe
+        out = out.map(|c| {
+            let c = 0.5 + (c - 0.5) * grade.contrast;
+            let c = roll_off(c, grade.shoulder);
+            grade.toe + c.max(0.0) * (1.0 - grade.toe)
+        });
+
+        // Per-channel lift/gamma/gain tone curves
+        for (channel, value) in out.iter_mut().enumerate() {
+            let curved = value.clamp(0.0, 1.0).powf(1.0 / grade.gamma[channel]);
+            let lift = grade.lift[channel];
+            *value = (lift + curved * (1.0 - lift)) * grade.gain[channel];
+        }
+
+        // Split toning: tint shadows and highlights separately
+        let toned_luma = luminance(out).clamp(0.0, 1.0);
+        let shadow_weight = (1.0 - toned_luma).powi(2) * grade.split_toning;
+        let highlight_weight = toned_luma.powi(2) * grade.split_toning;
+        for (channel, value) in out.iter_mut().enumerate() {
+            *value += grade.shadow_tint[channel] * shadow_weight
+                + grade.highlight_tint[channel] * highlight_weight;
+        }
+
+        out.map(|c| c.clamp(0.0, 1.0))
+    }
+
+    /// Build the blurred glow buffer for halation at reduced resolution
+    fn halation_map(&self, frame: &Frame, grade: &GradeParams) -> (Vec<f32>, u32, u32) {
+        let width = frame.width().div_ceil(HALATION_DOWNSCALE).max(1);
+        let height = frame.height().div_ceil(HALATION_DOWNSCALE).max(1);
+        let mut glow = vec![0.0f32; (width * height) as usize];
+
+        // Bright-pass on the downscaled frame
+        for y in 0..height {
+            for x in 0..width {
+                let mut sum = 0.0;
+                let mut count = 0;
+                for sy in (y * HALATION_DOWNSCALE)..((y + 1) * HALATION_DOWNSCALE).min(frame.height()) {
+                    for sx in (x * HALATION_DOWNSCALE)..((x + 1) * HALATION_DOWNSCALE).min(frame.width()) {
+                        sum += luminance(normalize(frame.get_pixel(sx, sy)));
+                        count += 1;
+                    }
+                }
+                let luma = sum / count.max(1) as f32;
+                let excess = (luma - grade.halation_threshold) / (1.0 - grade.halation_threshold).max(1e-3);
+                glow[(y * width + x) as usize] = excess.max(0.0);
+            }
+        }
+
+        // Two box blur passes approximate a gaussian glow
+        let radius = (width.max(height) / 40).max(1) as usize;
+        for _ in 0..2 {
+            box_blur(&mut glow, width as usize, height as usize, radius);
+        }
+
+        (glow, width, height)
+    }
+
+    /// Darkening factor for the lens vignette at a pixel
+    fn vignette_factor(&self, x: u32, y: u32, width: u32, height: u32, strength: f32) -> f32 {
+        if strength <= 0.0 {
+            return 1.0;
+        }
+
+        let dx = (x as f32 + 0.5) / width as f32 - 0.5;
+        let dy = (y as f32 + 0.5) / height as f32 - 0.5;
+        let distance = (dx * dx + dy * dy).sqrt() / std::f32::consts::FRAC_1_SQRT_2;
+
+        1.0 - strength * 0.6 * distance.powi(2)
+    }
+}
+
+impl Default for VintageStyle {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl Style for VintageStyle {
@@ -22,19 +147,61 @@ impl Style for VintageStyle {
     }
 
     fn description(&self) -> &str {
-        "Nostalgic vintage aesthetic with sepia tones, vignetting, and soft focus"
+        "Era-specific color grading: 70s Kodachrome, 80s pastel, 50s Technicolor and sepia"
     }
 
-    fn apply_effect(&self, _frame: &mut Frame, _config: &StyleConfig) -> Result<()> {
-        // TODO: Implement vintage effects
-        // - Sepia tone conversion
-        // - Vignetting
-        // - Soft focus/blur
-        // - Warm color grading
-        // - Contrast adjustment
+    fn apply_effect(&self, frame: &mut Frame, config: &StyleConfig, context: &FrameContext) -> Result<()> {
+        let intensity = config.intensity.clamp(0.0, 1.0);
+        if intensity <= 0.0 {
+            return Ok(());
+        }
+
+        let grade = self.grade_from_config(config)?;
+        let width = frame.width();
+        let height = frame.height();
+
+        // The halation blur is the costliest pass; drafts grade without it
+        let halation = if grade.halation > 0.0 && !context.draft {
+            Some(self.halation_map(frame, &grade))
+        } else {
+            None
+        };
+
+        for y in 0..height {
+            for x in 0..width {
+                let original = normalize(frame.get_pixel(x, y));
+                let mut graded = self.grade_pixel(original, &grade);
+
+                // Halation: red-orange glow bleeding around highlights (screen blend)
+                if let Some((glow, glow_width, glow_height)) = &halation {
+                    let gx = (x / HALATION_DOWNSCALE).min(glow_width - 1);
+                    let gy = (y / HALATION_DOWNSCALE).min(glow_height - 1);
+                    let amount = (glow[(gy * glow_width + gx) as usize] * grade.halation).min(1.0);
+
+                    for (value, &tint) in graded.iter_mut().zip(grade.halation_color.iter()) {
+                        let overlay = tint * amount;
+                        *value = 1.0 - (1.0 - *value) * (1.0 - overlay);
+                    }
+                }
+
+                let vignette = self.vignette_factor(x, y, width, height, grade.vignette);
+
+                let pixel = frame.get_pixel_mut(x, y);
+                for channel in 0..3 {
+                    let value = graded[channel] * vignette;
+                    let mixed = original[channel] + (value - original[channel]) * intensity;
+                    pixel[channel] = (mixed.clamp(0.0, 1.0) * 255.0).round() as u8;
+                }
+            }
+        }
+
         Ok(())
     }
 
+    fn validate_config(&self, config: &StyleConfig) -> Result<()> {
+        self.preset_from_config(config).map(|_| ())
+    }
+
     fn metadata(&self) -> StyleMetadata {
         StyleMetadata {
             gpu_accelerated: false,
@@ -42,12 +209,250 @@ impl Style for VintageStyle {
             composable: true,
             required_parameters: vec![],
             optional_parameters: vec![
-                ("sepia_strength".to_string(), "Intensity of sepia effect (0.0-1.0)".to_string()),
-                ("vignette_radius".to_string(), "Vignette effect radius (0.0-1.0)".to_string()),
-                ("soft_focus".to_string(), "Soft focus blur amount (0.0-1.0)".to_string()),
-                ("warmth".to_string(), "Color temperature warmth (0.0-1.0)".to_string()),
-                ("contrast_boost".to_string(), "Contrast enhancement (0.0-1.0)".to_string()),
+                (PRESET.to_string(), "Grading preset: kodachrome (70s), pastel (80s), technicolor (50s), sepia".to_string()),
+                (HALATION.to_string(), "Highlight halation glow, overrides the preset (0.0-1.0)".to_string()),
+                (SPLIT_TONING.to_string(), "Shadow/highlight split toning strength, overrides the preset (0.0-1.0)".to_string()),
+                (VIGNETTE_STRENGTH.to_string(), "Vignette effect strength, overrides the preset (0.0-1.0)".to_string()),
+                (CONTRAST_BOOST.to_string(), "Extra contrast on top of the preset (0.0-1.0)".to_string()),
+                (WARMTH.to_string(), "Extra color temperature warmth (0.0-1.0)".to_string()),
             ],
         }
     }
-}
\ No newline at end of file
+}
+
+fn normalize(pixel: [u8; 3]) -> [f32; 3] {
+    pixel.map(|c| c as f32 / 255.0)
+}
+
+fn luminance(rgb: [f32; 3]) -> f32 {
+    0.299 * rgb[0] + 0.587 * rgb[1] + 0.114 * rgb[2]
+}
+
+/// Compress values above `shoulder` so highlights roll off instead of clipping
+fn roll_off(value: f32, shoulder: f32) -> f32 {
+    if value <= shoulder {
+        return value;
+    }
+
+    let headroom = (1.0 - shoulder).max(1e-3);
+    shoulder + headroom * ((value - shoulder) / headroom).tanh()
+}
+
+/// In-place separable box blur using running sums
+fn box_blur(buffer: &mut [f32], width: usize, height: usize, radius: usize) {
+    let mut scratch = vec![0.0f32; buffer.len()];
+    let window = (2 * radius + 1) as f32;
+
+    // Horizontal pass
+    for y in 0..height {
+        let row = &buffer[y * width..(y + 1) * width];
+        let mut sum: f32 = (0..=radius).map(|i| row[i.min(width - 1)]).sum::<f32>()
+            + row[0] * radius as f32;
+        for x in 0..width {
+            scratch[y * width + x] = sum / window;
+            let add = row[(x + radius + 1).min(width - 1)];
+            let remove = row[x.saturating_sub(radius)];
+            sum += add - remove;
+        }
+    }
+
+    // Vertical pass
+    for x in 0..width {
+        let column = |y: usize| scratch[y.min(height - 1) * width + x];
+        let mut sum: f32 = (0..=radius).map(column).sum::<f32>() + column(0) * radius as f32;
+        for y in 0..height {
+            buffer[y * width + x] = sum / window;
+            sum += column(y + radius + 1) - column(y.saturating_sub(radius));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+
+    /// Synthetic color chart: gray ramp on top, hue sweep below, a hot spot for halation
+    fn test_chart() -> Frame {
+        let width = 48;
+        let height = 32;
+        let mut frame = Frame::new_black(width, height);
+
+        for y in 0..height {
+            for x in 0..width {
+                let t = x as f32 / (width - 1) as f32;
+                let color = if y < height / 2 {
+                    let v = (t * 255.0) as u8;
+                    [v, v, v]
+                } else {
+                    let hue = t * 6.0;
+                    let f = hue.fract();
+                    let (r, g, b) = match hue as u32 {
+                        0 => (1.0, f, 0.0),
+                        1 => (1.0 - f, 1.0, 0.0),
+                        2 => (0.0, 1.0, f),
+                        3 => (0.0, 1.0 - f, 1.0),
+                        4 => (f, 0.0, 1.0),
+                        _ => (1.0, 0.0, 1.0 - f),
+                    };
+                    [(r * 200.0) as u8, (g * 200.0) as u8, (b * 200.0) as u8]
+                };
+                frame.set_pixel(x, y, color);
+            }
+        }
+
+        // Bright patch to exercise halation
+        for y in 20..26 {
+            for x in 20..26 {
+                frame.set_pixel(x, y, [255, 255, 255]);
+            }
+        }
+
+        frame
+    }
+
+    fn golden_path(preset: VintagePreset) -> PathBuf {
+        PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+            .join("tests")
+            .join("golden")
+            .join(format!("vintage_{}.png", preset.name()))
+    }
+
+    /// Compare against the stored golden image (regenerate with `RETRO_UPDATE_GOLDEN=1`)
+    fn assert_matches_golden(preset: VintagePreset) {
+        let style = VintageStyle::new();
+        let config = StyleConfig::with_intensity(1.0).set(PRESET, preset.name());
+
+        let mut frame = test_chart();
+        style.apply_effect(&mut frame, &config, &FrameContext::default()).unwrap();
+
+        let path = golden_path(preset);
+        if std::env::var("RETRO_UPDATE_GOLDEN").is_ok() {
+            std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+            frame.save_png(&path).unwrap();
+        }
+
+        let golden = image::open(&path)
+            .unwrap_or_else(|e| panic!("missing golden image {}: {}", path.display(), e))
+            .to_rgb8();
+        assert_eq!(golden.dimensions(), (frame.width(), frame.height()));
+
+        // Allow a tiny tolerance for floating point differences between platforms
+        for (x, y, expected) in golden.enumerate_pixels() {
+            let actual = frame.get_pixel(x, y);
+            for channel in 0..3 {
+                let diff = (actual[channel] as i16 - expected[channel] as i16).abs();
+                assert!(
+                    diff <= 2,
+                    "{} preset differs from golden at ({}, {}): {:?} vs {:?}",
+                    preset.name(), x, y, actual, expected.0
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_golden_kodachrome() {
+        assert_matches_golden(VintagePreset::Kodachrome);
+    }
+
+    #[test]
+    fn test_golden_pastel() {
+        assert_matches_golden(VintagePreset::Pastel);
+    }
+
+    #[test]
+    fn test_golden_technicolor() {
+        assert_matches_golden(VintagePreset::Technicolor);
+    }
+
+    #[test]
+    fn test_golden_sepia() {
+        assert_matches_golden(VintagePreset::Sepia);
+    }
+
+    #[test]
+    fn test_preset_parsing() {
+        assert_eq!(VintagePreset::from_name("Kodachrome"), Some(VintagePreset::Kodachrome));
+        assert_eq!(VintagePreset::from_name("80s"), Some(VintagePreset::Pastel));
+        assert_eq!(VintagePreset::from_name("50s"), Some(VintagePreset::Technicolor));
+        assert_eq!(VintagePreset::from_name("sepia"), Some(VintagePreset::Sepia));
+        assert_eq!(VintagePreset::from_name("noir"), None);
+    }
+
+    #[test]
+    fn test_unknown_preset_is_rejected() {
+        let style = VintageStyle::new();
+        let config = StyleConfig::default().set(PRESET, "noir");
+
+        assert!(style.validate_config(&config).is_err());
+        assert!(style.apply_effect(&mut test_chart(), &config, &FrameContext::default()).is_err());
+    }
+
+    #[test]
+    fn test_draft_skips_halation() {
+        let style = VintageStyle::new();
+        let config = StyleConfig::with_intensity(1.0).set(PRESET, "kodachrome");
+        let draft = FrameContext { draft: true, ..FrameContext::default() };
+
+        let mut drafted = test_chart();
+        style.apply_effect(&mut drafted, &config, &draft).unwrap();
+
+        let mut without_halation = test_chart();
+        style.apply_effect(&mut without_halation, &config.clone().set(HALATION, 0.0), &FrameContext::default()).unwrap();
+
+        assert_eq!(drafted.to_rgb_bytes(), without_halation.to_rgb_bytes());
+    }
+
+    #[test]
+    fn test_sepia_is_monochrome_toned() {
+        let style = VintageStyle::new();
+        let config = StyleConfig::with_intensity(1.0)
+            .set(PRESET, "sepia")
+            .set(HALATION, 0.0)
+            .set(VIGNETTE_STRENGTH, 0.0);
+
+        let mut red = Frame::new_filled(4, 4, [200, 0, 0]);
+        let mut green = Frame::new_filled(4, 4, [0, 100, 0]);
+        style.apply_effect(&mut red, &config, &FrameContext::default()).unwrap();
+        style.apply_effect(&mut green, &config, &FrameContext::default()).unwrap();
+
+        // Warm brown tone: red >= green >= blue
+        let p = red.get_pixel(2, 2);
+        assert!(p[0] >= p[1] && p[1] >= p[2], "{:?}", p);
+
+        // Hue is discarded, so similar luminance gives similar output
+        let q = green.get_pixel(2, 2);
+        for channel in 0..3 {
+            assert!((p[channel] as i16 - q[channel] as i16).abs() <= 3, "{:?} vs {:?}", p, q);
+        }
+    }
+
+    #[test]
+    fn test_pastel_lifts_blacks() {
+        let style = VintageStyle::new();
+        let config = StyleConfig::with_intensity(1.0).set(PRESET, "pastel");
+
+        let mut frame = Frame::new_black(8, 8);
+        style.apply_effect(&mut frame, &config, &FrameContext::default()).unwrap();
+
+        assert!(frame.get_pixel(4, 4).iter().all(|&c| c > 15));
+    }
+
+    #[test]
+    fn test_highlights_roll_off() {
+        assert!(roll_off(1.3, 0.8) < 1.0);
+        assert_eq!(roll_off(0.5, 0.8), 0.5);
+    }
+
+    #[test]
+    fn test_zero_intensity_leaves_frame_untouched() {
+        let style = VintageStyle::new();
+        let mut frame = test_chart();
+        let original = frame.clone();
+
+        style.apply_effect(&mut frame, &StyleConfig::with_intensity(0.0), &FrameContext::default()).unwrap();
+
+        assert_eq!(frame.to_rgb_bytes(), original.to_rgb_bytes());
+    }
+}
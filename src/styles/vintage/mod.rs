@@ -1,3 +1,19 @@
+//! # Vintage Style Implementation
+//!
+//! Era-specific color grading built from tone curves, split toning, a soft
+//! highlight roll-off and halation. The look is chosen with the `preset`
+//! parameter: `kodachrome` (70s), `pastel` (80s), `technicolor` (50s) or `sepia`.
+
 mod effect;
+mod preset;
+
+pub use effect::VintageStyle;
+pub use preset::{GradeParams, VintagePreset};
 
-pub use effect::VintageStyle;
\ No newline at end of file
+// Vintage-specific parameter constants
+pub const PRESET: &str = "preset";
+pub const HALATION: &str = "halation";
+pub const SPLIT_TONING: &str = "split_toning";
+pub const VIGNETTE_STRENGTH: &str = "vignette_strength";
+pub const CONTRAST_BOOST: &str = "contrast_boost";
+pub const WARMTH: &str = "warmth";
@@ -0,0 +1,171 @@
+/// Era-specific grading presets for the vintage style
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum VintagePreset {
+    /// 70s Kodachrome: warm, saturated, punchy contrast
+    #[default]
+    Kodachrome,
+
+    /// 80s faded print: lifted blacks, low contrast, pastel pinks and cyans
+    Pastel,
+
+    /// 50s three-strip Technicolor: vivid primaries and deep blacks
+    Technicolor,
+
+    /// Monochrome sepia-toned print
+    Sepia,
+}
+
+/// Grading recipe a preset maps to
+#[derive(Debug, Clone, Copy)]
+pub struct GradeParams {
+    /// Convert to monochrome before toning
+    pub monochrome: bool,
+
+    /// Saturation multiplier around luminance
+    pub saturation: f32,
+
+    /// Contrast around mid-gray
+    pub contrast: f32,
+
+    /// Level where highlights start rolling off (0.0-1.0)
+    pub shoulder: f32,
+
+    /// Black level lift applied after the contrast curve
+    pub toe: f32,
+
+    /// Per-channel tone curve: black lift
+    pub lift: [f32; 3],
+
+    /// Per-channel tone curve: midtone gamma
+    pub gamma: [f32; 3],
+
+    /// Per-channel tone curve: white gain
+    pub gain: [f32; 3],
+
+    /// Tint added to the shadows
+    pub shadow_tint: [f32; 3],
+
+    /// Tint added to the highlights
+    pub highlight_tint: [f32; 3],
+
+    /// Strength of the split toning (0.0-1.0)
+    pub split_toning: f32,
+
+    /// Strength of the halation glow around highlights (0.0-1.0)
+    pub halation: f32,
+
+    /// Luminance above which highlights bloom
+    pub halation_threshold: f32,
+
+    /// Color of the halation glow
+    pub halation_color: [f32; 3],
+
+    /// Strength of the lens vignette (0.0-1.0)
+    pub vignette: f32,
+}
+
+impl VintagePreset {
+    /// All available presets
+    pub const ALL: [VintagePreset; 4] = [
+        VintagePreset::Kodachrome,
+        VintagePreset::Pastel,
+        VintagePreset::Technicolor,
+        VintagePreset::Sepia,
+    ];
+
+    /// Parse a preset from its name or era alias
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.to_lowercase().as_str() {
+            "kodachrome" | "70s" => Some(Self::Kodachrome),
+            "pastel" | "80s" => Some(Self::Pastel),
+            "technicolor" | "50s" => Some(Self::Technicolor),
+            "sepia" => Some(Self::Sepia),
+            _ => None,
+        }
+    }
+
+    /// Canonical name of the preset
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Kodachrome => "kodachrome",
+            Self::Pastel => "pastel",
+            Self::Technicolor => "technicolor",
+            Self::Sepia => "sepia",
+        }
+    }
+
+    /// Grading parameters for this preset
+    pub fn grade(&self) -> GradeParams {
+        match self {
+            Self::Kodachrome => GradeParams {
+                monochrome: false,
+                saturation: 1.15,
+                contrast: 1.2,
+                shoulder: 0.8,
+                toe: 0.02,
+                lift: [0.02, 0.01, 0.0],
+                gamma: [1.05, 1.0, 0.92],
+                gain: [1.05, 1.0, 0.88],
+                shadow_tint: [0.0, 0.02, 0.05],
+                highlight_tint: [0.08, 0.04, -0.04],
+                split_toning: 0.6,
+                halation: 0.25,
+                halation_threshold: 0.75,
+                halation_color: [1.0, 0.45, 0.2],
+                vignette: 0.2,
+            },
+            Self::Pastel => GradeParams {
+                monochrome: false,
+                saturation: 0.75,
+                contrast: 0.8,
+                shoulder: 0.7,
+                toe: 0.1,
+                lift: [0.08, 0.07, 0.09],
+                gamma: [1.05, 1.05, 1.0],
+                gain: [0.97, 0.95, 0.97],
+                shadow_tint: [0.0, 0.03, 0.06],
+                highlight_tint: [0.07, 0.0, 0.04],
+                split_toning: 0.8,
+                halation: 0.15,
+                halation_threshold: 0.8,
+                halation_color: [1.0, 0.6, 0.7],
+                vignette: 0.1,
+            },
+            Self::Technicolor => GradeParams {
+                monochrome: false,
+                saturation: 1.45,
+                contrast: 1.25,
+                shoulder: 0.85,
+                toe: 0.0,
+                lift: [0.0, 0.0, 0.01],
+                gamma: [1.0, 0.97, 1.0],
+                gain: [1.02, 0.98, 1.0],
+                shadow_tint: [0.0, 0.0, 0.04],
+                highlight_tint: [0.04, 0.03, 0.0],
+                split_toning: 0.5,
+                halation: 0.35,
+                halation_threshold: 0.7,
+                halation_color: [1.0, 0.3, 0.2],
+                vignette: 0.15,
+            },
+            Self::Sepia => GradeParams {
+                monochrome: true,
+                saturation: 1.0,
+                contrast: 1.05,
+                shoulder: 0.8,
+                toe: 0.04,
+                lift: [0.06, 0.03, 0.0],
+                gamma: [1.0, 1.05, 1.15],
+                gain: [1.0, 0.9, 0.72],
+                shadow_tint: [0.04, 0.01, -0.02],
+                highlight_tint: [0.06, 0.04, 0.0],
+                split_toning: 0.7,
+                halation: 0.1,
+                halation_threshold: 0.85,
+                halation_color: [1.0, 0.8, 0.6],
+                vignette: 0.35,
+            },
+        }
+    }
+}
+
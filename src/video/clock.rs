@@ -0,0 +1,131 @@
+//! Global frame clock for the output video
+//!
+//! Segment lengths are not rounded to whole frames one by one. Every cut is
+//! mapped to a frame index on a single clock, and each segment covers the
+//! frames between its cut and the next. The rounding error therefore never
+//! exceeds half a frame anywhere in the song, and the rendered video is
+//! exactly as long as the audio it is muxed with.
+
+use std::ops::Range;
+
+use crate::video::frame_rate::FrameRate;
+
+/// Maps between seconds and output frame indices at a fixed frame rate
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct FrameClock {
+    rate: FrameRate,
+}
+
+impl FrameClock {
+    pub fn new(rate: FrameRate) -> Self {
+        Self { rate }
+    }
+
+    /// Frame rate of the clock
+    pub fn rate(&self) -> FrameRate {
+        self.rate
+    }
+
+    /// Index of the frame boundary nearest to `time`
+    pub fn frame_at(&self, time: f64) -> u64 {
+        self.rate.frame_at(time)
+    }
+
+    /// Start time of frame `index`
+    pub fn time_of(&self, index: u64) -> f64 {
+        self.rate.time_of(index)
+    }
+
+    /// `time` moved to the nearest frame boundary
+    pub fn snap(&self, time: f64) -> f64 {
+        self.time_of(self.frame_at(time))
+    }
+
+    /// Number of frames in `duration` seconds of output
+    pub fn total_frames(&self, duration: f64) -> u64 {
+        self.frame_at(duration)
+    }
+
+    /// Frame range of each segment of a sorted cut list
+    ///
+    /// Segment `i` runs from cut `i` to cut `i + 1`, the last one to the end of
+    /// the output. The ranges are contiguous and together cover exactly
+    /// `total_frames(duration)` frames; a segment shorter than half a frame
+    /// gets an empty range.
+    pub fn segment_ranges(&self, cuts: &[f64], duration: f64) -> Vec<Range<u64>> {
+        let total = self.total_frames(duration);
+        let boundary = |time: f64| self.frame_at(time).min(total);
+
+        cuts.iter()
+            .enumerate()
+            .map(|(i, &cut)| {
+                // The first segment always starts the video
+                let start = if i == 0 { 0 } else { boundary(cut) };
+                let end = cuts.get(i + 1).map_or(total, |&next| boundary(next));
+                start..end.max(start)
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::{rngs::SmallRng, Rng, SeedableRng};
+
+    #[test]
+    fn test_snap_and_convert() {
+        let clock = FrameClock::new(FrameRate::PAL);
+
+        assert_eq!(clock.frame_at(1.0), 25);
+        assert_eq!(clock.frame_at(1.019), 25);
+        assert_eq!(clock.frame_at(1.021), 26);
+        assert_eq!(clock.snap(0.53), 0.52);
+        assert_eq!(clock.time_of(50), 2.0);
+        assert_eq!(clock.frame_at(-1.0), 0);
+    }
+
+    #[test]
+    fn test_segments_cover_every_frame_exactly_once() {
+        let mut rng = SmallRng::seed_from_u64(7);
+
+        for fps in [12.0, 23.976, 24.0, 25.0, 29.97, 30.0, 59.94, 60.0] {
+            let rate = FrameRate::from_f64(fps);
+            let clock = FrameClock::new(rate);
+
+            for _ in 0..200 {
+                let duration = rng.gen_range(1.0..300.0);
+                let mut cuts: Vec<f64> = (0..rng.gen_range(0..80))
+                    .map(|_| rng.gen_range(0.0..duration))
+                    .collect();
+                cuts.push(0.0);
+                cuts.sort_by(|a, b| a.total_cmp(b));
+
+                let ranges = clock.segment_ranges(&cuts, duration);
+                let total: u64 = ranges.iter().map(|r| r.end - r.start).sum();
+
+                assert_eq!(ranges.len(), cuts.len());
+                let exact = duration * rate.num() as f64 / rate.den() as f64;
+                assert_eq!(total, exact.round() as u64, "{} cuts at {} fps", cuts.len(), rate);
+                assert_eq!(ranges[0].start, 0);
+                for pair in ranges.windows(2) {
+                    assert_eq!(pair[0].end, pair[1].start);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_late_cuts_do_not_drift() {
+        // 600 segments of 0.41s: rounding each to 30fps on its own (12 frames,
+        // 0.4s) would end 6s early
+        let clock = FrameClock::new(FrameRate::integer(30));
+        let cuts: Vec<f64> = (0..600).map(|i| i as f64 * 0.41).collect();
+        let ranges = clock.segment_ranges(&cuts, 246.0);
+
+        for (cut, range) in cuts.iter().zip(&ranges) {
+            assert!((clock.time_of(range.start) - cut).abs() <= 0.5 / 30.0 + 1e-9);
+        }
+        assert_eq!(ranges.last().unwrap().end, 7380);
+    }
+}
@@ -1,13 +1,11 @@
 use std::path::Path;
 use std::process::{Command, Stdio};
-use std::fs::{File, create_dir_all};
-use std::io::Write;
 
-use tracing::{debug, info, warn};
-use tokio::task;
+use tracing::info;
 
 use crate::error::{VideoError, Result};
 use crate::video::types::{Frame, VideoParams};
+use crate::video::encoder::StreamingEncoder;
 use crate::video::processor::ProcessedSegment;
 
 /// Represents an encoded video output
@@ -22,15 +20,11 @@ pub struct EncodedVideo {
 /// Pure Rust video compositor using external FFmpeg commands
 pub struct VideoCompositor {
     params: VideoParams,
-    temp_dir: Option<String>,
 }
 
 impl VideoCompositor {
     pub fn new(params: VideoParams) -> Self {
-        Self {
-            params,
-            temp_dir: None,
-        }
+        Self { params }
     }
 
     pub fn check_ffmpeg_available() -> bool {
@@ -43,246 +37,48 @@ impl VideoCompositor {
             .unwrap_or(false)
     }
 
-    fn ensure_temp_dir(&mut self) -> Result<String> {
-        if let Some(ref temp_dir) = self.temp_dir {
-            return Ok(temp_dir.clone());
+    /// Start a streaming encode; frames are written as they are produced
+    pub fn begin<P: AsRef<Path>>(&self, audio_path: Option<P>, output_path: P) -> Result<StreamingEncoder> {
+        if !Self::check_ffmpeg_available() {
+            return Err(VideoError::EncodingFailed {
+                reason: "FFmpeg not found. Please install FFmpeg.".to_string(),
+            }.into());
         }
 
-        // Use current directory + unique temp folder
-        let current_dir = std::env::current_dir()
-            .map_err(|e| VideoError::EncodingFailed {
-                reason: format!("Cannot get current directory: {}", e),
-            })?;
-
-        let temp_dir = current_dir
-            .join(format!("retro_temp_{}", std::process::id()))
-            .display()
-            .to_string();
-
-        create_dir_all(&temp_dir)?;
-
-        debug!("Created temporary directory: {}", temp_dir);
-
-        // Verify directory exists and is writable
-        let test_file = format!("{}/test.txt", temp_dir);
-        std::fs::write(&test_file, "test").map_err(|e| VideoError::EncodingFailed {
-            reason: format!("Temporary directory not writable: {}", e),
-        })?;
-        std::fs::remove_file(&test_file).ok();
-
-        self.temp_dir = Some(temp_dir.clone());
-        Ok(temp_dir)
+        StreamingEncoder::start(
+            &self.params,
+            audio_path.as_ref().map(|p| p.as_ref()),
+            output_path.as_ref(),
+        )
     }
 
     pub async fn compose_video<P: AsRef<Path>>(
-        &mut self,
+        &self,
         segments: &[ProcessedSegment],
         audio_path: P,
         output_path: P,
     ) -> Result<EncodedVideo> {
         info!("Composing video with {} segments", segments.len());
 
-        if !Self::check_ffmpeg_available() {
-            return Err(VideoError::EncodingFailed {
-                reason: "FFmpeg not found. Please install FFmpeg.".to_string(),
-            }.into());
+        let mut encoder = self.begin(Some(audio_path), output_path)?;
+        for segment in segments {
+            encoder.write_segment(segment)?;
         }
+        let encoded_video = encoder.finish()?;
 
-        let temp_dir = self.ensure_temp_dir()?;
-
-        let frame_paths = self.save_frames_as_images(segments, &temp_dir).await?;
-        let frame_list_path = self.create_frame_list(&frame_paths, &temp_dir)?;
-
-        let video_only_path = format!("{}/video_only.mp4", temp_dir);
-        self.encode_video_from_frames(&frame_list_path, &video_only_path).await?;
-
-        // Get output path as string before moving
-        let output_path_str = output_path.as_ref().display().to_string();
-
-        self.combine_video_and_audio(&video_only_path, audio_path, &output_path_str).await?;
-
-        let encoded_video = self.get_output_info(&output_path_str, segments).await?;
-
-        info!("Video composition complete: {}MB", 
+        info!("Video composition complete: {}MB",
               encoded_video.file_size / 1024 / 1024);
 
         Ok(encoded_video)
     }
 
-    async fn save_frames_as_images(
-        &self,
-        segments: &[ProcessedSegment],
-        temp_dir: &str,
-    ) -> Result<Vec<String>> {
-        let mut frame_paths = Vec::new();
-        let mut frame_counter = 0;
-
-        debug!("Saving frames to directory: {}", temp_dir);
-
-        for segment in segments {
-            for frame in &segment.frames {
-                let frame_path = format!("{}/frame_{:06}.png", temp_dir, frame_counter);
-
-                debug!("Saving frame to: {}", frame_path);
-
-                frame.save_png(&frame_path).map_err(|e| VideoError::EncodingFailed {
-                    reason: format!("Failed to save frame: {}", e),
-                })?;
-
-                // Verify the file was created
-                if !std::path::Path::new(&frame_path).exists() {
-                    return Err(VideoError::EncodingFailed {
-                        reason: format!("Frame file not created: {}", frame_path),
-                    }.into());
-                }
-
-                frame_paths.push(frame_path);
-                frame_counter += 1;
-            }
-        }
-
-        info!("Saved {} frames as images", frame_counter);
-        Ok(frame_paths)
-    }
-
-    fn create_frame_list(&self, frame_paths: &[String], temp_dir: &str) -> Result<String> {
-        let list_path = format!("{}/frame_list.txt", temp_dir);
-        let mut file = File::create(&list_path)?;
-
-        let frame_duration = 1.0 / self.params.fps;
-
-        debug!("Creating frame list at: {}", list_path);
-        debug!("Frame duration: {:.6}s", frame_duration);
-
-        for (i, frame_path) in frame_paths.iter().enumerate() {
-            // Convert to absolute path to avoid FFmpeg path resolution issues
-            let absolute_path = std::path::Path::new(frame_path)
-                .canonicalize()
-                .map_err(|e| VideoError::EncodingFailed {
-                    reason: format!("Cannot resolve frame path {}: {}", frame_path, e),
-                })?;
-
-            debug!("Frame {}: {}", i, absolute_path.display());
-            writeln!(file, "file '{}'", absolute_path.display())?;
-            writeln!(file, "duration {:.6}", frame_duration)?;
-        }
-
-        // Add the last frame again for proper duration
-        if let Some(last_frame) = frame_paths.last() {
-            let absolute_path = std::path::Path::new(last_frame)
-                .canonicalize()
-                .map_err(|e| VideoError::EncodingFailed {
-                    reason: format!("Cannot resolve last frame path {}: {}", last_frame, e),
-                })?;
-            writeln!(file, "file '{}'", absolute_path.display())?;
-        }
-
-        debug!("Frame list created with {} entries", frame_paths.len());
-        Ok(list_path)
-    }
-
-    async fn encode_video_from_frames(&self, frame_list_path: &str, output_path: &str) -> Result<()> {
-        let mut cmd = Command::new("ffmpeg");
-        cmd.args(&[
-            "-f", "concat",
-            "-safe", "0",
-            "-i", frame_list_path,
-            "-c:v", &self.params.codec,
-            "-r", &self.params.fps.to_string(),
-            "-pix_fmt", "yuv420p",
-            "-crf", &self.quality_to_crf(self.params.quality).to_string(),
-            "-y",
-            output_path,
-        ]);
-
-        let output = task::spawn_blocking(move || cmd.output()).await
-            .map_err(|e| VideoError::EncodingFailed {
-                reason: format!("Failed to spawn FFmpeg process: {}", e),
-            })?
-            .map_err(|e| VideoError::EncodingFailed {
-                reason: format!("FFmpeg execution failed: {}", e),
-            })?;
-
-        if !output.status.success() {
-            let stderr = String::from_utf8_lossy(&output.stderr);
-            return Err(VideoError::EncodingFailed {
-                reason: format!("FFmpeg failed: {}", stderr),
-            }.into());
-        }
-
-        Ok(())
-    }
-
-    async fn combine_video_and_audio<P: AsRef<Path>>(
-        &self,
-        video_path: &str,
-        audio_path: P,
-        output_path: &str,
-    ) -> Result<()> {
-        let mut cmd = Command::new("ffmpeg");
-        cmd.args(&[
-            "-i", video_path,
-            "-i", &audio_path.as_ref().display().to_string(),
-            "-c:v", "copy",
-            "-c:a", "aac",
-            "-shortest",
-            "-y",
-            output_path,
-        ]);
-
-        let output = task::spawn_blocking(move || cmd.output()).await
-            .map_err(|e| VideoError::EncodingFailed {
-                reason: format!("Failed to spawn FFmpeg process: {}", e),
-            })?
-            .map_err(|e| VideoError::EncodingFailed {
-                reason: format!("FFmpeg execution failed: {}", e),
-            })?;
-
-        if !output.status.success() {
-            let stderr = String::from_utf8_lossy(&output.stderr);
-            return Err(VideoError::EncodingFailed {
-                reason: format!("FFmpeg failed: {}", stderr),
-            }.into());
-        }
-
-        Ok(())
-    }
-
-    fn quality_to_crf(&self, quality: u8) -> u8 {
-        (51 - ((quality as f32 / 100.0) * 51.0) as u8).clamp(0, 51)
-    }
-
-    async fn get_output_info<P: AsRef<Path>>(
-        &self,
-        output_path: P,
-        segments: &[ProcessedSegment],
-    ) -> Result<EncodedVideo> {
-        let metadata = std::fs::metadata(output_path.as_ref())?;
-        let duration = segments.last().map(|s| s.end_time).unwrap_or(0.0);
-        let frame_count = segments.iter().map(|s| s.frames.len()).sum();
-
-        Ok(EncodedVideo {
-            path: output_path.as_ref().display().to_string(),
-            duration,
-            frame_count,
-            file_size: metadata.len(),
-        })
-    }
-
     pub async fn create_test_video<P: AsRef<Path>>(
-        &mut self,
+        &self,
         output_path: P,
         duration_seconds: f64,
     ) -> Result<EncodedVideo> {
-        if !Self::check_ffmpeg_available() {
-            return Err(VideoError::EncodingFailed {
-                reason: "FFmpeg not found".to_string(),
-            }.into());
-        }
-
-        let temp_dir = self.ensure_temp_dir()?;
-        let frame_count = (duration_seconds * self.params.fps) as usize;
-        let mut frame_paths = Vec::new();
+        let frame_count = (duration_seconds * self.params.fps.as_f64()) as usize;
+        let mut encoder = self.begin(None, output_path)?;
 
         for i in 0..frame_count {
             let hue = (i as f32 / frame_count as f32) * 360.0;
@@ -293,24 +89,10 @@ impl VideoCompositor {
                 self.params.resolution.1,
                 color,
             );
-
-            let frame_path = format!("{}/test_frame_{:06}.png", temp_dir, i);
-            frame.save_png(&frame_path).map_err(|e| VideoError::EncodingFailed {
-                reason: format!("Failed to save test frame: {}", e),
-            })?;
-            frame_paths.push(frame_path);
+            encoder.write_frame(&frame)?;
         }
 
-        let frame_list_path = self.create_frame_list(&frame_paths, &temp_dir)?;
-        self.encode_video_from_frames(&frame_list_path, &output_path.as_ref().display().to_string()).await?;
-
-        let metadata = std::fs::metadata(output_path.as_ref())?;
-        Ok(EncodedVideo {
-            path: output_path.as_ref().display().to_string(),
-            duration: duration_seconds,
-            frame_count,
-            file_size: metadata.len(),
-        })
+        encoder.finish()
     }
 
     fn hsv_to_rgb(h: f32, s: f32, v: f32) -> [u8; 3] {
@@ -338,20 +120,4 @@ impl VideoCompositor {
             ((b + m) * 255.0) as u8,
         ]
     }
-
-    pub fn cleanup(&mut self) -> Result<()> {
-        if let Some(temp_dir) = &self.temp_dir {
-            if let Err(e) = std::fs::remove_dir_all(temp_dir) {
-                warn!("Failed to remove temporary directory: {}", e);
-            }
-            self.temp_dir = None;
-        }
-        Ok(())
-    }
 }
-
-impl Drop for VideoCompositor {
-    fn drop(&mut self) {
-        let _ = self.cleanup();
-    }
-}
\ No newline at end of file
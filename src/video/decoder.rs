@@ -0,0 +1,229 @@
+//! Streaming frame decoder
+//!
+//! Opens a clip once and reads raw RGB frames from FFmpeg's stdout at a fixed
+//! rate and resolution. Timestamps are served in order from that stream; a new
+//! FFmpeg process is only started when the requested times jump backwards or
+//! too far ahead to be worth decoding through.
+
+use std::io::{BufReader, ErrorKind, Read};
+use std::path::Path;
+use std::process::{Child, ChildStdout, Command, Stdio};
+
+use tracing::{debug, warn};
+
+use crate::error::{Result, VideoError};
+use crate::video::types::Frame;
+
+/// Forward gaps up to this many seconds are decoded through instead of seeking
+const SEEK_THRESHOLD: f64 = 2.0;
+
+/// One sequential read from a single seek point
+#[derive(Debug, Clone, PartialEq)]
+pub struct DecodeRun {
+    /// Seek position in the clip, in seconds
+    pub start: f64,
+    /// Frame offset from `start` for each requested timestamp, non-decreasing
+    pub offsets: Vec<usize>,
+}
+
+/// Group timestamps into sequential runs at the given decode rate
+///
+/// A timestamp continues the current run when it lies at or after the last
+/// frame taken and within `SEEK_THRESHOLD` of it; anything else starts a new
+/// run with its own seek.
+pub fn plan_runs(timestamps: &[f64], fps: f64) -> Vec<DecodeRun> {
+    let mut runs: Vec<DecodeRun> = Vec::new();
+
+    for &time in timestamps {
+        if let Some(run) = runs.last_mut() {
+            let offset = ((time - run.start) * fps).round();
+            let last = *run.offsets.last().unwrap_or(&0) as f64;
+
+            if offset >= last && (offset - last) / fps <= SEEK_THRESHOLD {
+                run.offsets.push(offset as usize);
+                continue;
+            }
+        }
+
+        runs.push(DecodeRun {
+            start: time.max(0.0),
+            offsets: vec![0],
+        });
+    }
+
+    runs
+}
+
+/// Decode rate implied by evenly spaced timestamps (the median forward step)
+pub fn sampling_rate(timestamps: &[f64], fallback: f64) -> f64 {
+    let mut steps: Vec<f64> = timestamps
+        .windows(2)
+        .map(|pair| pair[1] - pair[0])
+        .filter(|&step| step > 1e-6)
+        .collect();
+
+    if steps.is_empty() {
+        return fallback;
+    }
+
+    steps.sort_by(|a, b| a.total_cmp(b));
+    (1.0 / steps[steps.len() / 2]).clamp(1.0, 240.0)
+}
+
+/// A running FFmpeg process producing raw RGB frames
+pub struct FrameDecoder {
+    child: Child,
+    stdout: BufReader<ChildStdout>,
+    width: u32,
+    height: u32,
+}
+
+impl FrameDecoder {
+    /// Start decoding `path` from `start` seconds, resampled to `fps` and scaled to `resolution`
+    pub fn open(path: &Path, start: f64, fps: f64, resolution: (u32, u32)) -> Result<Self> {
+        let (width, height) = resolution;
+        let mut cmd = Command::new("ffmpeg");
+
+        // Hardware acceleration BEFORE input (macOS only)
+        if cfg!(target_os = "macos") {
+            cmd.args(["-hwaccel", "videotoolbox"]);
+        }
+
+        cmd.args([
+            "-ss", &start.to_string(),
+            "-i", &path.display().to_string(),
+            "-an",
+            "-vf", &format!("fps={},scale={}:{}", fps, width, height),
+            "-f", "rawvideo",
+            "-pix_fmt", "rgb24",
+            "-loglevel", "error",
+            "pipe:1",
+        ]);
+
+        debug!("Decoding {} from {:.2}s at {:.2} fps", path.display(), start, fps);
+
+        let mut child = cmd
+            .stdin(Stdio::null())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::null())
+            .spawn()
+            .map_err(|e| VideoError::FrameProcessingFailed {
+                reason: format!("FFmpeg execution failed: {}", e),
+            })?;
+
+        let stdout = child.stdout.take().ok_or_else(|| VideoError::FrameProcessingFailed {
+            reason: "FFmpeg stdout unavailable".to_string(),
+        })?;
+
+        Ok(Self {
+            child,
+            stdout: BufReader::new(stdout),
+            width,
+            height,
+        })
+    }
+
+    /// Read the next frame, or `None` once the clip has ended
+    pub fn next_frame(&mut self) -> Result<Option<Frame>> {
+        let mut data = vec![0u8; self.width as usize * self.height as usize * 3];
+
+        match self.stdout.read_exact(&mut data) {
+            Ok(()) => Ok(Frame::from_rgb_bytes(self.width, self.height, data)),
+            Err(e) if e.kind() == ErrorKind::UnexpectedEof => Ok(None),
+            Err(e) => Err(VideoError::FrameProcessingFailed {
+                reason: format!("Failed to read decoded frame: {}", e),
+            }.into()),
+        }
+    }
+
+    /// Decode one run, returning a frame for each of its offsets
+    ///
+    /// If the clip ends early the last decoded frame is repeated.
+    pub fn read_run(path: &Path, run: &DecodeRun, fps: f64, resolution: (u32, u32)) -> Result<Vec<Frame>> {
+        let mut decoder = Self::open(path, run.start, fps, resolution)?;
+        let mut frames = Vec::with_capacity(run.offsets.len());
+        let mut current: Option<Frame> = None;
+        let mut position = 0;
+        let mut ended = false;
+
+        for &offset in &run.offsets {
+            while !ended && (current.is_none() || position <= offset) {
+                match decoder.next_frame()? {
+                    Some(frame) => {
+                        current = Some(frame);
+                        position += 1;
+                    }
+                    None => ended = true,
+                }
+            }
+
+            match &current {
+                Some(frame) => frames.push(frame.clone()),
+                None => {
+                    return Err(VideoError::FrameProcessingFailed {
+                        reason: format!("No frames decoded from {} at {:.2}s", path.display(), run.start),
+                    }.into());
+                }
+            }
+        }
+
+        if ended && position < run.offsets.last().map_or(0, |&o| o + 1) {
+            warn!("{} ended early, holding last frame", path.display());
+        }
+
+        Ok(frames)
+    }
+}
+
+impl Drop for FrameDecoder {
+    fn drop(&mut self) {
+        // Runs usually stop reading before the clip ends
+        let _ = self.child.kill();
+        let _ = self.child.wait();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sequential_timestamps_share_one_run() {
+        let timestamps: Vec<f64> = (0..60).map(|i| 5.0 + i as f64 / 30.0).collect();
+
+        let runs = plan_runs(&timestamps, 30.0);
+
+        assert_eq!(runs.len(), 1);
+        assert_eq!(runs[0].start, 5.0);
+        assert_eq!(runs[0].offsets, (0..60).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn test_jumps_start_new_runs() {
+        // Loop back to the start, then leap far ahead
+        let timestamps = [3.0, 3.5, 0.0, 0.5, 20.0];
+
+        let runs = plan_runs(&timestamps, 10.0);
+
+        assert_eq!(runs.len(), 3);
+        assert_eq!(runs[0].offsets, vec![0, 5]);
+        assert_eq!(runs[1].start, 0.0);
+        assert_eq!(runs[2].start, 20.0);
+    }
+
+    #[test]
+    fn test_repeated_timestamps_reuse_frame() {
+        let runs = plan_runs(&[1.0, 1.0, 1.1], 10.0);
+
+        assert_eq!(runs.len(), 1);
+        assert_eq!(runs[0].offsets, vec![0, 0, 1]);
+    }
+
+    #[test]
+    fn test_sampling_rate_from_spacing() {
+        let timestamps: Vec<f64> = (0..10).map(|i| i as f64 / 24.0).collect();
+
+        assert!((sampling_rate(&timestamps, 30.0) - 24.0).abs() < 1e-6);
+        assert_eq!(sampling_rate(&[2.0], 30.0), 30.0);
+    }
+}
@@ -0,0 +1,300 @@
+//! Streaming video encoder
+//!
+//! Frames are piped as raw RGB into a single FFmpeg process over stdin, with
+//! the soundtrack muxed in the same pass. Nothing touches the disk except the
+//! final output, and only the frames currently being written live in memory.
+
+use std::io::{BufWriter, Read, Write};
+use std::path::Path;
+use std::process::{Child, ChildStdin, Command, Stdio};
+use std::thread::JoinHandle;
+
+use tracing::{debug, info};
+
+use crate::error::{CompositorError, Result, VideoError};
+use crate::video::compositor_pure_rust::EncodedVideo;
+use crate::video::processor::ProcessedSegment;
+use crate::video::types::{FieldOrder, Frame, VideoParams};
+
+/// Map the 0-100 quality setting onto an x264 CRF (0-51, lower is better)
+fn quality_to_crf(quality: u8) -> u8 {
+    (51 - ((quality.min(100) as f32 / 100.0) * 51.0) as u8).clamp(0, 51)
+}
+
+/// FFmpeg arguments for encoding raw RGB frames from stdin
+///
+/// When an audio file is given it is muxed in as the second input and the
+/// output stops at whichever stream ends first.
+pub fn ffmpeg_args(params: &VideoParams, audio_path: Option<&Path>, output_path: &Path) -> Vec<String> {
+    let (width, height) = params.resolution;
+
+    let mut args: Vec<String> = vec![
+        "-f".into(), "rawvideo".into(),
+        "-pix_fmt".into(), "rgb24".into(),
+        "-s".into(), format!("{}x{}", width, height),
+        "-r".into(), params.fps.to_string(),
+        "-i".into(), "pipe:0".into(),
+    ];
+
+    if let Some(audio_path) = audio_path {
+        args.extend([
+            "-i".into(), audio_path.display().to_string(),
+            "-map".into(), "0:v".into(),
+            "-map".into(), "1:a".into(),
+            "-c:a".into(), "aac".into(),
+            "-shortest".into(),
+        ]);
+    }
+
+    if params.draft {
+        args.extend(["-preset".into(), "ultrafast".into()]);
+    }
+
+    // Anamorphic and interlaced rasters are tagged so players show them as intended
+    let mut filters = Vec::new();
+    if params.pixel_aspect != (1, 1) {
+        filters.push(format!("setsar={}/{}", params.pixel_aspect.0, params.pixel_aspect.1));
+    }
+    let field_order = match params.field_order {
+        FieldOrder::Progressive => None,
+        FieldOrder::TopFieldFirst => Some(("tff", "tt")),
+        FieldOrder::BottomFieldFirst => Some(("bff", "bb")),
+    };
+    if let Some((field, _)) = field_order {
+        filters.push(format!("setfield={}", field));
+    }
+    if !filters.is_empty() {
+        args.extend(["-vf".into(), filters.join(",")]);
+    }
+    if let Some((_, order)) = field_order {
+        args.extend([
+            "-flags".into(), "+ildct+ilme".into(),
+            "-field_order".into(), order.into(),
+        ]);
+    }
+
+    args.extend([
+        "-c:v".into(), params.codec.clone(),
+        "-pix_fmt".into(), "yuv420p".into(),
+        "-crf".into(), quality_to_crf(params.quality).to_string(),
+        "-loglevel".into(), "error".into(),
+        "-y".into(),
+        output_path.display().to_string(),
+    ]);
+
+    args
+}
+
+/// A running FFmpeg process that accepts frames one at a time
+pub struct StreamingEncoder {
+    child: Child,
+    stdin: Option<BufWriter<ChildStdin>>,
+    stderr: Option<JoinHandle<String>>,
+    output_path: String,
+    params: VideoParams,
+    frames_written: usize,
+}
+
+impl StreamingEncoder {
+    /// Start FFmpeg writing to `output_path`, optionally muxing in `audio_path`
+    pub fn start(params: &VideoParams, audio_path: Option<&Path>, output_path: &Path) -> Result<Self> {
+        let args = ffmpeg_args(params, audio_path, output_path);
+        debug!("Starting FFmpeg: ffmpeg {}", args.join(" "));
+
+        let mut child = Command::new("ffmpeg")
+            .args(&args)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::null())
+            .stderr(Stdio::piped())
+            .spawn()
+            .map_err(|e| VideoError::EncodingFailed {
+                reason: format!("Failed to spawn FFmpeg process: {}", e),
+            })?;
+
+        let stdin = child.stdin.take().map(BufWriter::new);
+
+        // Drain stderr on its own thread so a chatty FFmpeg never blocks on a full pipe
+        let stderr = child.stderr.take().map(|mut stderr| {
+            std::thread::spawn(move || {
+                let mut log = String::new();
+                let _ = stderr.read_to_string(&mut log);
+                log
+            })
+        });
+
+        Ok(Self {
+            child,
+            stdin,
+            stderr,
+            output_path: output_path.display().to_string(),
+            params: params.clone(),
+            frames_written: 0,
+        })
+    }
+
+    /// Number of frames sent to FFmpeg so far
+    pub fn frames_written(&self) -> usize {
+        self.frames_written
+    }
+
+    /// Send one frame; it must match the output resolution
+    pub fn write_frame(&mut self, frame: &Frame) -> Result<()> {
+        let (width, height) = self.params.resolution;
+        if frame.width() != width || frame.height() != height {
+            return Err(VideoError::EncodingFailed {
+                reason: format!(
+                    "Frame {} is {}x{}, expected {}x{}",
+                    self.frames_written, frame.width(), frame.height(), width, height
+                ),
+            }.into());
+        }
+
+        let Some(stdin) = self.stdin.as_mut() else {
+            return Err(VideoError::EncodingFailed {
+                reason: "Encoder already finished".to_string(),
+            }.into());
+        };
+
+        if let Err(e) = stdin.write_all(frame.as_image().as_raw()) {
+            return Err(self.abort(format!("FFmpeg stopped accepting frames: {}", e)));
+        }
+
+        self.frames_written += 1;
+        Ok(())
+    }
+
+    /// Send every frame of a processed segment
+    pub fn write_segment(&mut self, segment: &ProcessedSegment) -> Result<()> {
+        for frame in &segment.frames {
+            self.write_frame(frame)?;
+        }
+        Ok(())
+    }
+
+    /// Close the pipe and wait for FFmpeg to finish writing the file
+    pub fn finish(mut self) -> Result<EncodedVideo> {
+        if let Some(mut stdin) = self.stdin.take() {
+            if let Err(e) = stdin.flush() {
+                return Err(self.abort(format!("FFmpeg stopped accepting frames: {}", e)));
+            }
+        }
+
+        let status = self.child.wait().map_err(|e| VideoError::EncodingFailed {
+            reason: format!("FFmpeg execution failed: {}", e),
+        })?;
+
+        let log = self.stderr_log();
+        if !status.success() {
+            return Err(VideoError::EncodingFailed {
+                reason: format!("FFmpeg failed: {}", log),
+            }.into());
+        }
+
+        let metadata = std::fs::metadata(&self.output_path)?;
+
+        info!("Encoded {} frames to {}", self.frames_written, self.output_path);
+
+        Ok(EncodedVideo {
+            path: self.output_path.clone(),
+            duration: self.params.fps.time_of(self.frames_written as u64),
+            frame_count: self.frames_written,
+            file_size: metadata.len(),
+        })
+    }
+
+    /// Stop FFmpeg after a failed write and report what it said
+    fn abort(&mut self, reason: String) -> CompositorError {
+        self.stdin = None;
+        let _ = self.child.wait();
+        let log = self.stderr_log();
+
+        VideoError::EncodingFailed {
+            reason: if log.trim().is_empty() { reason } else { format!("{}: {}", reason, log.trim()) },
+        }.into()
+    }
+
+    fn stderr_log(&mut self) -> String {
+        self.stderr
+            .take()
+            .and_then(|handle| handle.join().ok())
+            .unwrap_or_default()
+    }
+}
+
+impl Drop for StreamingEncoder {
+    fn drop(&mut self) {
+        // Abandoned mid-stream: don't leave FFmpeg waiting on stdin
+        if self.stdin.take().is_some() {
+            let _ = self.child.kill();
+            let _ = self.child.wait();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::video::{FrameRate, OutputPreset};
+
+    #[test]
+    fn test_args_stream_rgb_from_stdin() {
+        let params = VideoParams {
+            fps: FrameRate::PAL,
+            resolution: (640, 360),
+            codec: "h264".to_string(),
+            quality: 100,
+            ..VideoParams::default()
+        };
+
+        let args = ffmpeg_args(&params, None, Path::new("out.mp4"));
+        let joined = args.join(" ");
+
+        assert!(joined.starts_with("-f rawvideo -pix_fmt rgb24 -s 640x360 -r 25 -i pipe:0"));
+        assert!(joined.contains("-crf 0"));
+        assert!(!joined.contains("-c:a"));
+        assert!(!joined.contains("ultrafast"));
+        assert!(!joined.contains("-vf"));
+        assert!(!joined.contains("ildct"));
+        assert_eq!(args.last().map(String::as_str), Some("out.mp4"));
+    }
+
+    #[test]
+    fn test_args_keep_ntsc_rate_exact() {
+        let params = VideoParams { fps: FrameRate::NTSC, ..VideoParams::default() };
+        let joined = ffmpeg_args(&params, None, Path::new("out.mp4")).join(" ");
+
+        assert!(joined.contains("-r 30000/1001 -i pipe:0"));
+    }
+
+    #[test]
+    fn test_args_encode_broadcast_rasters_interlaced() {
+        let mut params = VideoParams::default();
+
+        OutputPreset::Ntsc.apply(&mut params);
+        let joined = ffmpeg_args(&params, None, Path::new("out.mp4")).join(" ");
+        assert!(joined.contains("-s 720x480 -r 30000/1001"));
+        assert!(joined.contains("-vf setsar=8/9,setfield=bff -flags +ildct+ilme -field_order bb"));
+
+        OutputPreset::Pal.apply(&mut params);
+        let joined = ffmpeg_args(&params, None, Path::new("out.mp4")).join(" ");
+        assert!(joined.contains("-vf setsar=16/15,setfield=tff -flags +ildct+ilme -field_order tt"));
+    }
+
+    #[test]
+    fn test_args_mux_audio_in_same_pass() {
+        let args = ffmpeg_args(&VideoParams::default(), Some(Path::new("song.wav")), Path::new("out.mp4"));
+        let joined = args.join(" ");
+
+        assert!(joined.contains("-i pipe:0 -i song.wav -map 0:v -map 1:a -c:a aac -shortest"));
+        assert_eq!(args.iter().filter(|a| *a == "-i").count(), 2);
+    }
+
+    #[test]
+    fn test_draft_uses_fastest_preset() {
+        let params = VideoParams { draft: true, ..VideoParams::default() };
+
+        let args = ffmpeg_args(&params, None, Path::new("preview.mp4"));
+
+        assert!(args.join(" ").contains("-preset ultrafast"));
+    }
+}
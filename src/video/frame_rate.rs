@@ -0,0 +1,235 @@
+//! Exact frame rates
+//!
+//! Broadcast rates such as 29.97 are really 30000/1001 frames per second and
+//! can't be written exactly as a float. Keeping the rate as a fraction lets
+//! frame times, encoder settings and exported timecode agree to the frame
+//! over a whole song.
+
+use std::fmt;
+
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+/// Frame rate as a reduced fraction of frames per second
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct FrameRate {
+    num: u32,
+    den: u32,
+}
+
+fn gcd(a: u32, b: u32) -> u32 {
+    if b == 0 { a } else { gcd(b, a % b) }
+}
+
+impl FrameRate {
+    /// 24 fps cinema
+    pub const FILM: FrameRate = FrameRate { num: 24, den: 1 };
+
+    /// 23.976 fps, film transferred to NTSC video
+    pub const FILM_NTSC: FrameRate = FrameRate { num: 24000, den: 1001 };
+
+    /// 25 fps PAL/SECAM video
+    pub const PAL: FrameRate = FrameRate { num: 25, den: 1 };
+
+    /// 29.97 fps NTSC video
+    pub const NTSC: FrameRate = FrameRate { num: 30000, den: 1001 };
+
+    /// `num / den` frames per second, reduced; `None` if either part is zero
+    pub fn new(num: u32, den: u32) -> Option<Self> {
+        if num == 0 || den == 0 {
+            return None;
+        }
+        let divisor = gcd(num, den);
+        Some(Self { num: num / divisor, den: den / divisor })
+    }
+
+    /// Whole frames per second
+    pub fn integer(fps: u32) -> Self {
+        Self { num: fps.max(1), den: 1 }
+    }
+
+    /// Closest rate to `fps`, recognising the NTSC x/1.001 family
+    ///
+    /// Rates within a thousandth of a frame of an integer or an NTSC rate are
+    /// taken to be exactly that rate; anything else keeps millihertz precision.
+    pub fn from_f64(fps: f64) -> Self {
+        if !(fps.is_finite() && fps > 0.0) {
+            return Self::integer(1);
+        }
+
+        let nearest = fps.round();
+        if (fps - nearest).abs() < 1e-3 {
+            return Self::integer(nearest as u32);
+        }
+
+        let ntsc = (fps * 1.001).round();
+        if (fps - ntsc / 1.001).abs() < 1e-3 {
+            return Self::new(ntsc as u32 * 1000, 1001).unwrap();
+        }
+
+        Self::new((fps * 1000.0).round() as u32, 1000).unwrap_or(Self::integer(1))
+    }
+
+    /// Parse `"30000/1001"`, `"29.97"`, `"30"` or a broadcast name (ntsc, pal, film)
+    pub fn from_name(name: &str) -> Option<Self> {
+        let name = name.trim().to_lowercase();
+        match name.as_str() {
+            "ntsc" => return Some(Self::NTSC),
+            "pal" | "secam" => return Some(Self::PAL),
+            "film" => return Some(Self::FILM),
+            _ => {}
+        }
+
+        match name.split_once('/') {
+            Some((num, den)) => Self::new(num.trim().parse().ok()?, den.trim().parse().ok()?),
+            None => name.parse::<f64>().ok().filter(|f| *f > 0.0).map(Self::from_f64),
+        }
+    }
+
+    /// Numerator of the fraction
+    pub fn num(&self) -> u32 {
+        self.num
+    }
+
+    /// Denominator of the fraction
+    pub fn den(&self) -> u32 {
+        self.den
+    }
+
+    /// Approximate frames per second
+    pub fn as_f64(&self) -> f64 {
+        self.num as f64 / self.den as f64
+    }
+
+    /// Whether this is a whole number of frames per second
+    pub fn is_integer(&self) -> bool {
+        self.den == 1
+    }
+
+    /// Frames per second rounded to an integer, as used for timecode
+    pub fn timebase(&self) -> u32 {
+        ((self.num as f64 / self.den as f64).round() as u32).max(1)
+    }
+
+    /// Start time of frame `index` in seconds
+    ///
+    /// Computed from the index each time, so there is no accumulated error.
+    pub fn time_of(&self, index: u64) -> f64 {
+        (index as u128 * self.den as u128) as f64 / self.num as f64
+    }
+
+    /// Index of the frame boundary nearest to `time`
+    pub fn frame_at(&self, time: f64) -> u64 {
+        (time.max(0.0) * self.num as f64 / self.den as f64).round() as u64
+    }
+
+    /// The lower of two rates
+    pub fn min(self, other: Self) -> Self {
+        if (self.num as u64 * other.den as u64) <= (other.num as u64 * self.den as u64) {
+            self
+        } else {
+            other
+        }
+    }
+}
+
+impl Default for FrameRate {
+    fn default() -> Self {
+        Self::integer(30)
+    }
+}
+
+/// `30` for whole rates, `30000/1001` otherwise; the form ffmpeg accepts for `-r`
+impl fmt::Display for FrameRate {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.is_integer() {
+            write!(f, "{}", self.num)
+        } else {
+            write!(f, "{}/{}", self.num, self.den)
+        }
+    }
+}
+
+/// Whole rates are written as numbers, others as `"num/den"` strings
+impl Serialize for FrameRate {
+    fn serialize<S: Serializer>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error> {
+        if self.is_integer() {
+            serializer.serialize_u32(self.num)
+        } else {
+            serializer.serialize_str(&self.to_string())
+        }
+    }
+}
+
+/// Accepts plain numbers (`30`, `29.97`) as well as strings (`"30000/1001"`, `"ntsc"`)
+impl<'de> Deserialize<'de> for FrameRate {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> std::result::Result<Self, D::Error> {
+        #[derive(Deserialize)]
+        #[serde(untagged)]
+        enum Repr {
+            Number(f64),
+            Text(String),
+        }
+
+        match Repr::deserialize(deserializer)? {
+            Repr::Number(fps) if fps > 0.0 => Ok(Self::from_f64(fps)),
+            Repr::Number(fps) => Err(serde::de::Error::custom(format!("invalid frame rate {}", fps))),
+            Repr::Text(text) => Self::from_name(&text)
+                .ok_or_else(|| serde::de::Error::custom(format!("invalid frame rate {:?}", text))),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_rates() {
+        assert_eq!(FrameRate::from_f64(29.97), FrameRate::NTSC);
+        assert_eq!(FrameRate::from_f64(23.976), FrameRate::FILM_NTSC);
+        assert_eq!(FrameRate::from_f64(59.94), FrameRate::new(60000, 1001).unwrap());
+        assert_eq!(FrameRate::from_f64(25.0), FrameRate::PAL);
+        assert_eq!(FrameRate::from_f64(12.5), FrameRate::new(25, 2).unwrap());
+
+        assert_eq!(FrameRate::from_name("30000/1001"), Some(FrameRate::NTSC));
+        assert_eq!(FrameRate::from_name("60/2"), Some(FrameRate::integer(30)));
+        assert_eq!(FrameRate::from_name("PAL"), Some(FrameRate::PAL));
+        assert_eq!(FrameRate::from_name("0/1"), None);
+        assert_eq!(FrameRate::from_name("fast"), None);
+
+        assert_eq!(FrameRate::NTSC.to_string(), "30000/1001");
+        assert_eq!(FrameRate::PAL.to_string(), "25");
+        assert_eq!(FrameRate::NTSC.timebase(), 30);
+    }
+
+    #[test]
+    fn test_ntsc_frame_times_are_exact() {
+        let rate = FrameRate::NTSC;
+
+        // One hour of NTSC is 107892 frames and 3.6 seconds of drop
+        assert_eq!(rate.frame_at(3600.0), 107_892);
+        assert_eq!(rate.time_of(107_892), 3599.9964);
+        assert_eq!(rate.frame_at(rate.time_of(1_000_001)), 1_000_001);
+        assert_eq!(rate.min(FrameRate::integer(12)), FrameRate::integer(12));
+        assert_eq!(FrameRate::PAL.min(rate), FrameRate::PAL);
+    }
+
+    #[test]
+    fn test_serde_accepts_numbers_and_fractions() {
+        #[derive(Serialize, Deserialize)]
+        struct Params {
+            fps: FrameRate,
+        }
+
+        let parsed: Params = toml::from_str("fps = 30").unwrap();
+        assert_eq!(parsed.fps, FrameRate::integer(30));
+        let parsed: Params = toml::from_str("fps = 29.97").unwrap();
+        assert_eq!(parsed.fps, FrameRate::NTSC);
+        let parsed: Params = toml::from_str("fps = \"24000/1001\"").unwrap();
+        assert_eq!(parsed.fps, FrameRate::FILM_NTSC);
+        assert!(toml::from_str::<Params>("fps = 0").is_err());
+
+        assert_eq!(serde_json::to_string(&Params { fps: FrameRate::NTSC }).unwrap(), r#"{"fps":"30000/1001"}"#);
+        assert_eq!(serde_json::to_string(&Params { fps: FrameRate::PAL }).unwrap(), r#"{"fps":25}"#);
+    }
+}
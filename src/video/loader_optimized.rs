@@ -1,14 +1,15 @@
-// src/video/loader_optimized.rs - Memory-efficient version
+// src/video/loader_optimized.rs - Streaming decode version
 
 use std::path::Path;
 use std::process::Command;
 use std::collections::HashMap;
 
-use rayon::prelude::*;
-use image::{ImageBuffer, Rgb, RgbImage, GenericImageView};
-use tracing::{debug, info, warn, error};
+use image::GenericImageView;
+use tracing::{info, warn};
 
 use crate::error::{VideoError, Result};
+use crate::video::decoder::{self, FrameDecoder};
+use crate::video::reframe;
 use crate::video::types::{Frame, VideoClip};
 
 #[derive(Debug, Clone)]
@@ -23,26 +24,12 @@ pub struct VideoMetadata {
 
 pub struct VideoLoader {
     metadata_cache: HashMap<String, VideoMetadata>,
-    temp_counter: u32,
-    max_parallel_extractions: usize,
+    /// Output size frames will be fitted into; decoding scales down towards it
+    target_resolution: Option<(u32, u32)>,
 }
 
 impl VideoLoader {
     pub fn new() -> Result<Self> {
-        let cpu_count = num_cpus::get();
-
-        // **MEMORY OPTIMIZATION** - Limit parallel extractions based on available memory
-        let max_parallel = if cfg!(target_os = "macos") {
-            // For macOS with VideoToolbox, we can be more aggressive but still conservative
-            (cpu_count / 2).max(2).min(8) // 2-8 parallel extractions
-        } else {
-            // For other systems, be more conservative
-            (cpu_count / 4).max(1).min(4) // 1-4 parallel extractions
-        };
-
-        info!("Detected {} CPU cores, using {} parallel extractions for memory efficiency", 
-              cpu_count, max_parallel);
-
         if cfg!(target_os = "macos") {
             info!("macOS detected - will use VideoToolbox hardware acceleration");
         }
@@ -55,11 +42,10 @@ impl VideoLoader {
             })?;
 
         if output.status.success() {
-            info!("Initialized memory-optimized video loader with external FFmpeg");
+            info!("Initialized streaming video loader with external FFmpeg");
             Ok(Self {
                 metadata_cache: HashMap::new(),
-                temp_counter: 0,
-                max_parallel_extractions: max_parallel,
+                target_resolution: None,
             })
         } else {
             Err(VideoError::LoadFailed {
@@ -68,6 +54,11 @@ impl VideoLoader {
         }
     }
 
+    /// Decode frames no larger than needed to cover this output size
+    pub fn set_target_resolution(&mut self, resolution: (u32, u32)) {
+        self.target_resolution = Some(resolution);
+    }
+
     pub fn load_metadata<P: AsRef<Path>>(&mut self, path: P) -> Result<VideoMetadata> {
         let path_str = path.as_ref().display().to_string();
 
@@ -103,7 +94,7 @@ impl VideoLoader {
 
     fn load_video_metadata_ffprobe(&self, path: &Path) -> Result<VideoMetadata> {
         let output = Command::new("ffprobe")
-            .args(&[
+            .args([
                 "-v", "quiet",
                 "-print_format", "json",
                 "-show_streams",
@@ -159,7 +150,10 @@ impl VideoLoader {
         }.into())
     }
 
-    /// **MEMORY-OPTIMIZED VERSION** - Process frames in smaller batches to avoid memory exhaustion
+    /// Decode the frames at the given times, opening the clip once per sequential run
+    ///
+    /// Frames are read from a single FFmpeg stream at the rate implied by the
+    /// timestamp spacing; FFmpeg is only restarted when the times jump.
     pub fn extract_frames_at_times<P: AsRef<Path>>(&mut self, path: P, timestamps: &[f64]) -> Result<Vec<Frame>> {
         if Self::is_image_file(path.as_ref()) {
             let base_frame = self.load_image_as_frame(path.as_ref())?;
@@ -170,51 +164,36 @@ impl VideoLoader {
             return Ok(Vec::new());
         }
 
-        let path_str = path.as_ref().display().to_string();
+        let metadata = self.load_metadata(path.as_ref())?;
+        let source_size = (metadata.width.max(1), metadata.height.max(1));
+        let frame_size = match self.target_resolution {
+            Some(target) => reframe::decode_size(source_size, target),
+            None => source_size,
+        };
 
-        // **BATCH PROCESSING** - Process frames in smaller batches to manage memory
-        let batch_size = 100; // Process 50 frames at a time
-        let total_frames = timestamps.len();
+        let fps = decoder::sampling_rate(timestamps, metadata.fps);
+        let runs = decoder::plan_runs(timestamps, fps);
 
-        info!("🚀 Extracting {} frames in batches of {} from {}", 
-              total_frames, batch_size, path.as_ref().display());
+        info!("🚀 Extracting {} frames in {} decode run(s) at {:.2} fps, {}x{} from {}",
+              timestamps.len(), runs.len(), fps, frame_size.0, frame_size.1, path.as_ref().display());
 
-        let mut all_frames = Vec::with_capacity(total_frames);
-        let mut total_success = 0;
+        let mut all_frames = Vec::with_capacity(timestamps.len());
         let mut total_failed = 0;
 
-        // Process in batches
-        for (batch_num, timestamp_batch) in timestamps.chunks(batch_size).enumerate() {
-            info!("Processing batch {}/{} ({} frames)...", 
-                  batch_num + 1, 
-                  (total_frames + batch_size - 1) / batch_size,
-                  timestamp_batch.len());
-
-            match self.extract_batch(&path_str, timestamp_batch, batch_num) {
-                Ok((frames, success_count, failed_count)) => {
-                    all_frames.extend(frames);
-                    total_success += success_count;
-                    total_failed += failed_count;
-                }
+        for run in &runs {
+            match FrameDecoder::read_run(path.as_ref(), run, fps, frame_size) {
+                Ok(frames) => all_frames.extend(frames),
                 Err(e) => {
-                    warn!("Batch {} failed: {}, using placeholders", batch_num + 1, e);
-                    // Add placeholder frames for the entire batch
-                    for _ in 0..timestamp_batch.len() {
-                        all_frames.push(Frame::new_filled(1920, 1080, [64, 64, 64]));
-                    }
-                    total_failed += timestamp_batch.len();
+                    warn!("Decoding from {:.2}s failed: {}, using placeholders", run.start, e);
+                    let (width, height) = frame_size;
+                    all_frames.extend(
+                        std::iter::repeat_n(Frame::new_filled(width, height, [64, 64, 64]), run.offsets.len())
+                    );
+                    total_failed += run.offsets.len();
                 }
             }
-
-            // **MEMORY CLEANUP** - Force garbage collection between batches
-            if batch_num % 3 == 2 {
-                // Every 3 batches, try to encourage memory cleanup
-                info!("Encouraging memory cleanup after batch {}...", batch_num + 1);
-                std::thread::sleep(std::time::Duration::from_millis(100));
-            }
         }
 
-        info!("✅ All batches complete: {}/{} frames successful", total_success, total_frames);
         if total_failed > 0 {
             warn!("⚠️  {} frames failed, using placeholders", total_failed);
         }
@@ -222,135 +201,6 @@ impl VideoLoader {
         Ok(all_frames)
     }
 
-    /// Extract a single batch of frames with controlled parallelism
-    fn extract_batch(&self, path_str: &str, timestamps: &[f64], batch_num: usize) -> Result<(Vec<Frame>, usize, usize)> {
-        // Create temp directory for this batch
-        let temp_dir = format!("/tmp/retro_compositor_batch_{}_{}", std::process::id(), batch_num);
-        std::fs::create_dir_all(&temp_dir).map_err(|_| VideoError::FrameProcessingFailed {
-            reason: "Cannot create temp directory".to_string(),
-        })?;
-
-        // **CONTROLLED PARALLELISM** - Process all timestamps in controlled parallel chunks
-        let chunk_size = self.max_parallel_extractions;
-        let mut all_results = Vec::new();
-
-        // Process timestamps in smaller parallel chunks
-        for (chunk_idx, timestamp_chunk) in timestamps.chunks(chunk_size).enumerate() {
-            let chunk_results: Vec<(usize, Result<Frame>)> = timestamp_chunk
-                .par_iter()
-                .enumerate()
-                .map(|(rel_idx, &timestamp)| {
-                    let abs_idx = chunk_idx * chunk_size + rel_idx;
-                    let temp_frame = format!("{}/frame_{:06}.png", temp_dir, abs_idx);
-
-                    // Create FFmpeg command with proper argument order
-                    let mut cmd = Command::new("ffmpeg");
-
-                    // Hardware acceleration BEFORE input (macOS only)
-                    if cfg!(target_os = "macos") {
-                        cmd.args(&["-hwaccel", "videotoolbox"]);
-                    }
-
-                    // Add seek and input
-                    cmd.args(&[
-                        "-ss", &timestamp.to_string(),
-                        "-i", path_str,
-                    ]);
-
-                    // Output options - **MEMORY OPTIMIZATION**: Use lower quality for intermediate frames
-                    cmd.args(&[
-                        "-vframes", "1",
-                        "-f", "image2",
-                        "-q:v", "5", // Slightly lower quality to reduce memory usage
-                        "-s", "1920x1080", // **RESIZE TO STANDARD HD** to save memory
-                        "-y",
-                        &temp_frame
-                    ]);
-
-                    // Execute FFmpeg
-                    let result = match cmd.output() {
-                        Ok(output) if output.status.success() => {
-                            if Path::new(&temp_frame).exists() {
-                                if let Ok(metadata) = std::fs::metadata(&temp_frame) {
-                                    if metadata.len() > 500 { // Lower threshold
-                                        match image::open(&temp_frame) {
-                                            Ok(img) => {
-                                                let rgb_image = img.to_rgb8();
-                                                let frame = Frame::new(rgb_image);
-                                                // Clean up immediately
-                                                let _ = std::fs::remove_file(&temp_frame);
-                                                Ok(frame)
-                                            }
-                                            Err(e) => Err(VideoError::FrameProcessingFailed {
-                                                reason: format!("Image load failed: {}", e),
-                                            }.into())
-                                        }
-                                    } else {
-                                        Err(VideoError::FrameProcessingFailed {
-                                            reason: "Frame file too small".to_string(),
-                                        }.into())
-                                    }
-                                } else {
-                                    Err(VideoError::FrameProcessingFailed {
-                                        reason: "Cannot read frame metadata".to_string(),
-                                    }.into())
-                                }
-                            } else {
-                                Err(VideoError::FrameProcessingFailed {
-                                    reason: "Frame file not created".to_string(),
-                                }.into())
-                            }
-                        }
-                        Ok(output) => {
-                            let stderr = String::from_utf8_lossy(&output.stderr);
-                            Err(VideoError::FrameProcessingFailed {
-                                reason: format!("FFmpeg failed: {}", stderr),
-                            }.into())
-                        }
-                        Err(e) => Err(VideoError::FrameProcessingFailed {
-                            reason: format!("FFmpeg execution failed: {}", e),
-                        }.into())
-                    };
-
-                    (abs_idx, result)
-                })
-                .collect();
-
-            // Collect results from this chunk
-            all_results.extend(chunk_results);
-
-            // Small delay between chunks to prevent overwhelming the system
-            if chunk_idx < (timestamps.len() + chunk_size - 1) / chunk_size - 1 {
-                std::thread::sleep(std::time::Duration::from_millis(50));
-            }
-        }
-
-        // Sort results by index and process them
-        all_results.sort_by_key(|(i, _)| *i);
-
-        let mut frames = Vec::with_capacity(timestamps.len());
-        let mut success_count = 0;
-        let mut error_count = 0;
-
-        for (_, result) in all_results {
-            match result {
-                Ok(frame) => {
-                    frames.push(frame);
-                    success_count += 1;
-                }
-                Err(_) => {
-                    error_count += 1;
-                    frames.push(Frame::new_filled(1920, 1080, [64, 64, 64]));
-                }
-            }
-        }
-
-        // Clean up temp directory
-        let _ = std::fs::remove_dir_all(&temp_dir);
-
-        Ok((frames, success_count, error_count))
-    }
-
     fn load_image_as_frame(&self, path: &Path) -> Result<Frame> {
         let image = image::open(path).map_err(|e| VideoError::LoadFailed {
             path: format!("{}: {}", path.display(), e),
@@ -515,8 +365,7 @@ impl Default for VideoLoader {
     fn default() -> Self {
         Self::new().unwrap_or_else(|_| Self {
             metadata_cache: HashMap::new(),
-            temp_counter: 0,
-            max_parallel_extractions: 4,
+            target_resolution: None,
         })
     }
 }
\ No newline at end of file
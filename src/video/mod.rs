@@ -1,12 +1,25 @@
 //! Video Processing Module
 
 pub mod types;
+pub mod clock;
+pub mod frame_rate;
 pub mod processor;
 pub mod loader_optimized;
 pub mod compositor_pure_rust;
+pub mod decoder;
+pub mod encoder;
+pub mod transitions;
+pub mod reframe;
+pub mod saliency;
+pub mod presets;
 
 
-pub use types::{Frame, VideoClip, VideoParams, VideoSequence};
+pub use types::{FieldOrder, FitMode, Frame, VideoClip, VideoParams, VideoSequence};
+pub use clock::FrameClock;
+pub use frame_rate::FrameRate;
 pub use processor::{VideoProcessor, ProcessedSegment};
 pub use loader_optimized::{VideoLoader, VideoMetadata};
 pub use compositor_pure_rust::{VideoCompositor, EncodedVideo};
+pub use encoder::StreamingEncoder;
+pub use transitions::TransitionType;
+pub use presets::OutputPreset;
@@ -0,0 +1,214 @@
+//! Named output formats for common delivery targets
+//!
+//! Each preset fixes the output resolution and how source clips are reframed
+//! into it, so landscape footage is cropped around its subject on vertical and
+//! square outputs instead of being squashed. The broadcast presets also fix
+//! the frame rate and produce true SD deliverables: a 720-pixel-wide raster of
+//! non-square pixels shown at 4:3, encoded interlaced.
+
+use serde::{Deserialize, Serialize};
+
+use crate::video::frame_rate::FrameRate;
+use crate::video::types::{FieldOrder, FitMode, VideoParams};
+
+/// Output frame shape
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum OutputPreset {
+    /// 16:9 1920x1080
+    Landscape,
+
+    /// 9:16 1080x1920 for short-form vertical platforms
+    Vertical,
+
+    /// 1:1 1080x1080
+    Square,
+
+    /// 4:5 1080x1350 for feed posts
+    Portrait,
+
+    /// 4:3 1440x1080, the shape of a true retro TV
+    Retro,
+
+    /// NTSC 480i 4:3 at 29.97 fps (720x480, 8:9 pixels, bottom field first)
+    Ntsc,
+
+    /// PAL 576i 4:3 at 25 fps (720x576, 16:15 pixels, top field first)
+    Pal,
+}
+
+impl OutputPreset {
+    /// All available presets
+    pub const ALL: [OutputPreset; 7] = [
+        OutputPreset::Landscape,
+        OutputPreset::Vertical,
+        OutputPreset::Square,
+        OutputPreset::Portrait,
+        OutputPreset::Retro,
+        OutputPreset::Ntsc,
+        OutputPreset::Pal,
+    ];
+
+    /// Parse a preset from its name or aspect ratio (e.g. "vertical" or "9:16")
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.to_lowercase().as_str() {
+            "landscape" | "16:9" | "widescreen" => Some(Self::Landscape),
+            "vertical" | "9:16" | "shorts" | "reels" | "tiktok" => Some(Self::Vertical),
+            "square" | "1:1" => Some(Self::Square),
+            "portrait" | "4:5" => Some(Self::Portrait),
+            "retro" | "4:3" => Some(Self::Retro),
+            "ntsc" | "480i" => Some(Self::Ntsc),
+            "pal" | "576i" => Some(Self::Pal),
+            _ => None,
+        }
+    }
+
+    /// Canonical name of the preset
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Landscape => "landscape",
+            Self::Vertical => "vertical",
+            Self::Square => "square",
+            Self::Portrait => "portrait",
+            Self::Retro => "retro",
+            Self::Ntsc => "ntsc",
+            Self::Pal => "pal",
+        }
+    }
+
+    /// Aspect ratio label
+    pub fn aspect(&self) -> &'static str {
+        match self {
+            Self::Landscape => "16:9",
+            Self::Vertical => "9:16",
+            Self::Square => "1:1",
+            Self::Portrait => "4:5",
+            Self::Retro | Self::Ntsc | Self::Pal => "4:3",
+        }
+    }
+
+    /// Stored output resolution (width, height)
+    ///
+    /// The broadcast presets store 720 anamorphic pixels per line; see `pixel_aspect`.
+    pub fn resolution(&self) -> (u32, u32) {
+        match self {
+            Self::Landscape => (1920, 1080),
+            Self::Vertical => (1080, 1920),
+            Self::Square => (1080, 1080),
+            Self::Portrait => (1080, 1350),
+            Self::Retro => (1440, 1080),
+            Self::Ntsc => (720, 480),
+            Self::Pal => (720, 576),
+        }
+    }
+
+    /// Shape of one stored pixel (width, height) so the full raster shows at `aspect`
+    pub fn pixel_aspect(&self) -> (u32, u32) {
+        match self {
+            Self::Ntsc => (8, 9),
+            Self::Pal => (16, 15),
+            _ => (1, 1),
+        }
+    }
+
+    /// Scan type of the encoded output
+    pub fn field_order(&self) -> FieldOrder {
+        match self {
+            Self::Ntsc => FieldOrder::BottomFieldFirst,
+            Self::Pal => FieldOrder::TopFieldFirst,
+            _ => FieldOrder::Progressive,
+        }
+    }
+
+    /// How mostly-landscape source clips are reframed into this shape
+    pub fn fit_mode(&self) -> FitMode {
+        match self {
+            Self::Landscape => FitMode::CenterCrop,
+            Self::Vertical | Self::Square | Self::Portrait | Self::Retro | Self::Ntsc | Self::Pal => {
+                FitMode::SmartCrop
+            }
+        }
+    }
+
+    /// Frame rate the preset requires, if any
+    pub fn frame_rate(&self) -> Option<FrameRate> {
+        match self {
+            Self::Ntsc => Some(FrameRate::NTSC),
+            Self::Pal => Some(FrameRate::PAL),
+            _ => None,
+        }
+    }
+
+    /// Set the raster, fit mode and any fixed frame rate of `params` to this preset's
+    pub fn apply(&self, params: &mut VideoParams) {
+        params.resolution = self.resolution();
+        params.pixel_aspect = self.pixel_aspect();
+        params.field_order = self.field_order();
+        params.fit_mode = self.fit_mode();
+        if let Some(rate) = self.frame_rate() {
+            params.fps = rate;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_presets_parse_by_name_and_aspect() {
+        for preset in OutputPreset::ALL {
+            assert_eq!(OutputPreset::from_name(preset.name()), Some(preset));
+            assert_eq!(OutputPreset::from_name(preset.aspect()).map(|p| p.aspect()), Some(preset.aspect()));
+        }
+        assert_eq!(OutputPreset::from_name("576i"), Some(OutputPreset::Pal));
+        assert_eq!(OutputPreset::from_name("480p"), None);
+        assert_eq!(OutputPreset::from_name("TikTok"), Some(OutputPreset::Vertical));
+        assert_eq!(OutputPreset::from_name("21:9"), None);
+    }
+
+    #[test]
+    fn test_resolution_matches_aspect() {
+        for preset in OutputPreset::ALL {
+            let (width, height) = preset.resolution();
+            let (par_x, par_y) = preset.pixel_aspect();
+            let (width, height) = (width * par_x, height * par_y);
+            let (a, b) = preset.aspect().split_once(':').unwrap();
+            let (a, b): (u32, u32) = (a.parse().unwrap(), b.parse().unwrap());
+
+            assert_eq!(width * b, height * a, "{} is not {}", preset.name(), preset.aspect());
+        }
+    }
+
+    #[test]
+    fn test_apply_sets_reframing() {
+        let mut params = VideoParams::default();
+        OutputPreset::Vertical.apply(&mut params);
+
+        assert_eq!(params.resolution, (1080, 1920));
+        assert_eq!(params.fit_mode, FitMode::SmartCrop);
+        assert_eq!(params.fps, VideoParams::default().fps);
+    }
+
+    #[test]
+    fn test_broadcast_presets_fix_frame_rate() {
+        let mut params = VideoParams::default();
+
+        OutputPreset::Ntsc.apply(&mut params);
+        assert_eq!(params.resolution, (720, 480));
+        assert_eq!(params.display_size(), (640, 480));
+        assert_eq!(params.field_order, FieldOrder::BottomFieldFirst);
+        assert_eq!(params.fps, FrameRate::NTSC);
+
+        OutputPreset::Pal.apply(&mut params);
+        assert_eq!(params.resolution, (720, 576));
+        assert_eq!(params.display_size(), (768, 576));
+        assert_eq!(params.field_order, FieldOrder::TopFieldFirst);
+        assert_eq!(params.fps, FrameRate::PAL);
+
+        // Leaving a broadcast preset goes back to square, progressive pixels
+        OutputPreset::Retro.apply(&mut params);
+        assert_eq!(params.pixel_aspect, (1, 1));
+        assert!(!params.field_order.is_interlaced());
+    }
+}
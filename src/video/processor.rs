@@ -1,22 +1,28 @@
 // src/video/processor.rs - Enhanced for smoother motion
 
 use std::collections::HashMap;
+use std::ops::Range;
 
 use rayon::prelude::*;
-use tracing::{debug, info, warn};
+use tracing::{debug, info};
 
 use crate::error::{VideoError, Result};
-use crate::styles::{Style, StyleConfig};
-use crate::video::types::{Frame, VideoClip, VideoParams};
+use crate::styles::{FrameContext, MusicContext, Style, StyleConfig};
+use crate::video::{reframe, saliency};
+use crate::video::clock::FrameClock;
+use crate::video::types::{FitMode, Frame, VideoClip, VideoParams};
 use crate::video::loader_optimized::{VideoLoader, VideoMetadata};
-use crate::composition::engine::CompositionTimeline;
+use crate::composition::engine::{CompositionTimeline, SegmentOverrides};
 
 pub struct VideoProcessor {
     loader: VideoLoader,
     frame_cache: HashMap<String, Vec<CachedFrame>>,
     target_params: VideoParams,
+    music: MusicContext,
+    seed: u64,
 }
 
+#[allow(dead_code)]
 #[derive(Clone)]
 struct CachedFrame {
     frame: Frame,
@@ -25,6 +31,8 @@ struct CachedFrame {
 
 #[derive(Debug, Clone)]
 pub struct ProcessedSegment {
+    /// Index of the timeline segment this was rendered from
+    pub segment_index: usize,
     pub start_time: f64,
     pub end_time: f64,
     pub clip_id: u32,
@@ -34,13 +42,30 @@ pub struct ProcessedSegment {
 
 impl VideoProcessor {
     pub fn new(target_params: VideoParams) -> Result<Self> {
+        let mut loader = VideoLoader::new()?;
+        loader.set_target_resolution(target_params.resolution);
+
         Ok(Self {
-            loader: VideoLoader::new()?,
+            loader,
             frame_cache: HashMap::new(),
             target_params,
+            music: MusicContext::default(),
+            seed: 0,
         })
     }
 
+    /// Let styles react to the beats, energy and sections of this song
+    pub fn with_music(mut self, music: MusicContext) -> Self {
+        self.music = music;
+        self
+    }
+
+    /// Seed that styles draw their noise from
+    pub fn with_seed(mut self, seed: u64) -> Self {
+        self.seed = seed;
+        self
+    }
+
     pub async fn process_timeline(
         &mut self,
         timeline: &CompositionTimeline,
@@ -49,12 +74,48 @@ impl VideoProcessor {
         style_config: &StyleConfig,
         total_duration: f64,
     ) -> Result<Vec<ProcessedSegment>> {
+        let mut processed_segments = Vec::new();
+
+        self.process_timeline_streaming(
+            timeline,
+            video_clips,
+            style,
+            style_config,
+            total_duration,
+            |segment| {
+                processed_segments.push(segment);
+                Ok(())
+            },
+        ).await?;
+
+        Ok(processed_segments)
+    }
+
+    /// Process the timeline one segment at a time, handing each to `on_segment`
+    ///
+    /// Only the segment being processed is held in memory, so the caller can
+    /// encode it and drop it before the next one is decoded.
+    pub async fn process_timeline_streaming<F>(
+        &mut self,
+        timeline: &CompositionTimeline,
+        video_clips: &[VideoClip],
+        style: &dyn Style,
+        style_config: &StyleConfig,
+        total_duration: f64,
+        mut on_segment: F,
+    ) -> Result<()>
+    where
+        F: FnMut(ProcessedSegment) -> Result<()>,
+    {
         info!("Processing {} timeline segments with {} style",
               timeline.cuts.len(), style.name());
 
-        let mut processed_segments = Vec::new();
+        // Segments cover exact ranges of one global frame count, so per-segment
+        // rounding can't accumulate into drift against the audio
+        let clock = FrameClock::new(self.target_params.fps);
+        let ranges = clock.segment_ranges(&timeline.cuts, total_duration);
 
-        for (i, &cut_time) in timeline.cuts.iter().enumerate() {
+        for (i, frames) in ranges.into_iter().enumerate() {
             let clip_id = timeline.clip_assignments.get(i).copied().unwrap_or(1);
 
             let clip = video_clips.iter()
@@ -63,64 +124,86 @@ impl VideoProcessor {
                     path: format!("clip_{}", clip_id),
                 })?;
 
-            let segment_end = timeline.cuts.get(i + 1).copied().unwrap_or(total_duration);
-            let segment_duration = segment_end - cut_time;
+            if frames.is_empty() {
+                debug!("Skipping segment {}: shorter than a frame", i);
+                continue;
+            }
+
+            debug!("Processing segment {}: frames {}..{} using clip '{}'",
+                   i, frames.start, frames.end, clip.name);
 
-            debug!("Processing segment {}: {:.2}s-{:.2}s using clip '{}' ({:.2}s)",
-                   i, cut_time, segment_end, clip.name, segment_duration);
+            let overrides = timeline.overrides.get(i).cloned().unwrap_or_default();
 
             let segment = self.process_segment_smooth(
                 clip,
-                cut_time,
-                segment_end,
-                segment_duration,
+                i,
+                frames,
                 style,
                 style_config,
+                &overrides,
             ).await?;
 
-            processed_segments.push(segment);
+            on_segment(segment)?;
         }
 
-        info!("Successfully processed {} segments", processed_segments.len());
-        Ok(processed_segments)
+        info!("Successfully processed {} segments", timeline.cuts.len());
+        Ok(())
     }
 
     /// **ENHANCED** segment processing for smoother motion
     async fn process_segment_smooth(
         &mut self,
         clip: &VideoClip,
-        start_time: f64,
-        end_time: f64,
-        duration: f64,
+        segment_index: usize,
+        frames: Range<u64>,
         style: &dyn Style,
         style_config: &StyleConfig,
+        overrides: &SegmentOverrides,
     ) -> Result<ProcessedSegment> {
-        let target_fps = self.target_params.fps;
+        let clock = FrameClock::new(self.target_params.fps);
+        let start_time = clock.time_of(frames.start);
+        let end_time = clock.time_of(frames.end);
+        let duration = end_time - start_time;
+        let frame_count = (frames.end - frames.start) as usize;
 
-        // **SMOOTH MOTION**: Calculate precise frame count and timing
-        let frame_count = (duration * target_fps).round() as usize;
-        let precise_frame_interval = duration / frame_count.max(1) as f64;
-
-        debug!("Segment needs {} frames at {:.1} fps (precise interval: {:.6}s)",
-               frame_count, target_fps, precise_frame_interval);
+        debug!("Segment needs {} frames at {:.3} fps ({:.3}s-{:.3}s)",
+               frame_count, clock.rate().as_f64(), start_time, end_time);
 
         // **SMOOTH EXTRACTION**: Get frames with better temporal distribution
-        let source_frames = self.extract_frames_smooth(clip, duration, frame_count).await?;
+        let source_frames = self.extract_frames_smooth(clip, duration, frame_count, overrides.source_in).await?;
+
+        // Styles can label or vary their output per shot
+        let segment_config = overrides.apply_style(style_config.clone());
+
+        // What each output frame lines up with in the render and in the music
+        let contexts: Vec<FrameContext> = frames.clone()
+            .map(|index| self.music.apply(FrameContext {
+                time: clock.time_of(index),
+                frame_index: index,
+                segment_index,
+                segment_progress: (index - frames.start) as f32 / frame_count.max(1) as f32,
+                clip_id: Some(clip.sequence_number),
+                seed: self.seed,
+                draft: self.target_params.draft,
+                ..FrameContext::default()
+            }))
+            .collect();
 
         // **ENHANCED EFFECTS**: Apply with temporal consistency
         let processed_frames = self.apply_effects_with_consistency(
             source_frames,
             style,
-            style_config,
-            frame_count,
+            &segment_config,
+            &contexts,
         ).await?;
 
-        // Generate precise frame timestamps
-        let frame_timestamps: Vec<f64> = (0..frame_count)
-            .map(|i| i as f64 * precise_frame_interval)
+        // Timestamps within the segment, on the global frame grid
+        let frame_timestamps: Vec<f64> = (0..frame_count as u64)
+            .map(|i| clock.time_of(i))
             .collect();
 
         Ok(ProcessedSegment {
+            segment_index,
             start_time,
             end_time,
             clip_id: clip.sequence_number,
@@ -135,16 +218,15 @@ impl VideoProcessor {
         clip: &VideoClip,
         duration: f64,
         frame_count: usize,
+        source_in: Option<f64>,
     ) -> Result<Vec<Frame>> {
-        let path_str = clip.path.display().to_string();
-
         // Load metadata to understand the clip
         let metadata = self.loader.load_metadata(&clip.path)?;
         debug!("Clip metadata: {:.1}s, {:.1} fps, {}x{}",
                metadata.duration, metadata.fps, metadata.width, metadata.height);
 
         // **SMOOTH SAMPLING**: Calculate optimal timestamps for natural motion
-        let timestamps = self.calculate_smooth_timestamps(&metadata, duration, frame_count);
+        let timestamps = self.calculate_smooth_timestamps(&metadata, duration, frame_count, source_in);
 
         debug!("Extracting {} frames with smooth sampling from clip: {}", 
                timestamps.len(), clip.name);
@@ -155,10 +237,11 @@ impl VideoProcessor {
         // **ENSURE CONSISTENT SIZING**: Resize all frames to target resolution
         let mut consistent_frames = Vec::with_capacity(frames.len());
         let target_size = self.target_params.resolution;
+        let focus = self.focus_track(&frames);
 
-        for frame in frames {
+        for (frame, focus) in frames.into_iter().zip(focus) {
             let resized_frame = if frame.width() != target_size.0 || frame.height() != target_size.1 {
-                self.resize_frame_smooth(&frame, target_size)?
+                self.resize_frame_smooth(&frame, target_size, focus)?
             } else {
                 frame
             };
@@ -168,30 +251,37 @@ impl VideoProcessor {
         Ok(consistent_frames)
     }
 
+    /// Default in-point for a segment: the middle of clips long enough to cover it, else the start
+    pub fn source_in_point(clip_duration: f64, segment_duration: f64) -> f64 {
+        ((clip_duration - segment_duration) / 2.0).max(0.0)
+    }
+
     /// **SMOOTH TIMESTAMP CALCULATION** for natural motion
     fn calculate_smooth_timestamps(
         &self,
         metadata: &VideoMetadata,
         segment_duration: f64,
         frame_count: usize,
+        source_in: Option<f64>,
     ) -> Vec<f64> {
         let clip_duration = metadata.duration;
+        let start_offset = source_in
+            .unwrap_or_else(|| Self::source_in_point(clip_duration, segment_duration))
+            .clamp(0.0, clip_duration.max(0.0));
 
-        if clip_duration >= segment_duration {
-            // **STRATEGY 1**: Clip is longer - sample from the middle for stability
-            let start_offset = (clip_duration - segment_duration) / 2.0;
-
+        if start_offset + segment_duration <= clip_duration {
+            // **STRATEGY 1**: Clip covers the segment - play straight through from the in-point
             (0..frame_count)
                 .map(|i| {
                     start_offset + (i as f64 * segment_duration) / frame_count.max(1) as f64
                 })
                 .collect()
         } else {
-            // **STRATEGY 2**: Clip is shorter - use smooth looping
+            // **STRATEGY 2**: Clip runs out - use smooth looping
             (0..frame_count)
                 .map(|i| {
                     let relative_time = (i as f64) / frame_count.max(1) as f64;
-                    let absolute_time = relative_time * segment_duration;
+                    let absolute_time = start_offset + relative_time * segment_duration;
 
                     // **SMOOTH LOOPING**: Avoid jumps at loop boundaries
                     let mut loop_position = absolute_time % clip_duration;
@@ -209,19 +299,29 @@ impl VideoProcessor {
         }
     }
 
-    /// **SMOOTH RESIZING** with quality preservation
-    fn resize_frame_smooth(&self, frame: &Frame, target_size: (u32, u32)) -> Result<Frame> {
+    /// **SMOOTH RESIZING** into the target box using the configured fit mode
+    fn resize_frame_smooth(&self, frame: &Frame, target_size: (u32, u32), focus: (f32, f32)) -> Result<Frame> {
         use image::imageops::FilterType;
 
-        // Use high-quality Lanczos3 filter for smooth resizing
-        let resized = image::imageops::resize(
-            frame.as_image(),
-            target_size.0,
-            target_size.1,
-            FilterType::Lanczos3,
-        );
+        // Use high-quality Lanczos3 filter for smooth resizing, bilinear for drafts
+        let filter = if self.target_params.draft { FilterType::Triangle } else { FilterType::Lanczos3 };
+
+        // Anamorphic rasters are fitted at their display shape, then squeezed into the stored pixels
+        let display_size = self.target_params.display_size();
+        let fitted = reframe::fit_frame(frame, display_size, self.target_params.fit_mode, focus, filter);
+        if display_size == target_size {
+            return Ok(fitted);
+        }
+
+        Ok(Frame::new(image::imageops::resize(fitted.as_image(), target_size.0, target_size.1, filter)))
+    }
 
-        Ok(Frame::new(resized))
+    /// Crop focus for each frame: follows the subject for smart crops, centered otherwise
+    fn focus_track(&self, frames: &[Frame]) -> Vec<(f32, f32)> {
+        match self.target_params.fit_mode {
+            FitMode::SmartCrop => saliency::focus_track(frames),
+            _ => vec![(0.5, 0.5); frames.len()],
+        }
     }
 
     /// **ENHANCED EFFECTS** with temporal consistency
@@ -230,37 +330,24 @@ impl VideoProcessor {
         mut frames: Vec<Frame>,
         style: &dyn Style,
         style_config: &StyleConfig,
-        frame_count: usize,
+        contexts: &[FrameContext],
     ) -> Result<Vec<Frame>> {
         debug!("Applying {} effects to {} frames with temporal consistency", 
                style.name(), frames.len());
 
         // **TEMPORAL CONSISTENCY**: Create variation that changes smoothly over time
-        frames.par_iter_mut().enumerate().try_for_each(|(i, frame)| {
+        frames.par_iter_mut().zip(contexts).try_for_each(|(frame, context)| {
             // Create frame-specific config with temporal variation
             let mut frame_config = style_config.clone();
 
             // **SMOOTH VARIATION**: Slowly varying parameters for natural feel
-            let time_factor = i as f32 / frame_count.max(1) as f32;
-            let slow_wave = (time_factor * std::f32::consts::PI * 0.5).sin() * 0.2;
+            let slow_wave = (context.segment_progress * std::f32::consts::PI * 0.5).sin() * 0.2;
 
             // Vary intensity slightly over time to avoid static look
             frame_config.intensity = (style_config.intensity + slow_wave * 0.3).clamp(0.0, 1.0);
 
-            // For VHS effects, add subtle temporal variation
-            if style.name() == "vhs" {
-                // Vary tracking errors over time
-                let tracking_base = style_config.get_f32_or("tracking_error", 0.5);
-                let tracking_variation = (time_factor * std::f32::consts::PI * 2.0).sin() * 0.1;
-                frame_config = frame_config.set("tracking_error", tracking_base + tracking_variation);
-
-                // Vary noise slightly
-                let noise_base = style_config.get_f32_or("noise_level", 0.6);
-                let noise_variation = (time_factor * std::f32::consts::PI * 3.0).sin() * 0.05;
-                frame_config = frame_config.set("noise_level", noise_base + noise_variation);
-            }
-
-            style.apply_effect(frame, &frame_config)
+            // Styles vary anything else themselves from the frame context
+            style.apply_effect(frame, &frame_config, context)
                 .map_err(|e| VideoError::FrameProcessingFailed {
                     reason: format!("Effect application failed: {}", e),
                 })
@@ -272,10 +359,11 @@ impl VideoProcessor {
     /// Resize frames to match target resolution
     pub fn resize_frames(&self, frames: &mut [Frame]) -> Result<()> {
         let target_resolution = self.target_params.resolution;
+        let focus = self.focus_track(frames);
 
-        for frame in frames.iter_mut() {
+        for (frame, focus) in frames.iter_mut().zip(focus) {
             if frame.width() != target_resolution.0 || frame.height() != target_resolution.1 {
-                *frame = self.resize_frame_smooth(frame, target_resolution)?;
+                *frame = self.resize_frame_smooth(frame, target_resolution, focus)?;
             }
         }
 
@@ -291,7 +379,7 @@ impl VideoProcessor {
         ProcessingStats {
             cached_clips,
             total_cached_frames,
-            target_fps: self.target_params.fps,
+            target_fps: self.target_params.fps.as_f64(),
             target_resolution: self.target_params.resolution,
         }
     }
@@ -0,0 +1,161 @@
+//! Fitting source frames into the output frame
+//!
+//! Works out which region of a source frame is kept and where it lands in the
+//! output, then resamples only that region. Crops are placed around a focus
+//! point given in normalized source coordinates, so the same geometry serves
+//! both centered and content-aware cropping.
+
+use image::imageops::{self, FilterType};
+
+use crate::video::types::{FitMode, Frame};
+
+/// Axis-aligned pixel rectangle
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Rect {
+    pub x: u32,
+    pub y: u32,
+    pub width: u32,
+    pub height: u32,
+}
+
+/// Scale applied to the source for a given fit mode
+fn fit_scale(mode: FitMode, source: (u32, u32), target: (u32, u32)) -> f64 {
+    let scale_x = target.0 as f64 / source.0 as f64;
+    let scale_y = target.1 as f64 / source.1 as f64;
+
+    match mode {
+        FitMode::Letterbox | FitMode::Pillarbox => scale_x.min(scale_y),
+        FitMode::CenterCrop | FitMode::SmartCrop => scale_x.max(scale_y),
+    }
+}
+
+/// Kept source span and its placement along one axis
+///
+/// Returns `(source_offset, source_len, target_offset, target_len)`.
+fn fit_axis(source: u32, target: u32, scale: f64, focus: f32) -> (u32, u32, u32, u32) {
+    let scaled = source as f64 * scale;
+
+    if scaled <= target as f64 {
+        // Everything fits: center it and leave bars either side
+        let len = (scaled.round() as u32).clamp(1, target);
+        (0, source, (target - len) / 2, len)
+    } else {
+        // Overflow: keep a window around the focus point
+        let visible = (target as f64 / scale).round().clamp(1.0, source as f64);
+        let offset = (focus as f64 * source as f64 - visible / 2.0).clamp(0.0, source as f64 - visible);
+        (offset.round() as u32, visible as u32, 0, target)
+    }
+}
+
+/// Source region kept and the output region it is drawn into
+///
+/// `focus` is the point to keep in view when cropping, as fractions of the
+/// source width and height; `(0.5, 0.5)` crops evenly from both sides.
+pub fn fit_rects(mode: FitMode, source: (u32, u32), target: (u32, u32), focus: (f32, f32)) -> (Rect, Rect) {
+    let scale = fit_scale(mode, source, target);
+    let (sx, sw, tx, tw) = fit_axis(source.0, target.0, scale, focus.0);
+    let (sy, sh, ty, th) = fit_axis(source.1, target.1, scale, focus.1);
+
+    (
+        Rect { x: sx, y: sy, width: sw, height: sh },
+        Rect { x: tx, y: ty, width: tw, height: th },
+    )
+}
+
+/// Size to decode a source at so it still covers `target` after fitting
+///
+/// Keeps the source aspect ratio and never upscales; the final resample to
+/// the output happens in `fit_frame`.
+pub fn decode_size(source: (u32, u32), target: (u32, u32)) -> (u32, u32) {
+    let scale = (target.0 as f64 / source.0 as f64)
+        .max(target.1 as f64 / source.1 as f64)
+        .min(1.0);
+
+    (
+        ((source.0 as f64 * scale).round() as u32).max(1),
+        ((source.1 as f64 * scale).round() as u32).max(1),
+    )
+}
+
+/// Resample a frame into the target size using the given fit mode
+///
+/// Uncovered areas are left black.
+pub fn fit_frame(frame: &Frame, target: (u32, u32), mode: FitMode, focus: (f32, f32), filter: FilterType) -> Frame {
+    let (src, dst) = fit_rects(mode, (frame.width(), frame.height()), target, focus);
+
+    let region = imageops::crop_imm(frame.as_image(), src.x, src.y, src.width, src.height).to_image();
+    let scaled = imageops::resize(&region, dst.width, dst.height, filter);
+
+    if dst.width == target.0 && dst.height == target.1 {
+        return Frame::new(scaled);
+    }
+
+    let mut canvas = Frame::new_black(target.0, target.1);
+    imageops::replace(canvas.as_image_mut(), &scaled, dst.x as i64, dst.y as i64);
+    canvas
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_center_crop_landscape_to_vertical() {
+        let (src, dst) = fit_rects(FitMode::CenterCrop, (1920, 1080), (1080, 1920), (0.5, 0.5));
+
+        // Full height kept, a 9:16 window cut from the middle
+        assert_eq!(dst, Rect { x: 0, y: 0, width: 1080, height: 1920 });
+        assert_eq!(src.height, 1080);
+        assert_eq!(src.width, 608);
+        assert_eq!(src.x, (1920 - 608) / 2);
+    }
+
+    #[test]
+    fn test_letterbox_and_pillarbox_add_bars() {
+        // 16:9 into 4:3: matching width leaves bars above and below
+        let (src, dst) = fit_rects(FitMode::Letterbox, (1920, 1080), (640, 480), (0.5, 0.5));
+        assert_eq!(src, Rect { x: 0, y: 0, width: 1920, height: 1080 });
+        assert_eq!(dst, Rect { x: 0, y: 60, width: 640, height: 360 });
+
+        // Vertical into 16:9: matching height leaves bars at the sides
+        let (src, dst) = fit_rects(FitMode::Pillarbox, (1080, 1920), (1920, 1080), (0.5, 0.5));
+        assert_eq!(src, Rect { x: 0, y: 0, width: 1080, height: 1920 });
+        assert_eq!(dst.height, 1080);
+        assert_eq!(dst.width, 608);
+        assert_eq!(dst.x, (1920 - 608) / 2);
+    }
+
+    #[test]
+    fn test_boxing_always_shows_the_whole_frame() {
+        // Portrait into 16:9 in letterbox mode: full height, bars at the sides
+        let (src, dst) = fit_rects(FitMode::Letterbox, (1080, 1920), (1920, 1080), (0.5, 0.5));
+        assert_eq!(src, Rect { x: 0, y: 0, width: 1080, height: 1920 });
+        assert_eq!(dst, Rect { x: (1920 - 608) / 2, y: 0, width: 608, height: 1080 });
+
+        // Landscape into 9:16 in pillarbox mode: full width, bars above and below
+        let (src, dst) = fit_rects(FitMode::Pillarbox, (1920, 1080), (1080, 1920), (0.5, 0.5));
+        assert_eq!(src, Rect { x: 0, y: 0, width: 1920, height: 1080 });
+        assert_eq!(dst, Rect { x: 0, y: (1920 - 608) / 2, width: 1080, height: 608 });
+    }
+
+    #[test]
+    fn test_decode_size_covers_target_without_upscaling() {
+        assert_eq!(decode_size((3840, 2160), (1280, 720)), (1280, 720));
+        assert_eq!(decode_size((3840, 2160), (1080, 1920)), (3413, 1920));
+        assert_eq!(decode_size((640, 360), (1920, 1080)), (640, 360));
+    }
+
+    #[test]
+    fn test_fit_frame_keeps_aspect() {
+        let frame = Frame::new_filled(160, 90, [200, 100, 50]);
+
+        let boxed = fit_frame(&frame, (120, 120), FitMode::Letterbox, (0.5, 0.5), FilterType::Lanczos3);
+        assert_eq!((boxed.width(), boxed.height()), (120, 120));
+        assert_eq!(boxed.get_pixel(60, 2), [0, 0, 0]);
+        assert_eq!(boxed.get_pixel(60, 60), [200, 100, 50]);
+
+        let cropped = fit_frame(&frame, (90, 160), FitMode::CenterCrop, (0.5, 0.5), FilterType::Triangle);
+        assert_eq!((cropped.width(), cropped.height()), (90, 160));
+        assert_eq!(cropped.get_pixel(45, 2), [200, 100, 50]);
+    }
+}
@@ -0,0 +1,238 @@
+//! Content-aware crop placement
+//!
+//! Estimates where the interesting part of each frame is from three cheap
+//! cues — edge density, motion against the previous frame and skin tones —
+//! and smooths that point over a segment so a smart crop follows its subject
+//! without jitter.
+
+use crate::video::types::Frame;
+
+/// Relative pull of each cue on the focus point
+const EDGE_WEIGHT: f64 = 1.0;
+const MOTION_WEIGHT: f64 = 1.5;
+const SKIN_WEIGHT: f64 = 1.0;
+
+/// Per-pixel luma change below this is treated as compression noise
+const MOTION_FLOOR: f32 = 12.0;
+
+/// Fraction of the remaining distance the smoothed focus moves each frame
+const SMOOTHING: f32 = 0.15;
+
+/// Samples per frame along the longer side
+const GRID_SIZE: u32 = 128;
+
+/// Weighted centroid accumulator for one cue
+#[derive(Default)]
+struct Centroid {
+    mass: f64,
+    x: f64,
+    y: f64,
+}
+
+impl Centroid {
+    fn add(&mut self, weight: f64, x: u32, y: u32) {
+        self.mass += weight;
+        self.x += weight * x as f64;
+        self.y += weight * y as f64;
+    }
+
+    /// Centroid in pixels, if the cue found anything
+    fn point(&self) -> Option<(f64, f64)> {
+        (self.mass > 1e-3).then(|| (self.x / self.mass, self.y / self.mass))
+    }
+}
+
+fn luma(pixel: [u8; 3]) -> f32 {
+    0.299 * pixel[0] as f32 + 0.587 * pixel[1] as f32 + 0.114 * pixel[2] as f32
+}
+
+/// Classic RGB skin-tone rule (Kovac et al.), good enough to find faces and hands
+fn is_skin([r, g, b]: [u8; 3]) -> bool {
+    let max = r.max(g).max(b);
+    let min = r.min(g).min(b);
+
+    r > 95 && g > 40 && b > 20
+        && max - min > 15
+        && r.abs_diff(g) > 15
+        && r > g && r > b
+}
+
+/// Most salient point of a frame, as fractions of its width and height
+///
+/// Each cue contributes its own centroid, so a cue with many weak responses
+/// can't drown out one with a few strong ones. `previous` enables the motion
+/// cue when it has the same size. Frames with no cues return the center.
+pub fn focus_point(frame: &Frame, previous: Option<&Frame>) -> (f32, f32) {
+    let (width, height) = (frame.width(), frame.height());
+    if width < 3 || height < 3 {
+        return (0.5, 0.5);
+    }
+
+    let previous = previous.filter(|p| p.width() == width && p.height() == height);
+    let step = (width.max(height) / GRID_SIZE).max(1);
+
+    let mut edges = Centroid::default();
+    let mut motion = Centroid::default();
+    let mut skin = Centroid::default();
+
+    for y in (step..height - step).step_by(step as usize) {
+        for x in (step..width - step).step_by(step as usize) {
+            let pixel = frame.get_pixel(x, y);
+            let center = luma(pixel);
+
+            let gx = luma(frame.get_pixel(x + step, y)) - luma(frame.get_pixel(x - step, y));
+            let gy = luma(frame.get_pixel(x, y + step)) - luma(frame.get_pixel(x, y - step));
+            edges.add((gx * gx + gy * gy).sqrt() as f64, x, y);
+
+            if let Some(previous) = previous {
+                let change = (center - luma(previous.get_pixel(x, y))).abs();
+                if change > MOTION_FLOOR {
+                    motion.add(change as f64, x, y);
+                }
+            }
+
+            if is_skin(pixel) {
+                skin.add(1.0, x, y);
+            }
+        }
+    }
+
+    let cues = [
+        (edges.point(), EDGE_WEIGHT),
+        (motion.point(), MOTION_WEIGHT),
+        (skin.point(), SKIN_WEIGHT),
+    ];
+
+    let (mut total, mut fx, mut fy) = (0.0, 0.0, 0.0);
+    for (point, weight) in cues {
+        if let Some((x, y)) = point {
+            total += weight;
+            fx += weight * x;
+            fy += weight * y;
+        }
+    }
+
+    if total == 0.0 {
+        return (0.5, 0.5);
+    }
+
+    ((fx / total / width as f64) as f32, (fy / total / height as f64) as f32)
+}
+
+/// Smooth a focus path with a zero-lag exponential filter
+///
+/// Runs forward then backward so the crop starts moving before the subject
+/// does rather than trailing behind it.
+pub fn smooth_track(points: &[(f32, f32)]) -> Vec<(f32, f32)> {
+    let mut smoothed = points.to_vec();
+
+    for i in 1..smoothed.len() {
+        let (px, py) = smoothed[i - 1];
+        let (x, y) = smoothed[i];
+        smoothed[i] = (px + (x - px) * SMOOTHING, py + (y - py) * SMOOTHING);
+    }
+
+    for i in (0..smoothed.len().saturating_sub(1)).rev() {
+        let (nx, ny) = smoothed[i + 1];
+        let (x, y) = smoothed[i];
+        smoothed[i] = (nx + (x - nx) * SMOOTHING, ny + (y - ny) * SMOOTHING);
+    }
+
+    smoothed
+}
+
+/// Smoothed focus point for every frame of a segment
+pub fn focus_track(frames: &[Frame]) -> Vec<(f32, f32)> {
+    let raw: Vec<(f32, f32)> = frames
+        .iter()
+        .enumerate()
+        .map(|(i, frame)| focus_point(frame, i.checked_sub(1).map(|p| &frames[p])))
+        .collect();
+
+    smooth_track(&raw)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::video::reframe::fit_rects;
+    use crate::video::types::FitMode;
+
+    /// Flat frame with a checkerboard patch whose left edge is at `x0`
+    fn frame_with_detail(x0: u32) -> Frame {
+        let mut frame = Frame::new_filled(200, 100, [40, 40, 40]);
+        for y in 30..70 {
+            for x in x0..x0 + 40 {
+                let value = if (x / 4 + y / 4) % 2 == 0 { 255 } else { 0 };
+                frame.set_pixel(x, y, [value, value, value]);
+            }
+        }
+        frame
+    }
+
+    #[test]
+    fn test_focus_finds_detail() {
+        let (fx, fy) = focus_point(&frame_with_detail(150), None);
+        assert!(fx > 0.7, "focus x = {}", fx);
+        assert!((fy - 0.5).abs() < 0.1, "focus y = {}", fy);
+
+        assert_eq!(focus_point(&Frame::new_black(200, 100), None), (0.5, 0.5));
+
+        // Smart crop follows the detail instead of the center
+        let (src, _) = fit_rects(FitMode::SmartCrop, (200, 100), (50, 100), (fx, fy));
+        assert!(src.x > 100);
+    }
+
+    #[test]
+    fn test_skin_pulls_focus() {
+        let mut frame = Frame::new_filled(200, 100, [30, 60, 120]);
+        for y in 20..60 {
+            for x in 10..40 {
+                frame.set_pixel(x, y, [220, 170, 140]);
+            }
+        }
+
+        // The patch's edges and skin tone agree on the left side
+        let (fx, _) = focus_point(&frame, None);
+        assert!(fx < 0.3, "focus x = {}", fx);
+        assert!(is_skin([220, 170, 140]));
+        assert!(!is_skin([30, 60, 120]));
+    }
+
+    #[test]
+    fn test_motion_outweighs_static_detail() {
+        // Static detail on the left, a bright block appears on the right
+        let previous = frame_with_detail(10);
+        let mut current = previous.clone();
+        for y in 40..60 {
+            for x in 160..180 {
+                current.set_pixel(x, y, [250, 250, 250]);
+            }
+        }
+
+        let (still, _) = focus_point(&current, None);
+        let (moving, _) = focus_point(&current, Some(&previous));
+        assert!(moving > still + 0.1, "still {} moving {}", still, moving);
+    }
+
+    #[test]
+    fn test_track_is_smooth() {
+        // Subject jumps across the frame halfway through
+        let frames: Vec<Frame> = (0..40)
+            .map(|i| frame_with_detail(if i < 20 { 10 } else { 150 }))
+            .collect();
+
+        let track = focus_track(&frames);
+        assert_eq!(track.len(), frames.len());
+
+        let max_step = track
+            .windows(2)
+            .map(|pair| (pair[1].0 - pair[0].0).abs())
+            .fold(0.0f32, f32::max);
+        assert!(max_step < 0.15, "largest jump {}", max_step);
+
+        // It still ends up on each subject
+        assert!(track[0].0 < 0.35);
+        assert!(track[39].0 > 0.65);
+    }
+}
@@ -0,0 +1,472 @@
+//! Transitions blended across the frames around each cut
+//!
+//! A transition of duration `d` is centered on the cut: the last `d/2` seconds
+//! of the outgoing segment and the first `d/2` seconds of the incoming segment
+//! are re-rendered, so the cut itself stays on the beat and the total frame
+//! count is unchanged.
+//!
+//! No footage is decoded beyond either side of the cut. Before the cut the
+//! incoming clip is held on its first frame, and after it the outgoing clip is
+//! held on its last frame, so the clip being blended in or out is a freeze
+//! frame. The transition window is taken out of both segments rather than
+//! overlapping extra footage.
+
+use serde::{Deserialize, Serialize};
+
+use crate::styles::noise::{hash, hash_unit};
+use crate::video::processor::ProcessedSegment;
+use crate::video::types::Frame;
+
+/// Salt for the tape rewind tearing pattern
+const REWIND_SALT: u64 = 21;
+
+/// How one segment hands over to the next
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TransitionType {
+    /// Hard cut with no blending
+    Cut,
+
+    /// Linear blend between the two clips, each frozen while it is off its own side of the cut
+    #[default]
+    Crossfade,
+
+    /// Fade the outgoing clip to black, then fade the incoming clip up
+    ///
+    /// Each clip only shows on its own side of the cut, so nothing is frozen.
+    DipToBlack,
+
+    /// VHS rewind glitch: vertical roll, tearing and snow peaking at the cut
+    ///
+    /// The clip from the other side of the cut shows through as a freeze frame.
+    TapeRewind,
+
+    /// Fast horizontal pan with motion blur, as if the camera whipped to the next shot
+    ///
+    /// The clip from the other side of the cut pans in or out as a freeze frame.
+    WhipPan,
+}
+
+impl TransitionType {
+    /// All available transitions
+    pub const ALL: [TransitionType; 5] = [
+        TransitionType::Cut,
+        TransitionType::Crossfade,
+        TransitionType::DipToBlack,
+        TransitionType::TapeRewind,
+        TransitionType::WhipPan,
+    ];
+
+    /// Parse a transition from its name
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.to_lowercase().replace('-', "_").as_str() {
+            "cut" | "none" => Some(Self::Cut),
+            "crossfade" | "dissolve" => Some(Self::Crossfade),
+            "dip_to_black" | "fade" => Some(Self::DipToBlack),
+            "tape_rewind" | "rewind" => Some(Self::TapeRewind),
+            "whip_pan" | "whip" => Some(Self::WhipPan),
+            _ => None,
+        }
+    }
+
+    /// Canonical name of the transition
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Cut => "cut",
+            Self::Crossfade => "crossfade",
+            Self::DipToBlack => "dip_to_black",
+            Self::TapeRewind => "tape_rewind",
+            Self::WhipPan => "whip_pan",
+        }
+    }
+
+    /// Render one transition frame
+    ///
+    /// `progress` runs from 0.0 (fully outgoing) to 1.0 (fully incoming), with
+    /// the cut at 0.5.
+    pub fn render(&self, outgoing: &Frame, incoming: &Frame, progress: f32) -> Frame {
+        let progress = progress.clamp(0.0, 1.0);
+
+        match self {
+            Self::Cut => {
+                if progress < 0.5 { outgoing.clone() } else { incoming.clone() }
+            }
+            Self::Crossfade => blend(outgoing, incoming, progress),
+            Self::DipToBlack => {
+                if progress < 0.5 {
+                    scale_brightness(outgoing, 1.0 - progress * 2.0)
+                } else {
+                    scale_brightness(incoming, progress * 2.0 - 1.0)
+                }
+            }
+            Self::TapeRewind => tape_rewind(outgoing, incoming, progress),
+            Self::WhipPan => whip_pan(outgoing, incoming, progress),
+        }
+    }
+}
+
+/// Frames each side of a cut covered by a transition of `duration` seconds
+pub fn transition_frames(duration: f64, fps: f64) -> usize {
+    (duration * fps / 2.0).round() as usize
+}
+
+/// Apply transitions between consecutive segments in place
+///
+/// `transitions[i]` is the transition into segment `i`; the first entry is
+/// ignored. Missing entries fall back to `default`.
+pub fn apply_transitions(
+    segments: &mut [ProcessedSegment],
+    transitions: &[TransitionType],
+    default: TransitionType,
+    duration: f64,
+    fps: f64,
+) {
+    let half_frames = transition_frames(duration, fps);
+
+    for index in 1..segments.len() {
+        let transition = transitions.get(index).copied().unwrap_or(default);
+        let (before, after) = segments.split_at_mut(index);
+        blend_segments(&mut before[index - 1], &mut after[0], transition, half_frames);
+    }
+}
+
+/// Apply one transition across the cut between two adjacent segments
+///
+/// Covers up to `half_frames` on each side, but never more than half of
+/// either segment.
+pub fn blend_segments(
+    outgoing: &mut ProcessedSegment,
+    incoming: &mut ProcessedSegment,
+    transition: TransitionType,
+    half_frames: usize,
+) {
+    if transition == TransitionType::Cut {
+        return;
+    }
+
+    let outgoing = &mut outgoing.frames;
+    let incoming = &mut incoming.frames;
+
+    let tail = half_frames.min(outgoing.len() / 2);
+    let head = half_frames.min(incoming.len() / 2);
+    if tail == 0 || head == 0 {
+        return;
+    }
+
+    let out_start = outgoing.len() - tail;
+    blend_frames(&mut outgoing[out_start..], &mut incoming[..head], transition);
+}
+
+/// Blend the tail of one segment into the head of the next
+///
+/// The other clip on each side of the cut is held on the frame nearest the
+/// cut: the incoming clip's first frame, or the outgoing clip's last.
+pub fn blend_frames(outgoing: &mut [Frame], incoming: &mut [Frame], transition: TransitionType) {
+    let (Some(last_out), Some(first_in)) = (outgoing.last().cloned(), incoming.first().cloned()) else {
+        return;
+    };
+
+    if last_out.width() != first_in.width() || last_out.height() != first_in.height() {
+        return;
+    }
+
+    let total = (outgoing.len() + incoming.len()) as f32;
+
+    // Before the cut the incoming clip is held on its first frame...
+    for (k, frame) in outgoing.iter_mut().enumerate() {
+        let progress = (k as f32 + 0.5) / total;
+        *frame = transition.render(frame, &first_in, progress);
+    }
+
+    // ...and after the cut the outgoing clip is held on its last frame
+    let offset = outgoing.len();
+    for (j, frame) in incoming.iter_mut().enumerate() {
+        let progress = ((offset + j) as f32 + 0.5) / total;
+        *frame = transition.render(&last_out, frame, progress);
+    }
+}
+
+fn blend(a: &Frame, b: &Frame, t: f32) -> Frame {
+    let data = a
+        .as_image()
+        .as_raw()
+        .iter()
+        .zip(b.as_image().as_raw().iter())
+        .map(|(&x, &y)| (x as f32 + (y as f32 - x as f32) * t).round() as u8)
+        .collect();
+
+    Frame::from_rgb_bytes(a.width(), a.height(), data).unwrap_or_else(|| a.clone())
+}
+
+fn scale_brightness(frame: &Frame, factor: f32) -> Frame {
+    let data = frame
+        .as_image()
+        .as_raw()
+        .iter()
+        .map(|&c| (c as f32 * factor.clamp(0.0, 1.0)).round() as u8)
+        .collect();
+
+    Frame::from_rgb_bytes(frame.width(), frame.height(), data).unwrap_or_else(|| frame.clone())
+}
+
+/// Strength of a transition effect that peaks at the cut
+fn peak_at_cut(progress: f32) -> f32 {
+    1.0 - (progress * 2.0 - 1.0).abs()
+}
+
+fn tape_rewind(outgoing: &Frame, incoming: &Frame, progress: f32) -> Frame {
+    let source = if progress < 0.5 { outgoing } else { incoming };
+    let strength = peak_at_cut(progress);
+    let width = source.width();
+    let height = source.height();
+
+    // The picture rolls vertically faster as the tape speeds up towards the cut
+    let roll = (strength * strength * height as f32 * 0.35) as u32;
+    let step = (progress * 1000.0) as u64;
+    let band_height = (height as u64 / 60).max(6);
+
+    let mut result = Frame::new_black(width, height);
+
+    for y in 0..height {
+        let source_y = (y + roll) % height;
+
+        // Rewind tearing: rows slide sideways, more so in random bands
+        let band = hash_unit(REWIND_SALT, step, y as u64 / band_height);
+        let tear = if band < strength * 0.5 { band * 2.0 - 0.5 } else { 0.0 };
+        let wave = ((y as f32 * 0.08) + progress * 40.0).sin() * 0.02;
+        let shift = ((wave + tear * 0.15) * strength * width as f32) as i64;
+
+        // A dark tracking bar crosses the picture
+        let bar_center = (progress * 3.0).fract() * height as f32;
+        let in_bar = (y as f32 - bar_center).abs() < height as f32 * 0.04 * strength;
+
+        for x in 0..width {
+            let source_x = (x as i64 - shift).rem_euclid(width as i64) as u32;
+            let mut pixel = source.get_pixel(source_x, source_y);
+
+            // Desaturate toward gray as the heads lose sync
+            let luma = (pixel[0] as u32 * 299 + pixel[1] as u32 * 587 + pixel[2] as u32 * 114) / 1000;
+            for channel in pixel.iter_mut() {
+                *channel = (*channel as f32 + (luma as f32 - *channel as f32) * strength * 0.6) as u8;
+            }
+
+            if in_bar {
+                pixel = pixel.map(|c| (c as f32 * 0.3) as u8);
+            }
+
+            // Snow
+            let noise = hash(REWIND_SALT + 1, step, (y as u64) << 32 | x as u64);
+            if ((noise >> 40) as f32 / (1u64 << 24) as f32) < strength * 0.12 {
+                let snow = 160 + (noise & 0x5F) as u8;
+                pixel = [snow, snow, snow];
+            }
+
+            result.set_pixel(x, y, pixel);
+        }
+    }
+
+    result
+}
+
+fn whip_pan(outgoing: &Frame, incoming: &Frame, progress: f32) -> Frame {
+    let width = outgoing.width();
+    let height = outgoing.height();
+
+    // Ease in towards the cut on the outgoing side, ease out on the incoming side
+    let (source, offset) = if progress < 0.5 {
+        let t = progress * 2.0;
+        (outgoing, -(t * t) * 0.5)
+    } else {
+        let t = (1.0 - progress) * 2.0;
+        (incoming, t * t * 0.5)
+    };
+
+    let shift = (offset * width as f32) as i64;
+    let blur_radius = (peak_at_cut(progress).powi(2) * width as f32 * 0.06) as i64;
+
+    let mut result = Frame::new_black(width, height);
+    let raw = source.as_image().as_raw();
+    let stride = width as usize * 3;
+
+    for y in 0..height as usize {
+        let row = &raw[y * stride..(y + 1) * stride];
+        let sample = |x: i64| -> [u32; 3] {
+            let x = x.rem_euclid(width as i64) as usize * 3;
+            [row[x] as u32, row[x + 1] as u32, row[x + 2] as u32]
+        };
+
+        // Horizontal motion blur via running sum over the shifted row
+        let window = (2 * blur_radius + 1) as u32;
+        let mut sum = [0u32; 3];
+        for dx in -blur_radius..=blur_radius {
+            let p = sample(-shift + dx);
+            for c in 0..3 {
+                sum[c] += p[c];
+            }
+        }
+
+        for x in 0..width as i64 {
+            let pixel = [sum[0] / window, sum[1] / window, sum[2] / window].map(|c| c as u8);
+            result.set_pixel(x as u32, y as u32, pixel);
+
+            let add = sample(x + 1 - shift + blur_radius);
+            let remove = sample(x - shift - blur_radius);
+            for c in 0..3 {
+                sum[c] = sum[c] + add[c] - remove[c];
+            }
+        }
+    }
+
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn segment(frames: Vec<Frame>, start: f64, end: f64) -> ProcessedSegment {
+        let count = frames.len();
+        ProcessedSegment {
+            segment_index: 0,
+            start_time: start,
+            end_time: end,
+            clip_id: 1,
+            frames,
+            frame_timestamps: (0..count).map(|i| i as f64 / 10.0).collect(),
+        }
+    }
+
+    fn solid(count: usize, color: [u8; 3]) -> Vec<Frame> {
+        vec![Frame::new_filled(16, 8, color); count]
+    }
+
+    #[test]
+    fn test_crossfade_midpoint_is_average() {
+        let out = Frame::new_filled(4, 4, [200, 0, 100]);
+        let inc = Frame::new_filled(4, 4, [0, 200, 100]);
+
+        let mid = TransitionType::Crossfade.render(&out, &inc, 0.5);
+
+        assert_eq!(mid.get_pixel(1, 1), [100, 100, 100]);
+    }
+
+    #[test]
+    fn test_dip_to_black_is_darkest_at_cut() {
+        let out = Frame::new_filled(4, 4, [200, 200, 200]);
+        let inc = Frame::new_filled(4, 4, [100, 100, 100]);
+
+        let early = TransitionType::DipToBlack.render(&out, &inc, 0.1).get_pixel(0, 0)[0];
+        let cut = TransitionType::DipToBlack.render(&out, &inc, 0.5).get_pixel(0, 0)[0];
+        let late = TransitionType::DipToBlack.render(&out, &inc, 0.9).get_pixel(0, 0)[0];
+
+        assert_eq!(cut, 0);
+        assert!(early > cut && late > cut);
+    }
+
+    #[test]
+    fn test_blend_holds_the_frames_nearest_the_cut() {
+        let mut outgoing = vec![Frame::new_filled(4, 4, [0; 3]), Frame::new_filled(4, 4, [40; 3])];
+        let mut incoming = vec![Frame::new_filled(4, 4, [200; 3]), Frame::new_filled(4, 4, [100; 3])];
+
+        blend_frames(&mut outgoing, &mut incoming, TransitionType::Crossfade);
+
+        // Before the cut, both outgoing frames blend toward the incoming clip's first frame
+        assert_eq!(outgoing[0].get_pixel(0, 0), [25; 3]);
+        assert_eq!(outgoing[1].get_pixel(0, 0), [100; 3]);
+
+        // After the cut, the outgoing clip stays on its last frame
+        assert_eq!(incoming[0].get_pixel(0, 0), [140; 3]);
+        assert_eq!(incoming[1].get_pixel(0, 0), [93; 3]);
+    }
+
+    #[test]
+    fn test_transitions_keep_frame_count_and_cut_position() {
+        let mut segments = vec![
+            segment(solid(10, [255, 0, 0]), 0.0, 1.0),
+            segment(solid(10, [0, 0, 255]), 1.0, 2.0),
+        ];
+
+        apply_transitions(&mut segments, &[], TransitionType::Crossfade, 0.4, 10.0);
+
+        assert_eq!(segments[0].frames.len(), 10);
+        assert_eq!(segments[1].frames.len(), 10);
+
+        // Untouched away from the cut
+        assert_eq!(segments[0].frames[0].get_pixel(0, 0), [255, 0, 0]);
+        assert_eq!(segments[1].frames[9].get_pixel(0, 0), [0, 0, 255]);
+
+        // Blended either side of the cut, still mostly outgoing before it
+        let before = segments[0].frames[9].get_pixel(0, 0);
+        let after = segments[1].frames[0].get_pixel(0, 0);
+        assert!(before[2] > 0 && before[0] > before[2]);
+        assert!(after[0] > 0 && after[2] > after[0]);
+    }
+
+    #[test]
+    fn test_zero_duration_is_a_hard_cut() {
+        let mut segments = vec![
+            segment(solid(5, [255, 0, 0]), 0.0, 0.5),
+            segment(solid(5, [0, 0, 255]), 0.5, 1.0),
+        ];
+
+        apply_transitions(&mut segments, &[], TransitionType::Crossfade, 0.0, 10.0);
+
+        assert_eq!(segments[0].frames[4].get_pixel(0, 0), [255, 0, 0]);
+        assert_eq!(segments[1].frames[0].get_pixel(0, 0), [0, 0, 255]);
+    }
+
+    #[test]
+    fn test_per_cut_transition_overrides_default() {
+        let mut segments = vec![
+            segment(solid(6, [255, 0, 0]), 0.0, 0.6),
+            segment(solid(6, [0, 0, 255]), 0.6, 1.2),
+            segment(solid(6, [0, 255, 0]), 1.2, 1.8),
+        ];
+
+        let transitions = [TransitionType::Cut, TransitionType::Cut, TransitionType::DipToBlack];
+        apply_transitions(&mut segments, &transitions, TransitionType::Crossfade, 0.4, 10.0);
+
+        // First cut stays hard, second dips to black
+        assert_eq!(segments[0].frames[5].get_pixel(0, 0), [255, 0, 0]);
+        assert_eq!(segments[1].frames[0].get_pixel(0, 0), [0, 0, 255]);
+        assert!(segments[1].frames[5].get_pixel(0, 0)[2] < 255);
+        assert!(segments[2].frames[0].get_pixel(0, 0)[1] < 255);
+    }
+
+    #[test]
+    fn test_whip_pan_blurs_and_moves() {
+        let mut out = Frame::new_black(64, 4);
+        for y in 0..4 {
+            out.set_pixel(10, y, [255, 255, 255]);
+        }
+        let inc = Frame::new_black(64, 4);
+
+        let frame = TransitionType::WhipPan.render(&out, &inc, 0.4);
+
+        // The sharp line is smeared across several pixels
+        let lit = (0..64).filter(|&x| frame.get_pixel(x, 0)[0] > 0).count();
+        assert!(lit > 3);
+        assert!(frame.get_pixel(10, 0)[0] < 255);
+    }
+
+    #[test]
+    fn test_tape_rewind_is_deterministic_and_visible() {
+        let out = Frame::new_filled(32, 24, [200, 50, 50]);
+        let inc = Frame::new_filled(32, 24, [50, 50, 200]);
+
+        let a = TransitionType::TapeRewind.render(&out, &inc, 0.45);
+        let b = TransitionType::TapeRewind.render(&out, &inc, 0.45);
+
+        assert_eq!(a.to_rgb_bytes(), b.to_rgb_bytes());
+        assert_ne!(a.to_rgb_bytes(), out.to_rgb_bytes());
+    }
+
+    #[test]
+    fn test_transition_names_roundtrip() {
+        for transition in TransitionType::ALL {
+            assert_eq!(TransitionType::from_name(transition.name()), Some(transition));
+        }
+        assert_eq!(TransitionType::from_name("whip-pan"), Some(TransitionType::WhipPan));
+        assert_eq!(TransitionType::from_name("star_wipe"), None);
+    }
+}
@@ -2,6 +2,8 @@ use image::{ImageBuffer, Rgb, RgbImage};
 use serde::{Deserialize, Serialize};
 use std::path::PathBuf;
 
+use crate::video::frame_rate::FrameRate;
+
 /// Represents a single video frame
 ///
 /// This is a simple wrapper around an RGB image buffer that provides
@@ -137,6 +139,15 @@ impl VideoClip {
         Some(Self::new(path, sequence_number, name))
     }
 
+    /// Lowercase words in the clip name, used to tag clips (e.g. "crowd_high-energy")
+    pub fn tags(&self) -> Vec<String> {
+        self.name
+            .split(|c: char| c == '_' || c == '-' || c.is_whitespace())
+            .filter(|word| !word.is_empty())
+            .map(|word| word.to_lowercase())
+            .collect()
+    }
+
     /// Get the file extension
     pub fn extension(&self) -> Option<&str> {
         self.path.extension()?.to_str()
@@ -152,11 +163,78 @@ impl VideoClip {
     }
 }
 
+/// How a source frame with a different aspect ratio is fitted into the output
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FitMode {
+    /// Show the whole frame, with bars on whichever axis has room to spare
+    Letterbox,
+
+    /// Same as `Letterbox`; the bars go wherever the source needs them
+    Pillarbox,
+
+    /// Fill the output, cropping the overflow equally from both sides
+    #[default]
+    CenterCrop,
+
+    /// Fill the output, cropping around the subject and following it through the segment
+    SmartCrop,
+}
+
+impl FitMode {
+    /// Parse a fit mode from its name
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.to_lowercase().replace('-', "_").as_str() {
+            "letterbox" => Some(Self::Letterbox),
+            "pillarbox" => Some(Self::Pillarbox),
+            "center_crop" | "crop" => Some(Self::CenterCrop),
+            "smart_crop" | "smart" => Some(Self::SmartCrop),
+            _ => None,
+        }
+    }
+
+    /// Canonical name of the fit mode
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Letterbox => "letterbox",
+            Self::Pillarbox => "pillarbox",
+            Self::CenterCrop => "center_crop",
+            Self::SmartCrop => "smart_crop",
+        }
+    }
+}
+
+/// Scan type of the encoded output
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FieldOrder {
+    /// Whole frames
+    #[default]
+    Progressive,
+
+    /// Interlaced, upper field first (PAL)
+    TopFieldFirst,
+
+    /// Interlaced, lower field first (NTSC)
+    BottomFieldFirst,
+}
+
+impl FieldOrder {
+    /// Whether the output is interlaced
+    pub fn is_interlaced(&self) -> bool {
+        *self != Self::Progressive
+    }
+}
+
+fn square_pixels() -> (u32, u32) {
+    (1, 1)
+}
+
 /// Video processing parameters
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct VideoParams {
-    /// Target frame rate for output
-    pub fps: f64,
+    /// Target frame rate for output, exact for broadcast rates like 30000/1001
+    pub fps: FrameRate,
 
     /// Target resolution (width, height)
     pub resolution: (u32, u32),
@@ -166,19 +244,52 @@ pub struct VideoParams {
 
     /// Quality setting (0-100, higher is better)
     pub quality: u8,
+
+    /// How source frames are fitted into `resolution`
+    #[serde(default)]
+    pub fit_mode: FitMode,
+
+    /// Shape of one stored pixel as width:height, (1, 1) for square pixels
+    #[serde(default = "square_pixels")]
+    pub pixel_aspect: (u32, u32),
+
+    /// Progressive or interlaced output, and which field comes first
+    #[serde(default)]
+    pub field_order: FieldOrder,
+
+    /// Favour speed over quality: cheaper resampling and effects, and the fastest encoder preset
+    #[serde(default)]
+    pub draft: bool,
 }
 
 impl Default for VideoParams {
     fn default() -> Self {
         Self {
-            fps: 30.0,
+            fps: FrameRate::integer(30),
             resolution: (1920, 1080),
             codec: "h264".to_string(),
             quality: 85,
+            fit_mode: FitMode::default(),
+            pixel_aspect: square_pixels(),
+            field_order: FieldOrder::default(),
+            draft: false,
         }
     }
 }
 
+impl VideoParams {
+    /// Output size in square pixels, the shape the picture is shown at
+    ///
+    /// Equal to `resolution` unless the pixels are anamorphic, as on SD
+    /// broadcast rasters where 720x480 is shown as 640x480.
+    pub fn display_size(&self) -> (u32, u32) {
+        let (width, height) = self.resolution;
+        let (num, den) = self.pixel_aspect;
+        let width = (width as u64 * num as u64 / den.max(1) as u64) as u32;
+        (width.max(1), height)
+    }
+}
+
 /// Represents a sequence of video clips in order
 #[derive(Debug, Clone)]
 pub struct VideoSequence {